@@ -0,0 +1,285 @@
+//! # ARIA
+//!
+//! `aria` implements the ARIA block cipher (RFC 5794), the Korean national
+//! standard (KS X 1213).
+//! ARIA is a substitution-permutation network close to AES: it uses the
+//! AES S-box and its inverse together with a second S-box, and an
+//! involutive 16x16 binary matrix as diffusion layer.
+//! It has 12, 14 or 16 rounds for 128, 192 and 256-bit keys.
+
+use cipher::block::aes::{INV_SBOX, SBOX};
+use cipher::block::{xor, Block, BlockCipher};
+
+/// The key schedule constants; the order depends on the key size.
+const C: [u128; 3] = [
+    0x517cc1b727220a94fe13abe8fa9a6ee0,
+    0x6db14acc9e21c820ff28b1d5ef5de2b0,
+    0xdb92371d2126e9700324977504e8c90e,
+];
+
+/// The diffusion layer: output byte i is the XOR of the input bytes
+/// listed at index i.
+const DIFFUSION: [[usize; 7]; 16] = [
+    [3, 4, 6, 8, 9, 13, 14],
+    [2, 5, 7, 8, 9, 12, 15],
+    [1, 4, 6, 10, 11, 12, 15],
+    [0, 5, 7, 10, 11, 13, 14],
+    [0, 2, 5, 8, 11, 14, 15],
+    [1, 3, 4, 9, 10, 14, 15],
+    [0, 2, 7, 9, 10, 12, 13],
+    [1, 3, 6, 8, 11, 12, 13],
+    [0, 1, 4, 7, 10, 13, 15],
+    [0, 1, 5, 6, 11, 12, 14],
+    [2, 3, 5, 6, 8, 13, 15],
+    [2, 3, 4, 7, 9, 12, 14],
+    [1, 2, 6, 7, 9, 11, 12],
+    [0, 3, 6, 7, 8, 10, 13],
+    [0, 3, 4, 5, 9, 11, 14],
+    [1, 2, 4, 5, 8, 10, 15],
+];
+
+/// The maximum number of round keys (256-bit keys).
+const MAX_ROUND_KEYS: usize = 17;
+
+/// An ARIA block cipher.
+pub struct Aria {
+    rounds: usize,
+    ek: [Block; MAX_ROUND_KEYS],
+    dk: [Block; MAX_ROUND_KEYS],
+}
+
+// Public methods.
+impl Aria {
+    /// new creates an ARIA cipher with the given key.
+    /// The key needs to contain 16, 24 or 32 bytes.
+    pub fn new(key: &[u8]) -> Aria {
+        if key.len() != 16 && key.len() != 24 && key.len() != 32 {
+            panic!("Key length must be 16, 24 or 32 bytes.");
+        }
+
+        let mut instance = Aria {
+            rounds: 12 + (key.len() - 16) / 4,
+            ek: [[0u8; 16]; MAX_ROUND_KEYS],
+            dk: [[0u8; 16]; MAX_ROUND_KEYS],
+        };
+
+        instance.expand_key(key);
+
+        instance
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, block: &Block) -> Block {
+        crypt(block, &self.ek[..self.rounds + 1])
+    }
+
+    /// decrypt a block of data: decryption is the same as encryption, with
+    /// the decryption round keys.
+    pub fn decrypt(&self, block: &Block) -> Block {
+        crypt(block, &self.dk[..self.rounds + 1])
+    }
+}
+
+impl BlockCipher for Aria {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        self.encrypt(block)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        self.decrypt(block)
+    }
+}
+
+// Private methods.
+impl Aria {
+    /// Apply the key schedule: a 3-round Feistel network over the key
+    /// generates four words, which are rotated and mixed into the round
+    /// keys.
+    fn expand_key(&mut self, key: &[u8]) {
+        let mut k = [0u8; 32];
+        k[..key.len()].copy_from_slice(key);
+        let kl = u128::from_be_bytes(to_block(&k[..16]));
+        let kr = u128::from_be_bytes(to_block(&k[16..]));
+
+        let offset = (key.len() - 16) / 8;
+        let ck = |i: usize| C[(offset + i) % 3].to_be_bytes();
+
+        let w0 = kl;
+        let w1 = u128::from_be_bytes(fo(&w0.to_be_bytes(), &ck(0))) ^ kr;
+        let w2 = u128::from_be_bytes(fe(&w1.to_be_bytes(), &ck(1))) ^ w0;
+        let w3 = u128::from_be_bytes(fo(&w2.to_be_bytes(), &ck(2))) ^ w1;
+
+        let w = [w0, w1, w2, w3];
+        // Round keys use the rotations 19 and 31 to the right, then 61, 31
+        // and 19 to the left, in groups of four.
+        let rotations = [128 - 19, 128 - 31, 61, 31, 19];
+        for i in 0..=self.rounds {
+            let a = w[i % 4];
+            let b = w[(i + 1) % 4];
+            let ek = a ^ b.rotate_left(rotations[i / 4]);
+            self.ek[i] = ek.to_be_bytes();
+        }
+
+        self.dk[0] = self.ek[self.rounds];
+        for i in 1..self.rounds {
+            self.dk[i] = diffusion(&self.ek[self.rounds - i]);
+        }
+        self.dk[self.rounds] = self.ek[0];
+    }
+}
+
+/// crypt runs the rounds with the given round keys: odd and even round
+/// functions alternate, and the last round replaces the diffusion layer
+/// with a final key addition.
+fn crypt(block: &Block, rk: &[Block]) -> Block {
+    let rounds = rk.len() - 1;
+    let mut state = *block;
+
+    for (i, k) in rk[..rounds - 1].iter().enumerate() {
+        state = if i.is_multiple_of(2) {
+            fo(&state, k)
+        } else {
+            fe(&state, k)
+        };
+    }
+
+    let mut state = substitute(&xor(&state, &rk[rounds - 1]), false);
+    state = xor(&state, &rk[rounds]);
+
+    state
+}
+
+/// fo is the round function of odd rounds.
+fn fo(d: &Block, rk: &Block) -> Block {
+    diffusion(&substitute(&xor(d, rk), true))
+}
+
+/// fe is the round function of even rounds.
+fn fe(d: &Block, rk: &Block) -> Block {
+    diffusion(&substitute(&xor(d, rk), false))
+}
+
+/// substitute applies the substitution layer: type 1 uses the S-boxes in
+/// the order SB1, SB2, SB1⁻¹, SB2⁻¹, and type 2 uses their inverses.
+fn substitute(x: &Block, type1: bool) -> Block {
+    let mut y = [0u8; 16];
+    for i in 0..16 {
+        let b = x[i] as usize;
+        y[i] = match (i % 4, type1) {
+            (0, true) | (2, false) => SBOX[b],
+            (1, true) | (3, false) => SB2[b],
+            (2, true) | (0, false) => INV_SBOX[b],
+            _ => SB4[b],
+        };
+    }
+
+    y
+}
+
+/// diffusion applies the diffusion layer, which is an involution.
+fn diffusion(x: &Block) -> Block {
+    let mut y = [0u8; 16];
+    for i in 0..16 {
+        y[i] = DIFFUSION[i].iter().fold(0, |acc, j| acc ^ x[*j]);
+    }
+
+    y
+}
+
+fn to_block(bytes: &[u8]) -> Block {
+    let mut b = [0u8; 16];
+    b.copy_from_slice(bytes);
+    b
+}
+
+/// The second S-box.
+const SB2: [u8; 256] = [
+    0xe2, 0x4e, 0x54, 0xfc, 0x94, 0xc2, 0x4a, 0xcc, 0x62, 0x0d, 0x6a, 0x46, 0x3c, 0x4d, 0x8b, 0xd1,
+    0x5e, 0xfa, 0x64, 0xcb, 0xb4, 0x97, 0xbe, 0x2b, 0xbc, 0x77, 0x2e, 0x03, 0xd3, 0x19, 0x59, 0xc1,
+    0x1d, 0x06, 0x41, 0x6b, 0x55, 0xf0, 0x99, 0x69, 0xea, 0x9c, 0x18, 0xae, 0x63, 0xdf, 0xe7, 0xbb,
+    0x00, 0x73, 0x66, 0xfb, 0x96, 0x4c, 0x85, 0xe4, 0x3a, 0x09, 0x45, 0xaa, 0x0f, 0xee, 0x10, 0xeb,
+    0x2d, 0x7f, 0xf4, 0x29, 0xac, 0xcf, 0xad, 0x91, 0x8d, 0x78, 0xc8, 0x95, 0xf9, 0x2f, 0xce, 0xcd,
+    0x08, 0x7a, 0x88, 0x38, 0x5c, 0x83, 0x2a, 0x28, 0x47, 0xdb, 0xb8, 0xc7, 0x93, 0xa4, 0x12, 0x53,
+    0xff, 0x87, 0x0e, 0x31, 0x36, 0x21, 0x58, 0x48, 0x01, 0x8e, 0x37, 0x74, 0x32, 0xca, 0xe9, 0xb1,
+    0xb7, 0xab, 0x0c, 0xd7, 0xc4, 0x56, 0x42, 0x26, 0x07, 0x98, 0x60, 0xd9, 0xb6, 0xb9, 0x11, 0x40,
+    0xec, 0x20, 0x8c, 0xbd, 0xa0, 0xc9, 0x84, 0x04, 0x49, 0x23, 0xf1, 0x4f, 0x50, 0x1f, 0x13, 0xdc,
+    0xd8, 0xc0, 0x9e, 0x57, 0xe3, 0xc3, 0x7b, 0x65, 0x3b, 0x02, 0x8f, 0x3e, 0xe8, 0x25, 0x92, 0xe5,
+    0x15, 0xdd, 0xfd, 0x17, 0xa9, 0xbf, 0xd4, 0x9a, 0x7e, 0xc5, 0x39, 0x67, 0xfe, 0x76, 0x9d, 0x43,
+    0xa7, 0xe1, 0xd0, 0xf5, 0x68, 0xf2, 0x1b, 0x34, 0x70, 0x05, 0xa3, 0x8a, 0xd5, 0x79, 0x86, 0xa8,
+    0x30, 0xc6, 0x51, 0x4b, 0x1e, 0xa6, 0x27, 0xf6, 0x35, 0xd2, 0x6e, 0x24, 0x16, 0x82, 0x5f, 0xda,
+    0xe6, 0x75, 0xa2, 0xef, 0x2c, 0xb2, 0x1c, 0x9f, 0x5d, 0x6f, 0x80, 0x0a, 0x72, 0x44, 0x9b, 0x6c,
+    0x90, 0x0b, 0x5b, 0x33, 0x7d, 0x5a, 0x52, 0xf3, 0x61, 0xa1, 0xf7, 0xb0, 0xd6, 0x3f, 0x7c, 0x6d,
+    0xed, 0x14, 0xe0, 0xa5, 0x3d, 0x22, 0xb3, 0xf8, 0x89, 0xde, 0x71, 0x1a, 0xaf, 0xba, 0xb5, 0x81,
+];
+
+/// The inverse of the second S-box.
+const SB4: [u8; 256] = [
+    0x30, 0x68, 0x99, 0x1b, 0x87, 0xb9, 0x21, 0x78, 0x50, 0x39, 0xdb, 0xe1, 0x72, 0x09, 0x62, 0x3c,
+    0x3e, 0x7e, 0x5e, 0x8e, 0xf1, 0xa0, 0xcc, 0xa3, 0x2a, 0x1d, 0xfb, 0xb6, 0xd6, 0x20, 0xc4, 0x8d,
+    0x81, 0x65, 0xf5, 0x89, 0xcb, 0x9d, 0x77, 0xc6, 0x57, 0x43, 0x56, 0x17, 0xd4, 0x40, 0x1a, 0x4d,
+    0xc0, 0x63, 0x6c, 0xe3, 0xb7, 0xc8, 0x64, 0x6a, 0x53, 0xaa, 0x38, 0x98, 0x0c, 0xf4, 0x9b, 0xed,
+    0x7f, 0x22, 0x76, 0xaf, 0xdd, 0x3a, 0x0b, 0x58, 0x67, 0x88, 0x06, 0xc3, 0x35, 0x0d, 0x01, 0x8b,
+    0x8c, 0xc2, 0xe6, 0x5f, 0x02, 0x24, 0x75, 0x93, 0x66, 0x1e, 0xe5, 0xe2, 0x54, 0xd8, 0x10, 0xce,
+    0x7a, 0xe8, 0x08, 0x2c, 0x12, 0x97, 0x32, 0xab, 0xb4, 0x27, 0x0a, 0x23, 0xdf, 0xef, 0xca, 0xd9,
+    0xb8, 0xfa, 0xdc, 0x31, 0x6b, 0xd1, 0xad, 0x19, 0x49, 0xbd, 0x51, 0x96, 0xee, 0xe4, 0xa8, 0x41,
+    0xda, 0xff, 0xcd, 0x55, 0x86, 0x36, 0xbe, 0x61, 0x52, 0xf8, 0xbb, 0x0e, 0x82, 0x48, 0x69, 0x9a,
+    0xe0, 0x47, 0x9e, 0x5c, 0x04, 0x4b, 0x34, 0x15, 0x79, 0x26, 0xa7, 0xde, 0x29, 0xae, 0x92, 0xd7,
+    0x84, 0xe9, 0xd2, 0xba, 0x5d, 0xf3, 0xc5, 0xb0, 0xbf, 0xa4, 0x3b, 0x71, 0x44, 0x46, 0x2b, 0xfc,
+    0xeb, 0x6f, 0xd5, 0xf6, 0x14, 0xfe, 0x7c, 0x70, 0x5a, 0x7d, 0xfd, 0x2f, 0x18, 0x83, 0x16, 0xa5,
+    0x91, 0x1f, 0x05, 0x95, 0x74, 0xa9, 0xc1, 0x5b, 0x4a, 0x85, 0x6d, 0x13, 0x07, 0x4f, 0x4e, 0x45,
+    0xb2, 0x0f, 0xc9, 0x1c, 0xa6, 0xbc, 0xec, 0x73, 0x90, 0x7b, 0xcf, 0x59, 0x8f, 0xa1, 0xf9, 0x2d,
+    0xf2, 0xb1, 0x00, 0x94, 0x37, 0x9f, 0xd0, 0x2e, 0x9c, 0x6e, 0x28, 0x3f, 0x80, 0xf0, 0x3d, 0xd3,
+    0x25, 0x8a, 0xb5, 0xe7, 0x42, 0xb3, 0xc7, 0xea, 0xf7, 0x4c, 0x11, 0x33, 0x03, 0xa2, 0xac, 0x60,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::{block, hex};
+
+    fn check_vector(key: &str, plain: &str, cipher: &str) {
+        let instance = Aria::new(&hex(key));
+        assert_eq!(block(cipher), instance.encrypt(&block(plain)));
+        assert_eq!(block(plain), instance.decrypt(&block(cipher)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_invalid_key_length() {
+        Aria::new(&[0u8; 8]);
+    }
+
+    #[test]
+    fn diffusion_is_involution() {
+        let x = block("000102030405060708090a0b0c0d0e0f");
+        assert_eq!(x, diffusion(&diffusion(&x)));
+    }
+
+    #[test]
+    fn aria_128() {
+        // Test vectors of RFC 5794 appendix A.
+        check_vector(
+            "000102030405060708090a0b0c0d0e0f",
+            "00112233445566778899aabbccddeeff",
+            "d718fbd6ab644c739da95f3be6451778",
+        );
+    }
+
+    #[test]
+    fn aria_192() {
+        check_vector(
+            "000102030405060708090a0b0c0d0e0f1011121314151617",
+            "00112233445566778899aabbccddeeff",
+            "26449c1805dbe7aa25a468ce263a9e79",
+        );
+    }
+
+    #[test]
+    fn aria_256() {
+        check_vector(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "00112233445566778899aabbccddeeff",
+            "f92bd7c79fb72e2f2b8f80c1972d24fc",
+        );
+    }
+}
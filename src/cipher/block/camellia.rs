@@ -0,0 +1,394 @@
+//! # Camellia
+//!
+//! `camellia` implements the Camellia block cipher (RFC 3713), a Japanese
+//! standard (CRYPTREC) also selected by NESSIE and ISO/IEC 18033-3.
+//! Camellia is a Feistel cipher with 18 rounds for 128-bit keys and 24
+//! rounds for 192 and 256-bit keys, with key-dependent FL and FL⁻¹ layers
+//! every 6 rounds.
+
+use cipher::block::{Block, BlockCipher};
+
+/// The key schedule constants.
+const SIGMA: [u64; 6] = [
+    0xa09e667f3bcc908b,
+    0xb67ae8584caa73b2,
+    0xc6ef372fe94f82be,
+    0x54ff53a5f1d36f1c,
+    0x10e527fade682d1d,
+    0xb05688c2b3e6c1fd,
+];
+
+// Indices of the keys used by the key schedule.
+const KL: usize = 0;
+const KR: usize = 1;
+const KA: usize = 2;
+const KB: usize = 3;
+
+/// The subkeys for 128-bit keys (RFC 3713 section 2.2), as the source key
+/// and rotation of each subkey.
+/// Subkeys with an even index are the high half of the rotated key, and
+/// the ones with an odd index the low half.
+const SCHEDULE_128: Schedule = Schedule {
+    kw: [(KL, 0), (KL, 0), (KA, 111), (KA, 111)],
+    k: &[
+        (KA, 0),
+        (KA, 0),
+        (KL, 15),
+        (KL, 15),
+        (KA, 15),
+        (KA, 15),
+        (KL, 45),
+        (KL, 45),
+        (KA, 45),
+        (KL, 60),
+        (KA, 60),
+        (KA, 60),
+        (KL, 94),
+        (KL, 94),
+        (KA, 94),
+        (KA, 94),
+        (KL, 111),
+        (KL, 111),
+    ],
+    ke: &[(KA, 30), (KA, 30), (KL, 77), (KL, 77)],
+};
+
+/// The subkeys for 192 and 256-bit keys.
+const SCHEDULE_256: Schedule = Schedule {
+    kw: [(KL, 0), (KL, 0), (KB, 111), (KB, 111)],
+    k: &[
+        (KB, 0),
+        (KB, 0),
+        (KR, 15),
+        (KR, 15),
+        (KA, 15),
+        (KA, 15),
+        (KB, 30),
+        (KB, 30),
+        (KL, 45),
+        (KL, 45),
+        (KA, 45),
+        (KA, 45),
+        (KR, 60),
+        (KR, 60),
+        (KB, 60),
+        (KB, 60),
+        (KL, 77),
+        (KL, 77),
+        (KR, 94),
+        (KR, 94),
+        (KA, 94),
+        (KA, 94),
+        (KL, 111),
+        (KL, 111),
+    ],
+    ke: &[(KR, 30), (KR, 30), (KL, 60), (KL, 60), (KA, 77), (KA, 77)],
+};
+
+/// The source key and rotation of the subkeys.
+struct Schedule {
+    kw: [(usize, u32); 4],
+    k: &'static [(usize, u32)],
+    ke: &'static [(usize, u32)],
+}
+
+/// The maximum number of round subkeys (256-bit keys).
+const MAX_ROUND_KEYS: usize = 24;
+
+/// The maximum number of FL and FL⁻¹ subkeys (256-bit keys).
+const MAX_FL_KEYS: usize = 6;
+
+/// A Camellia block cipher.
+pub struct Camellia {
+    rounds: usize,
+    kw: [u64; 4],
+    k: [u64; MAX_ROUND_KEYS],
+    ke: [u64; MAX_FL_KEYS],
+}
+
+// Public methods.
+impl Camellia {
+    /// new creates a Camellia cipher with the given key.
+    /// The key needs to contain 16, 24 or 32 bytes.
+    pub fn new(key: &[u8]) -> Camellia {
+        if key.len() != 16 && key.len() != 24 && key.len() != 32 {
+            panic!("Key length must be 16, 24 or 32 bytes.");
+        }
+
+        let mut instance = Camellia {
+            rounds: if key.len() == 16 { 18 } else { 24 },
+            kw: [0u64; 4],
+            k: [0u64; MAX_ROUND_KEYS],
+            ke: [0u64; MAX_FL_KEYS],
+        };
+
+        instance.expand_key(key);
+
+        instance
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, block: &Block) -> Block {
+        let k = &self.k[..self.rounds];
+        let ke = &self.ke[..self.fl_keys()];
+        crypt(block, &self.kw, k.iter(), ke.iter())
+    }
+
+    /// decrypt a block of data: the subkeys are used in reverse order.
+    pub fn decrypt(&self, block: &Block) -> Block {
+        let kw = [self.kw[2], self.kw[3], self.kw[0], self.kw[1]];
+        let k = &self.k[..self.rounds];
+        let ke = &self.ke[..self.fl_keys()];
+        crypt(block, &kw, k.iter().rev(), ke.iter().rev())
+    }
+}
+
+impl BlockCipher for Camellia {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        self.encrypt(block)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        self.decrypt(block)
+    }
+}
+
+// Private methods.
+impl Camellia {
+    /// Apply the key schedule: the subkeys are rotations of KL and KR (from
+    /// the key) and of KA and KB (derived from them).
+    fn expand_key(&mut self, key: &[u8]) {
+        let kl = read_u128(&key[..16]);
+        let kr = match key.len() {
+            16 => 0,
+            // The missing 64 bits are the complement of the last 64 bits of
+            // the key.
+            24 => {
+                let r = read_u128(&[&key[16..], &[0u8; 8]].concat());
+                r | (!r >> 64)
+            }
+            _ => read_u128(&key[16..]),
+        };
+
+        let mut d1 = ((kl ^ kr) >> 64) as u64;
+        let mut d2 = (kl ^ kr) as u64;
+        d2 ^= f(d1, SIGMA[0]);
+        d1 ^= f(d2, SIGMA[1]);
+        d1 ^= (kl >> 64) as u64;
+        d2 ^= kl as u64;
+        d2 ^= f(d1, SIGMA[2]);
+        d1 ^= f(d2, SIGMA[3]);
+        let ka = (u128::from(d1) << 64) | u128::from(d2);
+
+        let mut d1 = ((ka ^ kr) >> 64) as u64;
+        let mut d2 = (ka ^ kr) as u64;
+        d2 ^= f(d1, SIGMA[4]);
+        d1 ^= f(d2, SIGMA[5]);
+        let kb = (u128::from(d1) << 64) | u128::from(d2);
+
+        let keys = [kl, kr, ka, kb];
+        let schedule = if key.len() == 16 {
+            &SCHEDULE_128
+        } else {
+            &SCHEDULE_256
+        };
+
+        let subkey = |i: usize, (source, rotation): (usize, u32)| {
+            let k = keys[source].rotate_left(rotation);
+            if i.is_multiple_of(2) {
+                (k >> 64) as u64
+            } else {
+                k as u64
+            }
+        };
+
+        for (i, s) in schedule.kw.iter().enumerate() {
+            self.kw[i] = subkey(i, *s);
+        }
+        for (i, s) in schedule.k.iter().enumerate() {
+            self.k[i] = subkey(i, *s);
+        }
+        for (i, s) in schedule.ke.iter().enumerate() {
+            self.ke[i] = subkey(i, *s);
+        }
+    }
+
+    /// fl_keys returns the number of FL and FL⁻¹ subkeys: there is a layer
+    /// every 6 rounds.
+    fn fl_keys(&self) -> usize {
+        2 * (self.rounds / 6 - 1)
+    }
+}
+
+/// crypt runs the Feistel network with the given subkeys: whitening, 6
+/// rounds, then an FL/FL⁻¹ layer before each following group of 6 rounds,
+/// and whitening again.
+fn crypt<'a, K, E>(block: &Block, kw: &[u64; 4], k: K, ke: E) -> Block
+where
+    K: Iterator<Item = &'a u64>,
+    E: Iterator<Item = &'a u64>,
+{
+    let m = read_u128(block);
+    let mut d1 = (m >> 64) as u64 ^ kw[0];
+    let mut d2 = m as u64 ^ kw[1];
+
+    let mut ke = ke;
+    for (i, k) in k.enumerate() {
+        if i > 0 && i.is_multiple_of(6) {
+            d1 = fl(d1, *ke.next().unwrap());
+            d2 = fl_inv(d2, *ke.next().unwrap());
+        }
+
+        if i.is_multiple_of(2) {
+            d2 ^= f(d1, *k);
+        } else {
+            d1 ^= f(d2, *k);
+        }
+    }
+
+    d2 ^= kw[2];
+    d1 ^= kw[3];
+
+    ((u128::from(d2) << 64) | u128::from(d1)).to_be_bytes()
+}
+
+/// f is the round function: S-boxes followed by the byte-wise linear
+/// P-function.
+fn f(input: u64, k: u64) -> u64 {
+    let x = (input ^ k).to_be_bytes();
+
+    let t1 = SBOX1[x[0] as usize];
+    let t2 = sbox2(x[1]);
+    let t3 = sbox3(x[2]);
+    let t4 = sbox4(x[3]);
+    let t5 = sbox2(x[4]);
+    let t6 = sbox3(x[5]);
+    let t7 = sbox4(x[6]);
+    let t8 = SBOX1[x[7] as usize];
+
+    u64::from_be_bytes([
+        t1 ^ t3 ^ t4 ^ t6 ^ t7 ^ t8,
+        t1 ^ t2 ^ t4 ^ t5 ^ t7 ^ t8,
+        t1 ^ t2 ^ t3 ^ t5 ^ t6 ^ t8,
+        t2 ^ t3 ^ t4 ^ t5 ^ t6 ^ t7,
+        t1 ^ t2 ^ t6 ^ t7 ^ t8,
+        t2 ^ t3 ^ t5 ^ t7 ^ t8,
+        t3 ^ t4 ^ t5 ^ t6 ^ t8,
+        t1 ^ t4 ^ t5 ^ t6 ^ t7,
+    ])
+}
+
+fn sbox2(x: u8) -> u8 {
+    SBOX1[x as usize].rotate_left(1)
+}
+
+fn sbox3(x: u8) -> u8 {
+    SBOX1[x as usize].rotate_left(7)
+}
+
+fn sbox4(x: u8) -> u8 {
+    SBOX1[x.rotate_left(1) as usize]
+}
+
+fn fl(input: u64, k: u64) -> u64 {
+    let mut x1 = (input >> 32) as u32;
+    let mut x2 = input as u32;
+    let k1 = (k >> 32) as u32;
+    let k2 = k as u32;
+
+    x2 ^= (x1 & k1).rotate_left(1);
+    x1 ^= x2 | k2;
+
+    (u64::from(x1) << 32) | u64::from(x2)
+}
+
+fn fl_inv(input: u64, k: u64) -> u64 {
+    let mut y1 = (input >> 32) as u32;
+    let mut y2 = input as u32;
+    let k1 = (k >> 32) as u32;
+    let k2 = k as u32;
+
+    y1 ^= y2 | k2;
+    y2 ^= (y1 & k1).rotate_left(1);
+
+    (u64::from(y1) << 32) | u64::from(y2)
+}
+
+fn read_u128(bytes: &[u8]) -> u128 {
+    let mut b = [0u8; 16];
+    b.copy_from_slice(bytes);
+    u128::from_be_bytes(b)
+}
+
+/// The first S-box; the other three are derived from it.
+const SBOX1: [u8; 256] = [
+    0x70, 0x82, 0x2c, 0xec, 0xb3, 0x27, 0xc0, 0xe5, 0xe4, 0x85, 0x57, 0x35, 0xea, 0x0c, 0xae, 0x41,
+    0x23, 0xef, 0x6b, 0x93, 0x45, 0x19, 0xa5, 0x21, 0xed, 0x0e, 0x4f, 0x4e, 0x1d, 0x65, 0x92, 0xbd,
+    0x86, 0xb8, 0xaf, 0x8f, 0x7c, 0xeb, 0x1f, 0xce, 0x3e, 0x30, 0xdc, 0x5f, 0x5e, 0xc5, 0x0b, 0x1a,
+    0xa6, 0xe1, 0x39, 0xca, 0xd5, 0x47, 0x5d, 0x3d, 0xd9, 0x01, 0x5a, 0xd6, 0x51, 0x56, 0x6c, 0x4d,
+    0x8b, 0x0d, 0x9a, 0x66, 0xfb, 0xcc, 0xb0, 0x2d, 0x74, 0x12, 0x2b, 0x20, 0xf0, 0xb1, 0x84, 0x99,
+    0xdf, 0x4c, 0xcb, 0xc2, 0x34, 0x7e, 0x76, 0x05, 0x6d, 0xb7, 0xa9, 0x31, 0xd1, 0x17, 0x04, 0xd7,
+    0x14, 0x58, 0x3a, 0x61, 0xde, 0x1b, 0x11, 0x1c, 0x32, 0x0f, 0x9c, 0x16, 0x53, 0x18, 0xf2, 0x22,
+    0xfe, 0x44, 0xcf, 0xb2, 0xc3, 0xb5, 0x7a, 0x91, 0x24, 0x08, 0xe8, 0xa8, 0x60, 0xfc, 0x69, 0x50,
+    0xaa, 0xd0, 0xa0, 0x7d, 0xa1, 0x89, 0x62, 0x97, 0x54, 0x5b, 0x1e, 0x95, 0xe0, 0xff, 0x64, 0xd2,
+    0x10, 0xc4, 0x00, 0x48, 0xa3, 0xf7, 0x75, 0xdb, 0x8a, 0x03, 0xe6, 0xda, 0x09, 0x3f, 0xdd, 0x94,
+    0x87, 0x5c, 0x83, 0x02, 0xcd, 0x4a, 0x90, 0x33, 0x73, 0x67, 0xf6, 0xf3, 0x9d, 0x7f, 0xbf, 0xe2,
+    0x52, 0x9b, 0xd8, 0x26, 0xc8, 0x37, 0xc6, 0x3b, 0x81, 0x96, 0x6f, 0x4b, 0x13, 0xbe, 0x63, 0x2e,
+    0xe9, 0x79, 0xa7, 0x8c, 0x9f, 0x6e, 0xbc, 0x8e, 0x29, 0xf5, 0xf9, 0xb6, 0x2f, 0xfd, 0xb4, 0x59,
+    0x78, 0x98, 0x06, 0x6a, 0xe7, 0x46, 0x71, 0xba, 0xd4, 0x25, 0xab, 0x42, 0x88, 0xa2, 0x8d, 0xfa,
+    0x72, 0x07, 0xb9, 0x55, 0xf8, 0xee, 0xac, 0x0a, 0x36, 0x49, 0x2a, 0x68, 0x3c, 0x38, 0xf1, 0xa4,
+    0x40, 0x28, 0xd3, 0x7b, 0xbb, 0xc9, 0x43, 0xc1, 0x15, 0xe3, 0xad, 0xf4, 0x77, 0xc7, 0x80, 0x9e,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::{block, hex};
+
+    fn check_vector(key: &str, plain: &str, cipher: &str) {
+        let instance = Camellia::new(&hex(key));
+        assert_eq!(block(cipher), instance.encrypt(&block(plain)));
+        assert_eq!(block(plain), instance.decrypt(&block(cipher)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_invalid_key_length() {
+        Camellia::new(&[0u8; 20]);
+    }
+
+    #[test]
+    fn fl_and_fl_inv() {
+        let k = 0x0123456789abcdef;
+        let x = 0xfedcba9876543210;
+        assert_eq!(x, fl_inv(fl(x, k), k));
+    }
+
+    #[test]
+    fn camellia_128() {
+        // Test vectors of RFC 3713 appendix A.
+        check_vector(
+            "0123456789abcdeffedcba9876543210",
+            "0123456789abcdeffedcba9876543210",
+            "67673138549669730857065648eabe43",
+        );
+    }
+
+    #[test]
+    fn camellia_192() {
+        check_vector(
+            "0123456789abcdeffedcba98765432100011223344556677",
+            "0123456789abcdeffedcba9876543210",
+            "b4993401b3e996f84ee5cee7d79b09b9",
+        );
+    }
+
+    #[test]
+    fn camellia_256() {
+        check_vector(
+            "0123456789abcdeffedcba987654321000112233445566778899aabbccddeeff",
+            "0123456789abcdeffedcba9876543210",
+            "9acc237dff16d76c20ef7c919e3a7509",
+        );
+    }
+}
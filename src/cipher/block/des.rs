@@ -0,0 +1,397 @@
+//! # DES
+//!
+//! `des` implements the DES block cipher (FIPS 46-3) and Triple-DES, also
+//! known as TDEA (SP 800-67).
+//! DES uses 56-bit keys, which can be brute-forced: it should only be used
+//! to decode legacy data.
+//! Triple-DES chains three DES operations (encrypt-decrypt-encrypt) to
+//! increase the key size.
+
+/// The number of rounds.
+const ROUNDS: usize = 16;
+
+/// The weak keys: encryption and decryption are the same operation.
+/// Parity bits are set.
+pub const WEAK_KEYS: [u64; 4] = [
+    0x0101010101010101,
+    0xfefefefefefefefe,
+    0xe0e0e0e0f1f1f1f1,
+    0x1f1f1f1f0e0e0e0e,
+];
+
+/// The semi-weak keys, by pairs: encrypting with one key of a pair is the
+/// same as decrypting with the other.
+/// Parity bits are set.
+pub const SEMI_WEAK_KEYS: [(u64, u64); 6] = [
+    (0x01fe01fe01fe01fe, 0xfe01fe01fe01fe01),
+    (0x1fe01fe00ef10ef1, 0xe01fe01ff10ef10e),
+    (0x01e001e001f101f1, 0xe001e001f101f101),
+    (0x1ffe1ffe0efe0efe, 0xfe1ffe1ffe0efe0e),
+    (0x011f011f010e010e, 0x1f011f010e010e01),
+    (0xe0fee0fef1fef1fe, 0xfee0fee0fef1fef1),
+];
+
+/// A DES block cipher.
+pub struct Des {
+    subkeys: [u64; ROUNDS],
+}
+
+/// A Triple-DES block cipher, in EDE form.
+pub struct TripleDes {
+    k1: Des,
+    k2: Des,
+    k3: Des,
+}
+
+// Public methods.
+impl Des {
+    /// new creates a DES cipher with the given key.
+    /// The least significant bit of each byte is a parity bit and is
+    /// ignored.
+    pub fn new(key: u64) -> Des {
+        let mut instance = Des {
+            subkeys: [0u64; ROUNDS],
+        };
+
+        instance.expand_key(key);
+
+        instance
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, block: u64) -> u64 {
+        self.feistel(block, false)
+    }
+
+    /// decrypt a block of data.
+    pub fn decrypt(&self, block: u64) -> u64 {
+        self.feistel(block, true)
+    }
+}
+
+// Public methods.
+impl TripleDes {
+    /// new creates a 3-key Triple-DES cipher (keying option 1).
+    /// The three keys should be distinct.
+    pub fn new(k1: u64, k2: u64, k3: u64) -> TripleDes {
+        TripleDes {
+            k1: Des::new(k1),
+            k2: Des::new(k2),
+            k3: Des::new(k3),
+        }
+    }
+
+    /// new_two_key creates a 2-key Triple-DES cipher (keying option 2),
+    /// where the third key is the same as the first one.
+    pub fn new_two_key(k1: u64, k2: u64) -> TripleDes {
+        TripleDes::new(k1, k2, k1)
+    }
+
+    /// encrypt a block of data: encrypt with k1, decrypt with k2 and
+    /// encrypt with k3.
+    pub fn encrypt(&self, block: u64) -> u64 {
+        self.k3.encrypt(self.k2.decrypt(self.k1.encrypt(block)))
+    }
+
+    /// decrypt a block of data: decrypt with k3, encrypt with k2 and
+    /// decrypt with k1.
+    pub fn decrypt(&self, block: u64) -> u64 {
+        self.k1.decrypt(self.k2.encrypt(self.k3.decrypt(block)))
+    }
+}
+
+/// is_weak_key returns true if the key is one of the 4 weak keys.
+/// Parity bits are ignored.
+pub fn is_weak_key(key: u64) -> bool {
+    WEAK_KEYS
+        .iter()
+        .any(|k| strip_parity(*k) == strip_parity(key))
+}
+
+/// is_semi_weak_key returns true if the key is one of the 12 semi-weak
+/// keys.
+/// Parity bits are ignored.
+pub fn is_semi_weak_key(key: u64) -> bool {
+    SEMI_WEAK_KEYS.iter().any(|(k1, k2)| {
+        strip_parity(*k1) == strip_parity(key) || strip_parity(*k2) == strip_parity(key)
+    })
+}
+
+// Private methods.
+impl Des {
+    /// Apply the key schedule: PC-1, then rotations of the two 28-bit
+    /// halves and PC-2 for each round.
+    fn expand_key(&mut self, key: u64) {
+        let cd = permute(key, 64, &PC1);
+        let mut c = (cd >> 28) as u32;
+        let mut d = (cd & 0x0fffffff) as u32;
+
+        for (subkey, &shift) in self.subkeys.iter_mut().zip(SHIFTS.iter()) {
+            c = rotate_28(c, shift);
+            d = rotate_28(d, shift);
+
+            let cd = (u64::from(c) << 28) | u64::from(d);
+            *subkey = permute(cd, 56, &PC2);
+        }
+    }
+
+    /// feistel runs the 16 rounds, with subkeys in reverse order for
+    /// decryption.
+    fn feistel(&self, block: u64, reverse: bool) -> u64 {
+        let block = permute(block, 64, &IP);
+        let mut l = (block >> 32) as u32;
+        let mut r = block as u32;
+
+        for i in 0..ROUNDS {
+            let k = if reverse {
+                self.subkeys[ROUNDS - 1 - i]
+            } else {
+                self.subkeys[i]
+            };
+
+            let ll = l;
+            l = r;
+            r = ll ^ f(r, k);
+        }
+
+        // The halves are not swapped after the last round.
+        permute((u64::from(r) << 32) | u64::from(l), 64, &FP)
+    }
+}
+
+/// f is the round function: expansion, key mixing, S-boxes and
+/// permutation.
+fn f(r: u32, k: u64) -> u32 {
+    let x = permute(u64::from(r), 32, &E) ^ k;
+
+    let mut s = 0u32;
+    for (i, sbox) in SBOXES.iter().enumerate() {
+        let b = ((x >> (42 - 6 * i)) & 0x3f) as usize;
+        let row = ((b & 0x20) >> 4) | (b & 1);
+        let column = (b >> 1) & 0xf;
+        s = (s << 4) | u32::from(sbox[16 * row + column]);
+    }
+
+    permute(u64::from(s), 32, &P) as u32
+}
+
+/// permute applies a permutation table to the given number of input bits.
+/// Tables use the specification's conventions: bits are numbered from 1,
+/// starting with the most significant bit.
+fn permute(input: u64, input_bits: u32, table: &[u8]) -> u64 {
+    let mut res = 0u64;
+    for position in table {
+        let bit = (input >> (input_bits - u32::from(*position))) & 1;
+        res = (res << 1) | bit;
+    }
+
+    res
+}
+
+/// rotate_28 rotates a 28-bit value to the left.
+fn rotate_28(x: u32, shift: u32) -> u32 {
+    ((x << shift) | (x >> (28 - shift))) & 0x0fffffff
+}
+
+fn strip_parity(key: u64) -> u64 {
+    key & 0xfefefefefefefefe
+}
+
+/// Initial permutation.
+const IP: [u8; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2, 60, 52, 44, 36, 28, 20, 12, 4, 62, 54, 46, 38, 30, 22, 14, 6,
+    64, 56, 48, 40, 32, 24, 16, 8, 57, 49, 41, 33, 25, 17, 9, 1, 59, 51, 43, 35, 27, 19, 11, 3, 61,
+    53, 45, 37, 29, 21, 13, 5, 63, 55, 47, 39, 31, 23, 15, 7,
+];
+
+/// Final permutation (inverse of IP).
+const FP: [u8; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32, 39, 7, 47, 15, 55, 23, 63, 31, 38, 6, 46, 14, 54, 22, 62, 30,
+    37, 5, 45, 13, 53, 21, 61, 29, 36, 4, 44, 12, 52, 20, 60, 28, 35, 3, 43, 11, 51, 19, 59, 27,
+    34, 2, 42, 10, 50, 18, 58, 26, 33, 1, 41, 9, 49, 17, 57, 25,
+];
+
+/// Expansion from 32 to 48 bits.
+const E: [u8; 48] = [
+    32, 1, 2, 3, 4, 5, 4, 5, 6, 7, 8, 9, 8, 9, 10, 11, 12, 13, 12, 13, 14, 15, 16, 17, 16, 17, 18,
+    19, 20, 21, 20, 21, 22, 23, 24, 25, 24, 25, 26, 27, 28, 29, 28, 29, 30, 31, 32, 1,
+];
+
+/// Permutation applied to the S-box outputs.
+const P: [u8; 32] = [
+    16, 7, 20, 21, 29, 12, 28, 17, 1, 15, 23, 26, 5, 18, 31, 10, 2, 8, 24, 14, 32, 27, 3, 9, 19,
+    13, 30, 6, 22, 11, 4, 25,
+];
+
+/// Permuted choice 1: selects 56 key bits, dropping parity bits.
+const PC1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18, 10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60,
+    52, 44, 36, 63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22, 14, 6, 61, 53, 45, 37, 29,
+    21, 13, 5, 28, 20, 12, 4,
+];
+
+/// Permuted choice 2: selects the 48 bits of each subkey.
+const PC2: [u8; 48] = [
+    14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, 23, 19, 12, 4, 26, 8, 16, 7, 27, 20, 13, 2, 41, 52,
+    31, 37, 47, 55, 30, 40, 51, 45, 33, 48, 44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+];
+
+/// Left rotations of the key halves for each round.
+const SHIFTS: [u32; ROUNDS] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+/// The eight S-boxes, each made of 4 rows of 16 entries.
+const SBOXES: [[u8; 64]; 8] = [
+    [
+        14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7, 0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12,
+        11, 9, 5, 3, 8, 4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0, 15, 12, 8, 2, 4, 9,
+        1, 7, 5, 11, 3, 14, 10, 0, 6, 13,
+    ],
+    [
+        15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10, 3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1,
+        10, 6, 9, 11, 5, 0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15, 13, 8, 10, 1, 3, 15,
+        4, 2, 11, 6, 7, 12, 0, 5, 14, 9,
+    ],
+    [
+        10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8, 13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5,
+        14, 12, 11, 15, 1, 13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7, 1, 10, 13, 0, 6,
+        9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12,
+    ],
+    [
+        7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15, 13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2,
+        12, 1, 10, 14, 9, 10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4, 3, 15, 0, 6, 10, 1,
+        13, 8, 9, 4, 5, 11, 12, 7, 2, 14,
+    ],
+    [
+        2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9, 14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15,
+        10, 3, 9, 8, 6, 4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14, 11, 8, 12, 7, 1, 14,
+        2, 13, 6, 15, 0, 9, 10, 4, 5, 3,
+    ],
+    [
+        12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11, 10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13,
+        14, 0, 11, 3, 8, 9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6, 4, 3, 2, 12, 9, 5,
+        15, 10, 11, 14, 1, 7, 6, 0, 8, 13,
+    ],
+    [
+        4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1, 13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5,
+        12, 2, 15, 8, 6, 1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2, 6, 11, 13, 8, 1, 4,
+        10, 7, 9, 5, 0, 15, 14, 2, 3, 12,
+    ],
+    [
+        13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7, 1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6,
+        11, 0, 14, 9, 2, 7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8, 2, 1, 14, 7, 4, 10,
+        8, 13, 15, 12, 9, 0, 3, 5, 6, 11,
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initial_permutation() {
+        assert_eq!(0xcc00ccfff0aaf0aa, permute(0x0123456789abcdef, 64, &IP));
+        assert_eq!(
+            0x0123456789abcdef,
+            permute(permute(0x0123456789abcdef, 64, &IP), 64, &FP)
+        );
+    }
+
+    #[test]
+    fn key_schedule() {
+        let des = Des::new(0x133457799bbcdff1);
+        assert_eq!(0x1b02effc7072, des.subkeys[0]);
+        assert_eq!(0xcb3d8b0e17f5, des.subkeys[15]);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let des = Des::new(0x133457799bbcdff1);
+        assert_eq!(0x85e813540f0ab405, des.encrypt(0x0123456789abcdef));
+        assert_eq!(0x0123456789abcdef, des.decrypt(0x85e813540f0ab405));
+    }
+
+    #[test]
+    fn now_is_the_time() {
+        // Example of FIPS 81 ("Now is t").
+        let des = Des::new(0x0123456789abcdef);
+        assert_eq!(0x3fa40e8a984d4815, des.encrypt(0x4e6f772069732074));
+    }
+
+    #[test]
+    fn parity_bits_are_ignored() {
+        let des = Des::new(0x133457799bbcdff1);
+        let other = Des::new(0x133457799bbcdff1 ^ 0x0101010101010101);
+        assert_eq!(des.subkeys, other.subkeys);
+    }
+
+    #[test]
+    fn triple_des() {
+        // Example of SP 800-67 appendix B.
+        let tdes = TripleDes::new(0x0123456789abcdef, 0x23456789abcdef01, 0x456789abcdef0123);
+        let plain = [0x5468652071756663, 0x6b2062726f776e20, 0x666f78206a756d70];
+        let cipher = [0xa826fd8ce53b855f, 0xcce21c8112256fe6, 0x68d5c05dd9b6b900];
+
+        for i in 0..3 {
+            assert_eq!(cipher[i], tdes.encrypt(plain[i]));
+            assert_eq!(plain[i], tdes.decrypt(cipher[i]));
+        }
+    }
+
+    #[test]
+    fn triple_des_two_key() {
+        let k1 = 0x0123456789abcdef;
+        let k2 = 0x23456789abcdef01;
+        let two_key = TripleDes::new_two_key(k1, k2);
+        let three_key = TripleDes::new(k1, k2, k1);
+        assert_eq!(three_key.encrypt(42), two_key.encrypt(42));
+        assert_eq!(42, two_key.decrypt(two_key.encrypt(42)));
+    }
+
+    #[test]
+    fn triple_des_single_key_is_des() {
+        // With three equal keys EDE degenerates into single DES, which is
+        // what makes it backwards compatible.
+        let k = 0x133457799bbcdff1;
+        assert_eq!(
+            Des::new(k).encrypt(0x0123456789abcdef),
+            TripleDes::new(k, k, k).encrypt(0x0123456789abcdef)
+        );
+    }
+
+    #[test]
+    fn weak_keys() {
+        for k in WEAK_KEYS.iter() {
+            assert!(is_weak_key(*k));
+            assert!(is_weak_key(strip_parity(*k)));
+            assert!(!is_semi_weak_key(*k));
+
+            // Encrypting twice with a weak key gives back the plaintext.
+            let des = Des::new(*k);
+            assert_eq!(
+                0x0123456789abcdef,
+                des.encrypt(des.encrypt(0x0123456789abcdef))
+            );
+        }
+
+        assert!(!is_weak_key(0x133457799bbcdff1));
+    }
+
+    #[test]
+    fn semi_weak_keys() {
+        for (k1, k2) in SEMI_WEAK_KEYS.iter() {
+            assert!(is_semi_weak_key(*k1));
+            assert!(is_semi_weak_key(*k2));
+            assert!(!is_weak_key(*k1));
+
+            // Encrypting with one key of the pair undoes the other.
+            let des1 = Des::new(*k1);
+            let des2 = Des::new(*k2);
+            assert_eq!(
+                0x0123456789abcdef,
+                des2.encrypt(des1.encrypt(0x0123456789abcdef))
+            );
+        }
+
+        assert!(!is_semi_weak_key(0x133457799bbcdff1));
+    }
+}
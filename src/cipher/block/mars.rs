@@ -3,6 +3,8 @@
 //! `mars` implements the mars block cipher.
 //! Mars was one of the AES finalists.
 
+use cipher::block::{read_le_words, write_le_words, Block, BlockCipher};
+
 /// A Mars block cipher.
 pub struct Mars {
     key: [u32; 40],
@@ -35,7 +37,7 @@ impl Mars {
         for i in 0..8 {
             b = (b ^ s0(a)).wrapping_add(s1(a.rotate_right(8)));
             c = c.wrapping_add(s0(a.rotate_right(16)));
-            d = d ^ s1(a.rotate_right(24));
+            d ^= s1(a.rotate_right(24));
             a = a.rotate_right(24);
 
             if i == 1 || i == 5 {
@@ -60,13 +62,13 @@ impl Mars {
             if i < 8 {
                 b = b.wrapping_add(l);
             } else {
-                b = b ^ r;
+                b ^= r;
             }
 
             c = c.wrapping_add(m);
 
             if i < 8 {
-                d = d ^ r;
+                d ^= r;
             } else {
                 d = d.wrapping_add(l);
             }
@@ -88,7 +90,7 @@ impl Mars {
                 a = a.wrapping_sub(d);
             }
 
-            b = b ^ s1(a);
+            b ^= s1(a);
             c = c.wrapping_sub(s0(a.rotate_left(8)));
             d = d.wrapping_sub(s1(a.rotate_left(16))) ^ s0(a.rotate_left(24));
             let aa = a;
@@ -124,7 +126,7 @@ impl Mars {
             a = a.rotate_right(24);
             d = (d ^ s0(a.rotate_right(8))).wrapping_add(s1(a.rotate_right(16)));
             c = c.wrapping_add(s0(a.rotate_right(24)));
-            b = b ^ s1(a);
+            b ^= s1(a);
 
             if i == 2 || i == 6 {
                 a = a.wrapping_add(d);
@@ -150,13 +152,13 @@ impl Mars {
             if i < 8 {
                 b = b.wrapping_sub(l);
             } else {
-                b = b ^ r;
+                b ^= r;
             }
 
             c = c.wrapping_sub(m);
 
             if i < 8 {
-                d = d ^ r;
+                d ^= r;
             } else {
                 d = d.wrapping_sub(l);
             }
@@ -179,7 +181,7 @@ impl Mars {
             }
 
             a = a.rotate_left(24);
-            d = d ^ s1(a.rotate_right(24));
+            d ^= s1(a.rotate_right(24));
             c = c.wrapping_sub(s0(a.rotate_right(16)));
             b = b.wrapping_sub(s1(a.rotate_right(8))) ^ s0(a);
         }
@@ -193,14 +195,27 @@ impl Mars {
     }
 }
 
+// Blocks are read as four little-endian words, as in the specification.
+impl BlockCipher for Mars {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        let (a, b, c, d) = read_le_words(block);
+        let (a, b, c, d) = self.encrypt(a, b, c, d);
+        write_le_words(a, b, c, d)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        let (a, b, c, d) = read_le_words(block);
+        let (a, b, c, d) = self.decrypt(a, b, c, d);
+        write_le_words(a, b, c, d)
+    }
+}
+
 // Private methods.
 impl Mars {
     /// Apply the key expansion algorithm.
     fn expand_key(&mut self, key: &[u32]) {
         let mut t = [0u32; 15];
-        for i in 0..key.len() {
-            t[i] = key[i];
-        }
+        t[..key.len()].copy_from_slice(key);
 
         t[key.len()] = key.len() as u32;
 
@@ -239,6 +254,7 @@ impl Mars {
     }
 
     /// Compute the word mask used in the key expansion algorithm.
+    #[allow(clippy::needless_range_loop)]
     fn compute_key_mask(w: u32) -> u32 {
         // According to the spec:
         // M = 0
@@ -297,8 +313,8 @@ impl Mars {
         }
 
         let mut res = 0;
-        for i in 0..32 {
-            if m[i] {
+        for (i, &bit) in m.iter().enumerate() {
+            if bit {
                 res += 1 << i;
             }
         }
@@ -309,8 +325,10 @@ impl Mars {
     /// efunc is used in the encryption and decryption algorithms.
     fn efunc(input: u32, key1: u32, key2: u32) -> (u32, u32, u32) {
         let r = input.rotate_left(13).wrapping_mul(key2).rotate_left(10);
-        let m = input.wrapping_add(key1).rotate_left(r.rotate_right(5) % 32);
+        // The S-box lookup uses M before its data-dependent rotation.
+        let m = input.wrapping_add(key1);
         let l = (s(m) ^ r.rotate_right(5) ^ r).rotate_left(r % 32);
+        let m = m.rotate_left(r.rotate_right(5) % 32);
         (l, m, r)
     }
 }
@@ -463,4 +481,19 @@ mod tests {
         assert_eq!(24, d3);
         assert_eq!(42, d4);
     }
+
+    #[test]
+    fn encrypt_block_kat() {
+        // First vector of the ecb_tbl.txt file of the Mars submission.
+        let instance = Mars::new(&[0u32; 4]);
+        let cipher = instance.encrypt_block(&[0u8; 16]);
+        assert_eq!(
+            [
+                0xdc, 0xc0, 0x7b, 0x8d, 0xfb, 0x07, 0x38, 0xd6, 0xe3, 0x0a, 0x22, 0xdf, 0xcf, 0x27,
+                0xe8, 0x86,
+            ],
+            cipher
+        );
+        assert_eq!([0u8; 16], instance.decrypt_block(&cipher));
+    }
 }
@@ -3,5 +3,87 @@
 //! `block` contains a collection of block cipher implementations.
 //! These are more suited for software implementations than stream ciphers
 //! (except for Salsa20 and a few other specialized stream ciphers).
+//! Modes of operation need 128-bit blocks: ciphers with 64-bit blocks
+//! (DES, Blowfish, TEA, XTEA, PRESENT and the smaller Speck and Simon
+//! variants) only provide their own encrypt and decrypt methods, and do not
+//! implement `BlockCipher`.
 
+pub mod aes;
+pub mod aria;
+pub mod blowfish;
+pub mod camellia;
+pub mod des;
 pub mod mars;
+pub mod present;
+pub mod rc6;
+pub mod serpent;
+pub mod simon;
+pub mod sm4;
+pub mod speck;
+pub mod tea;
+pub mod twofish;
+pub mod xtea;
+
+/// The size of a block (in bytes) for 128-bit block ciphers.
+pub const BLOCK_SIZE_BYTES: usize = 16;
+
+/// A 128-bit block of data.
+pub type Block = [u8; BLOCK_SIZE_BYTES];
+
+/// A block cipher operating on 128-bit blocks.
+/// Modes of operation are written against this trait so that they can be
+/// used with any of the block ciphers of this crate.
+pub trait BlockCipher {
+    /// encrypt_block encrypts a single block of data.
+    fn encrypt_block(&self, block: &Block) -> Block;
+
+    /// decrypt_block decrypts a single block of data.
+    fn decrypt_block(&self, block: &Block) -> Block;
+}
+
+// Modes of operation can borrow a block cipher instead of owning it.
+impl<C: BlockCipher> BlockCipher for &C {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        (**self).encrypt_block(block)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        (**self).decrypt_block(block)
+    }
+}
+
+/// xor combines two blocks byte by byte.
+pub(crate) fn xor(a: &Block, b: &Block) -> Block {
+    let mut res = [0u8; BLOCK_SIZE_BYTES];
+    for (r, (x, y)) in res.iter_mut().zip(a.iter().zip(b.iter())) {
+        *r = x ^ y;
+    }
+
+    res
+}
+
+/// read_le_words splits a block into four little-endian words.
+/// Most AES candidates (Mars, Serpent, Twofish, RC6) use this convention.
+pub(crate) fn read_le_words(block: &Block) -> (u32, u32, u32, u32) {
+    let mut w = [0u32; 4];
+    for i in 0..4 {
+        w[i] = u32::from_le_bytes([
+            block[4 * i],
+            block[4 * i + 1],
+            block[4 * i + 2],
+            block[4 * i + 3],
+        ]);
+    }
+
+    (w[0], w[1], w[2], w[3])
+}
+
+/// write_le_words joins four words into a block in little-endian order.
+pub(crate) fn write_le_words(a: u32, b: u32, c: u32, d: u32) -> Block {
+    let mut block = [0u8; BLOCK_SIZE_BYTES];
+    block[..4].copy_from_slice(&a.to_le_bytes());
+    block[4..8].copy_from_slice(&b.to_le_bytes());
+    block[8..12].copy_from_slice(&c.to_le_bytes());
+    block[12..].copy_from_slice(&d.to_le_bytes());
+    block
+}
@@ -0,0 +1,187 @@
+//! # PRESENT
+//!
+//! `present` implements the PRESENT lightweight block cipher (ISO/IEC
+//! 29192-2), designed for RFID tags and sensors.
+//! PRESENT is a substitution-permutation network with a single 4-bit S-box
+//! and a bit permutation, which are cheap in hardware but slow in
+//! software.
+//! It uses 80 or 128-bit keys and 64-bit blocks.
+
+/// The number of rounds.
+const ROUNDS: usize = 31;
+
+/// The S-box.
+const SBOX: [u8; 16] = [
+    0xc, 0x5, 0x6, 0xb, 0x9, 0x0, 0xa, 0xd, 0x3, 0xe, 0xf, 0x8, 0x4, 0x7, 0x1, 0x2,
+];
+
+/// The inverse S-box.
+const INV_SBOX: [u8; 16] = [
+    0x5, 0xe, 0xf, 0x8, 0xc, 0x1, 0x2, 0xd, 0xb, 0x4, 0x6, 0x3, 0x0, 0x7, 0x9, 0xa,
+];
+
+/// A PRESENT block cipher.
+pub struct Present {
+    round_keys: [u64; ROUNDS + 1],
+}
+
+// Public methods.
+impl Present {
+    /// new creates a PRESENT cipher with an 80-bit (10 bytes) or 128-bit
+    /// (16 bytes) key, most significant byte first.
+    /// new panics if the key has another length.
+    pub fn new(key: &[u8]) -> Present {
+        if key.len() != 10 && key.len() != 16 {
+            panic!("Key length must be 10 or 16 bytes.");
+        }
+
+        let mut instance = Present {
+            round_keys: [0u64; ROUNDS + 1],
+        };
+
+        instance.expand_key(key);
+
+        instance
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, block: u64) -> u64 {
+        let mut state = block;
+
+        for i in 0..ROUNDS {
+            state ^= self.round_keys[i];
+            state = substitute(state, &SBOX);
+            state = permute(state);
+        }
+
+        state ^ self.round_keys[ROUNDS]
+    }
+
+    /// decrypt a block of data.
+    pub fn decrypt(&self, block: u64) -> u64 {
+        let mut state = block ^ self.round_keys[ROUNDS];
+
+        for i in (0..ROUNDS).rev() {
+            state = inverse_permute(state);
+            state = substitute(state, &INV_SBOX);
+            state ^= self.round_keys[i];
+        }
+
+        state
+    }
+}
+
+// Private methods.
+impl Present {
+    /// Apply the key schedule: each round key is the top 64 bits of the key
+    /// register, which is then rotated, passed through the S-box and mixed
+    /// with the round counter.
+    fn expand_key(&mut self, key: &[u8]) {
+        let bits = 8 * key.len() as u32;
+        let mask = u128::MAX >> (128 - bits);
+
+        let mut k = 0u128;
+        for b in key {
+            k = (k << 8) | u128::from(*b);
+        }
+
+        for i in 0..=ROUNDS {
+            self.round_keys[i] = (k >> (bits - 64)) as u64;
+
+            k = ((k << 61) | (k >> (bits - 61))) & mask;
+            k = sbox_nibble(k, bits - 4);
+
+            let counter = (i + 1) as u128;
+            if bits == 80 {
+                k ^= counter << 15;
+            } else {
+                k = sbox_nibble(k, bits - 8);
+                k ^= counter << 62;
+            }
+        }
+    }
+}
+
+/// sbox_nibble applies the S-box to the nibble of the key register at the
+/// given bit offset.
+fn sbox_nibble(k: u128, offset: u32) -> u128 {
+    let nibble = ((k >> offset) & 0xf) as usize;
+    (k & !(0xf << offset)) | (u128::from(SBOX[nibble]) << offset)
+}
+
+/// substitute applies an S-box to the 16 nibbles of the state.
+fn substitute(state: u64, sbox: &[u8; 16]) -> u64 {
+    let mut res = 0u64;
+    for i in 0..16 {
+        let nibble = ((state >> (4 * i)) & 0xf) as usize;
+        res |= u64::from(sbox[nibble]) << (4 * i);
+    }
+
+    res
+}
+
+/// permute moves bit `i` to bit `16 * i mod 63`, except bit 63 which stays
+/// in place.
+fn permute(state: u64) -> u64 {
+    let mut res = 0u64;
+    for i in 0..64 {
+        res |= ((state >> i) & 1) << destination(i);
+    }
+
+    res
+}
+
+fn inverse_permute(state: u64) -> u64 {
+    let mut res = 0u64;
+    for i in 0..64 {
+        res |= ((state >> destination(i)) & 1) << i;
+    }
+
+    res
+}
+
+fn destination(i: u32) -> u32 {
+    if i == 63 {
+        63
+    } else {
+        (16 * i) % 63
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_vector(key: &[u8], plain: u64, cipher: u64) {
+        let instance = Present::new(key);
+        assert_eq!(cipher, instance.encrypt(plain));
+        assert_eq!(plain, instance.decrypt(cipher));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_invalid_key_length() {
+        Present::new(&[0u8; 12]);
+    }
+
+    #[test]
+    fn inverse_sbox() {
+        for i in 0..16 {
+            assert_eq!(i as u8, INV_SBOX[SBOX[i] as usize]);
+        }
+    }
+
+    #[test]
+    fn present_80() {
+        // Test vectors of the specification.
+        check_vector(&[0u8; 10], 0, 0x5579c1387b228445);
+        check_vector(&[0xffu8; 10], 0, 0xe72c46c0f5945049);
+        check_vector(&[0u8; 10], u64::MAX, 0xa112ffc72f68417b);
+        check_vector(&[0xffu8; 10], u64::MAX, 0x3333dcd3213210d2);
+    }
+
+    #[test]
+    fn present_128() {
+        check_vector(&[0u8; 16], 0, 0x96db702a2e6900af);
+    }
+}
@@ -0,0 +1,225 @@
+//! # RC6
+//!
+//! `rc6` implements the RC6 block cipher (RC6-32/20/b).
+//! RC6 was one of the AES finalists.
+//! It relies on data-dependent rotations and 32-bit multiplications,
+//! which are fast in software but costly in hardware.
+
+use cipher::block::{read_le_words, write_le_words, Block, BlockCipher};
+
+/// The number of rounds.
+const ROUNDS: usize = 20;
+
+/// The number of round key words.
+const KEY_WORDS: usize = 2 * ROUNDS + 4;
+
+/// Magic constant derived from e.
+const P32: u32 = 0xb7e15163;
+
+/// Magic constant derived from the golden ratio.
+const Q32: u32 = 0x9e3779b9;
+
+/// A RC6 block cipher.
+pub struct Rc6 {
+    key: [u32; KEY_WORDS],
+}
+
+// Public methods.
+impl Rc6 {
+    /// new creates a RC6 cipher with the given key.
+    /// The key needs to contain between 1 and 63 words.
+    pub fn new(key: &[u32]) -> Rc6 {
+        if key.is_empty() || key.len() > 63 {
+            panic!("Key length must be between 1 and 63.");
+        }
+
+        let mut instance = Rc6 {
+            key: [0u32; KEY_WORDS],
+        };
+
+        instance.expand_key(key);
+
+        instance
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, a: u32, b: u32, c: u32, d: u32) -> (u32, u32, u32, u32) {
+        let mut a = a;
+        let mut b = b.wrapping_add(self.key[0]);
+        let mut c = c;
+        let mut d = d.wrapping_add(self.key[1]);
+
+        for i in 1..ROUNDS + 1 {
+            let t = b
+                .wrapping_mul(b.wrapping_mul(2).wrapping_add(1))
+                .rotate_left(5);
+            let u = d
+                .wrapping_mul(d.wrapping_mul(2).wrapping_add(1))
+                .rotate_left(5);
+            a = (a ^ t).rotate_left(u % 32).wrapping_add(self.key[2 * i]);
+            c = (c ^ u)
+                .rotate_left(t % 32)
+                .wrapping_add(self.key[2 * i + 1]);
+
+            let aa = a;
+            a = b;
+            b = c;
+            c = d;
+            d = aa;
+        }
+
+        a = a.wrapping_add(self.key[2 * ROUNDS + 2]);
+        c = c.wrapping_add(self.key[2 * ROUNDS + 3]);
+
+        (a, b, c, d)
+    }
+
+    /// decrypt a block of data.
+    pub fn decrypt(&self, a: u32, b: u32, c: u32, d: u32) -> (u32, u32, u32, u32) {
+        let mut a = a.wrapping_sub(self.key[2 * ROUNDS + 2]);
+        let mut b = b;
+        let mut c = c.wrapping_sub(self.key[2 * ROUNDS + 3]);
+        let mut d = d;
+
+        for i in (1..ROUNDS + 1).rev() {
+            let dd = d;
+            d = c;
+            c = b;
+            b = a;
+            a = dd;
+
+            let u = d
+                .wrapping_mul(d.wrapping_mul(2).wrapping_add(1))
+                .rotate_left(5);
+            let t = b
+                .wrapping_mul(b.wrapping_mul(2).wrapping_add(1))
+                .rotate_left(5);
+            c = c.wrapping_sub(self.key[2 * i + 1]).rotate_right(t % 32) ^ u;
+            a = a.wrapping_sub(self.key[2 * i]).rotate_right(u % 32) ^ t;
+        }
+
+        d = d.wrapping_sub(self.key[1]);
+        b = b.wrapping_sub(self.key[0]);
+
+        (a, b, c, d)
+    }
+}
+
+impl BlockCipher for Rc6 {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        let (a, b, c, d) = read_le_words(block);
+        let (a, b, c, d) = self.encrypt(a, b, c, d);
+        write_le_words(a, b, c, d)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        let (a, b, c, d) = read_le_words(block);
+        let (a, b, c, d) = self.decrypt(a, b, c, d);
+        write_le_words(a, b, c, d)
+    }
+}
+
+// Private methods.
+impl Rc6 {
+    /// Apply the key expansion algorithm (the same as RC5's).
+    fn expand_key(&mut self, key: &[u32]) {
+        let mut l = Vec::from(key);
+
+        self.key[0] = P32;
+        for i in 1..KEY_WORDS {
+            self.key[i] = self.key[i - 1].wrapping_add(Q32);
+        }
+
+        let mut a = 0u32;
+        let mut b = 0u32;
+        let mut i = 0;
+        let mut j = 0;
+        for _ in 0..3 * KEY_WORDS.max(l.len()) {
+            a = self.key[i].wrapping_add(a).wrapping_add(b).rotate_left(3);
+            self.key[i] = a;
+            b = l[j]
+                .wrapping_add(a)
+                .wrapping_add(b)
+                .rotate_left(a.wrapping_add(b) % 32);
+            l[j] = b;
+            i = (i + 1) % KEY_WORDS;
+            j = (j + 1) % l.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::{block, words};
+
+    fn check_vector(key: &str, plain: &str, cipher: &str) {
+        let instance = Rc6::new(&words(key));
+        assert_eq!(block(cipher), instance.encrypt_block(&block(plain)));
+        assert_eq!(block(plain), instance.decrypt_block(&block(cipher)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_key_too_small() {
+        Rc6::new(&[]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_key_too_big() {
+        Rc6::new(&[0u32; 64]);
+    }
+
+    #[test]
+    fn kat_128() {
+        // Test vectors of the RC6 specification.
+        check_vector(
+            "00000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "8fc3a53656b1f778c129df4e9848a41e",
+        );
+        check_vector(
+            "0123456789abcdef0112233445566778",
+            "02132435465768798a9bacbdcedfe0f1",
+            "524e192f4715c6231f51f6367ea43f18",
+        );
+    }
+
+    #[test]
+    fn kat_192() {
+        check_vector(
+            "000000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "6cd61bcb190b30384e8a3f168690ae82",
+        );
+        check_vector(
+            "0123456789abcdef0112233445566778899aabbccddeeff0",
+            "02132435465768798a9bacbdcedfe0f1",
+            "688329d019e505041e52e92af95291d4",
+        );
+    }
+
+    #[test]
+    fn kat_256() {
+        check_vector(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "8f5fbd0510d15fa893fa3fda6e857ec2",
+        );
+        check_vector(
+            "0123456789abcdef0112233445566778899aabbccddeeff01032547698badcfe",
+            "02132435465768798a9bacbdcedfe0f1",
+            "c8241816f0d7e48920ad16a1674e5d48",
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let instance = Rc6::new(&[42u32; 8]);
+        let (e1, e2, e3, e4) = instance.encrypt(2, 4, 24, 42);
+        let (d1, d2, d3, d4) = instance.decrypt(e1, e2, e3, e4);
+
+        assert_eq!((2, 4, 24, 42), (d1, d2, d3, d4));
+    }
+}
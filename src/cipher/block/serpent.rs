@@ -0,0 +1,329 @@
+//! # Serpent
+//!
+//! `serpent` implements the Serpent block cipher.
+//! Serpent was one of the AES finalists.
+//! This implementation uses the bitslice formulation: the 32 4-bit S-boxes
+//! of a round are applied in parallel with word-wide boolean operations,
+//! and the initial and final permutations disappear.
+
+use cipher::block::{read_le_words, write_le_words, Block, BlockCipher};
+
+/// The number of rounds.
+const ROUNDS: usize = 32;
+
+/// The golden ratio, used in the key schedule.
+const PHI: u32 = 0x9e3779b9;
+
+/// A Serpent block cipher.
+pub struct Serpent {
+    key: [[u32; 4]; ROUNDS + 1],
+}
+
+// Public methods.
+impl Serpent {
+    /// new creates a serpent cipher with the given key.
+    /// The key needs to contain between 1 and 8 words: keys shorter than
+    /// 256 bits are padded.
+    pub fn new(key: &[u32]) -> Serpent {
+        if key.is_empty() || key.len() > 8 {
+            panic!("Key length must be between 1 and 8.");
+        }
+
+        let mut instance = Serpent {
+            key: [[0u32; 4]; ROUNDS + 1],
+        };
+
+        instance.expand_key(key);
+
+        instance
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, a: u32, b: u32, c: u32, d: u32) -> (u32, u32, u32, u32) {
+        let mut x = [a, b, c, d];
+
+        for i in 0..ROUNDS {
+            xor_key(&mut x, &self.key[i]);
+            x = sbox(&SBOX_ANF[i % 8], x);
+
+            if i < ROUNDS - 1 {
+                x = linear_transform(x);
+            } else {
+                xor_key(&mut x, &self.key[ROUNDS]);
+            }
+        }
+
+        (x[0], x[1], x[2], x[3])
+    }
+
+    /// decrypt a block of data.
+    pub fn decrypt(&self, a: u32, b: u32, c: u32, d: u32) -> (u32, u32, u32, u32) {
+        let mut x = [a, b, c, d];
+
+        for i in (0..ROUNDS).rev() {
+            if i < ROUNDS - 1 {
+                x = inverse_linear_transform(x);
+            } else {
+                xor_key(&mut x, &self.key[ROUNDS]);
+            }
+
+            x = sbox(&INV_SBOX_ANF[i % 8], x);
+            xor_key(&mut x, &self.key[i]);
+        }
+
+        (x[0], x[1], x[2], x[3])
+    }
+}
+
+impl BlockCipher for Serpent {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        let (a, b, c, d) = read_le_words(block);
+        let (a, b, c, d) = self.encrypt(a, b, c, d);
+        write_le_words(a, b, c, d)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        let (a, b, c, d) = read_le_words(block);
+        let (a, b, c, d) = self.decrypt(a, b, c, d);
+        write_le_words(a, b, c, d)
+    }
+}
+
+// Methods used by the SOSEMANUK stream cipher, which is built on a
+// 24-round version of Serpent.
+impl Serpent {
+    /// round_key returns the round key at the given index.
+    pub(crate) fn round_key(&self, i: usize) -> [u32; 4] {
+        self.key[i]
+    }
+
+    /// full_round applies round i, always followed by the linear
+    /// transformation.
+    pub(crate) fn full_round(&self, i: usize, x: [u32; 4]) -> [u32; 4] {
+        let mut x = x;
+        xor_key(&mut x, &self.key[i]);
+        linear_transform(sbox(&SBOX_ANF[i % 8], x))
+    }
+}
+
+// Private methods.
+impl Serpent {
+    /// Apply the key expansion algorithm.
+    fn expand_key(&mut self, key: &[u32]) {
+        // The prekey w is preceded by the 8 words of the padded key:
+        // a single 1 bit is appended to keys shorter than 256 bits.
+        let mut w = [0u32; 8 + 4 * (ROUNDS + 1)];
+        w[..key.len()].copy_from_slice(key);
+        if key.len() < 8 {
+            w[key.len()] = 1;
+        }
+
+        for i in 8..w.len() {
+            let t = w[i - 8] ^ w[i - 5] ^ w[i - 3] ^ w[i - 1] ^ PHI ^ (i - 8) as u32;
+            w[i] = t.rotate_left(11);
+        }
+
+        // Round keys go through the S-boxes, starting with S3.
+        for i in 0..ROUNDS + 1 {
+            let k = [w[8 + 4 * i], w[9 + 4 * i], w[10 + 4 * i], w[11 + 4 * i]];
+            self.key[i] = sbox(&SBOX_ANF[(ROUNDS + 3 - i) % 8], k);
+        }
+    }
+}
+
+fn xor_key(x: &mut [u32; 4], k: &[u32; 4]) {
+    for i in 0..4 {
+        x[i] ^= k[i];
+    }
+}
+
+/// sbox applies a 4-bit S-box to the 32 nibbles formed by the bits at the
+/// same position in each word (x[0] holds the least significant bits).
+/// Each output bit is evaluated from its algebraic normal form: a xor of
+/// monomials, where each monomial is an and of input bits.
+pub(crate) fn sbox(anf: &[u16; 4], x: [u32; 4]) -> [u32; 4] {
+    let mut y = [0u32; 4];
+
+    for m in 0..16 {
+        let mut monomial = 0xffffffffu32;
+        for (i, &xi) in x.iter().enumerate() {
+            if m & (1 << i) != 0 {
+                monomial &= xi;
+            }
+        }
+
+        for (yj, &a) in y.iter_mut().zip(anf.iter()) {
+            if a & (1 << m) != 0 {
+                *yj ^= monomial;
+            }
+        }
+    }
+
+    y
+}
+
+/// linear_transform mixes the 32 S-box outputs of a round.
+fn linear_transform(x: [u32; 4]) -> [u32; 4] {
+    let [mut x0, mut x1, mut x2, mut x3] = x;
+
+    x0 = x0.rotate_left(13);
+    x2 = x2.rotate_left(3);
+    x1 ^= x0 ^ x2;
+    x3 ^= x2 ^ (x0 << 3);
+    x1 = x1.rotate_left(1);
+    x3 = x3.rotate_left(7);
+    x0 ^= x1 ^ x3;
+    x2 ^= x3 ^ (x1 << 7);
+    x0 = x0.rotate_left(5);
+    x2 = x2.rotate_left(22);
+
+    [x0, x1, x2, x3]
+}
+
+/// inverse_linear_transform undoes linear_transform.
+fn inverse_linear_transform(x: [u32; 4]) -> [u32; 4] {
+    let [mut x0, mut x1, mut x2, mut x3] = x;
+
+    x2 = x2.rotate_right(22);
+    x0 = x0.rotate_right(5);
+    x2 ^= x3 ^ (x1 << 7);
+    x0 ^= x1 ^ x3;
+    x3 = x3.rotate_right(7);
+    x1 = x1.rotate_right(1);
+    x3 ^= x2 ^ (x0 << 3);
+    x1 ^= x0 ^ x2;
+    x2 = x2.rotate_right(3);
+    x0 = x0.rotate_right(13);
+
+    [x0, x1, x2, x3]
+}
+
+/// Algebraic normal form of the S-boxes.
+/// Bit m of SBOX_ANF[i][j] is set if the monomial made of the input bits
+/// set in m appears in output bit j of S-box i.
+pub(crate) const SBOX_ANF: [[u16; 4]; 8] = [
+    [0x61fb, 0x64e3, 0x45ac, 0x0316],
+    [0x7247, 0x6d3b, 0x011d, 0x6b25],
+    [0x0134, 0x3ad6, 0x3d46, 0x0497],
+    [0x7346, 0x3a26, 0x0d9a, 0x31be],
+    [0x071d, 0x7562, 0x5cda, 0x0e56],
+    [0x071d, 0x1d1b, 0x7925, 0x2397],
+    [0x49f7, 0x0215, 0x5cdb, 0x51bc],
+    [0x7619, 0x2b7c, 0x4f96, 0x02b6],
+];
+
+/// Algebraic normal form of the inverse S-boxes.
+const INV_SBOX_ANF: [[u16; 4]; 8] = [
+    [0x7e59, 0x6436, 0x011f, 0x7943],
+    [0x648f, 0x6794, 0x21e7, 0x0512],
+    [0x0456, 0x3a1c, 0x2f1b, 0x21c9],
+    [0x4752, 0x63d4, 0x3e68, 0x1ab6],
+    [0x3b17, 0x2338, 0x0dbf, 0x1a1c],
+    [0x0942, 0x0be6, 0x2c1a, 0x029d],
+    [0x49eb, 0x0135, 0x5c47, 0x5bdd],
+    [0x5c47, 0x6753, 0x3924, 0x0e98],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::{block, words};
+
+    /// The eight 4-bit S-boxes of the specification.
+    const SBOX: [[u8; 16]; 8] = [
+        [3, 8, 15, 1, 10, 6, 5, 11, 14, 13, 4, 2, 7, 0, 9, 12],
+        [15, 12, 2, 7, 9, 0, 5, 10, 1, 11, 14, 8, 6, 13, 3, 4],
+        [8, 6, 7, 9, 3, 12, 10, 15, 13, 1, 14, 4, 0, 11, 5, 2],
+        [0, 15, 11, 8, 12, 9, 6, 3, 13, 1, 2, 4, 10, 7, 5, 14],
+        [1, 15, 8, 3, 12, 0, 11, 6, 2, 5, 4, 10, 9, 14, 7, 13],
+        [15, 5, 2, 11, 4, 10, 9, 12, 0, 3, 14, 8, 13, 6, 7, 1],
+        [7, 2, 12, 5, 8, 4, 6, 11, 14, 9, 1, 15, 13, 3, 10, 0],
+        [1, 13, 15, 0, 14, 8, 2, 11, 7, 4, 12, 10, 9, 3, 5, 6],
+    ];
+
+    #[test]
+    fn sbox_anf() {
+        // Applying the bitsliced S-box to the 16 possible nibbles (one per
+        // bit position) gives back the S-box table.
+        let x = [0xaaaa, 0xcccc, 0xf0f0, 0xff00];
+        for i in 0..8 {
+            let y = sbox(&SBOX_ANF[i], x);
+            for (n, &expected) in SBOX[i].iter().enumerate() {
+                let out = (y[0] >> n & 1)
+                    | (y[1] >> n & 1) << 1
+                    | (y[2] >> n & 1) << 2
+                    | (y[3] >> n & 1) << 3;
+                assert_eq!(expected as u32, out);
+            }
+
+            assert_eq!(x, sbox(&INV_SBOX_ANF[i], y));
+        }
+    }
+
+    #[test]
+    fn linear_transform_inverse() {
+        let x = [0x01234567, 0x89abcdef, 0xdeadbeef, 0x42424242];
+        assert_ne!(x, linear_transform(x));
+        assert_eq!(x, inverse_linear_transform(linear_transform(x)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_key_too_small() {
+        Serpent::new(&[]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_key_too_big() {
+        Serpent::new(&[0u32; 9]);
+    }
+
+    #[test]
+    fn short_key_padding() {
+        // A 128-bit key is the 256-bit key obtained by appending a 1 bit.
+        let short = Serpent::new(&[1, 2, 3, 4]);
+        let padded = Serpent::new(&[1, 2, 3, 4, 1, 0, 0, 0]);
+        assert_eq!(short.key, padded.key);
+    }
+
+    fn check_vector(key: &str, plain: &str, cipher: &str) {
+        let instance = Serpent::new(&words(key));
+        assert_eq!(block(cipher), instance.encrypt_block(&block(plain)));
+        assert_eq!(block(plain), instance.decrypt_block(&block(cipher)));
+    }
+
+    #[test]
+    fn kat_128() {
+        // NESSIE test vectors.
+        check_vector(
+            "00000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "3620b17ae6a993d09618b8768266bae9",
+        );
+        check_vector(
+            "80000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "264e5481eff42a4606abda06c0bfda3d",
+        );
+    }
+
+    #[test]
+    fn kat_256() {
+        // NESSIE test vectors.
+        check_vector(
+            "8000000000000000000000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "a223aa1288463c0e2be38ebd825616c0",
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let instance = Serpent::new(&[42u32; 8]);
+        let (e1, e2, e3, e4) = instance.encrypt(2, 4, 24, 42);
+        let (d1, d2, d3, d4) = instance.decrypt(e1, e2, e3, e4);
+
+        assert_eq!((2, 4, 24, 42), (d1, d2, d3, d4));
+    }
+}
@@ -0,0 +1,297 @@
+//! # Simon
+//!
+//! `simon` implements the Simon family of lightweight block ciphers,
+//! designed by the NSA alongside Speck.
+//! Simon is tuned for hardware: its round function only uses AND, XOR and
+//! rotations, and its key schedule adds a constant from one of five
+//! 62-bit sequences.
+//! Words have 16, 24, 32, 48 or 64 bits, and blocks are made of two words.
+//! Only Simon128 (64-bit words) implements `BlockCipher`.
+
+use cipher::block::speck::{read_words, write_words};
+use cipher::block::{Block, BlockCipher};
+
+/// The constant sequences z0 to z4 of the key schedule, least significant
+/// bit first.
+const Z: [u64; 5] = [
+    0b01100111000011010100100010111110110011100001101010010001011111,
+    0b01011010000110010011111011100010101101000011001001111101110001,
+    0b11001101101001111110001000010100011001001011000000111011110101,
+    0b11110000101100111001010001001000000111101001100011010111011011,
+    0b11110111001001010011000011101000000100011011010110011110001011,
+];
+
+/// A Simon block cipher.
+pub struct Simon {
+    word_size: u32,
+    mask: u64,
+    round_keys: Vec<u64>,
+}
+
+/// Simon with 128-bit blocks, which can be used as a `BlockCipher`.
+pub struct Simon128 {
+    simon: Simon,
+}
+
+// Public methods.
+impl Simon {
+    /// new creates a Simon cipher with the given word size (in bits) and
+    /// key.
+    /// Key words are given from the most significant to the least
+    /// significant, as in the specification: `(k[m-1], ..., k[0])`.
+    /// Each word must fit in the word size.
+    /// The valid key lengths depend on the word size: 4 words for 16-bit
+    /// words, 3 or 4 words for 24 and 32-bit words, 2 or 3 words for 48-bit
+    /// words, and 2, 3 or 4 words for 64-bit words.
+    /// new panics if the word size or key length is invalid.
+    pub fn new(word_size: u32, key: &[u64]) -> Simon {
+        let (rounds, z) = parameters(word_size, key.len());
+        let mask = u64::MAX >> (64 - word_size);
+        if key.iter().any(|k| k & !mask != 0) {
+            panic!("Key words must fit in the word size.");
+        }
+
+        let mut instance = Simon {
+            word_size,
+            mask,
+            round_keys: vec![0u64; rounds],
+        };
+
+        instance.expand_key(key, Z[z]);
+
+        instance
+    }
+
+    /// encrypt a block of data, made of the words `(x, y)`.
+    pub fn encrypt(&self, x: u64, y: u64) -> (u64, u64) {
+        let mut x = x;
+        let mut y = y;
+
+        for k in self.round_keys.iter() {
+            let xx = x;
+            x = y ^ self.f(x) ^ k;
+            y = xx;
+        }
+
+        (x, y)
+    }
+
+    /// decrypt a block of data, made of the words `(x, y)`.
+    pub fn decrypt(&self, x: u64, y: u64) -> (u64, u64) {
+        let mut x = x;
+        let mut y = y;
+
+        for k in self.round_keys.iter().rev() {
+            let yy = y;
+            y = x ^ self.f(y) ^ k;
+            x = yy;
+        }
+
+        (x, y)
+    }
+}
+
+// Public methods.
+impl Simon128 {
+    /// new creates a Simon128 cipher with a key of 2, 3 or 4 words, from
+    /// the most significant to the least significant.
+    pub fn new(key: &[u64]) -> Simon128 {
+        Simon128 {
+            simon: Simon::new(64, key),
+        }
+    }
+}
+
+// Simon128 uses the same byte order as Speck128.
+impl BlockCipher for Simon128 {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        let (x, y) = read_words(block);
+        let (x, y) = self.simon.encrypt(x, y);
+        write_words(x, y)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        let (x, y) = read_words(block);
+        let (x, y) = self.simon.decrypt(x, y);
+        write_words(x, y)
+    }
+}
+
+// Private methods.
+impl Simon {
+    /// Apply the key schedule.
+    fn expand_key(&mut self, key: &[u64], z: u64) {
+        let m = key.len();
+        let c = self.mask ^ 3;
+
+        for i in 0..m {
+            self.round_keys[i] = key[m - 1 - i];
+        }
+
+        for i in m..self.round_keys.len() {
+            let mut t = self.rotate_right(self.round_keys[i - 1], 3);
+            if m == 4 {
+                t ^= self.round_keys[i - 3];
+            }
+            t ^= self.rotate_right(t, 1);
+
+            let bit = (z >> ((i - m) % 62)) & 1;
+            self.round_keys[i] = c ^ bit ^ self.round_keys[i - m] ^ t;
+        }
+    }
+
+    /// f is the round function.
+    fn f(&self, x: u64) -> u64 {
+        (self.rotate_left(x, 1) & self.rotate_left(x, 8)) ^ self.rotate_left(x, 2)
+    }
+
+    fn rotate_left(&self, x: u64, n: u32) -> u64 {
+        ((x << n) | (x >> (self.word_size - n))) & self.mask
+    }
+
+    fn rotate_right(&self, x: u64, n: u32) -> u64 {
+        ((x >> n) | (x << (self.word_size - n))) & self.mask
+    }
+}
+
+/// parameters returns the number of rounds and the index of the constant
+/// sequence for the given word size and number of key words.
+fn parameters(word_size: u32, key_words: usize) -> (usize, usize) {
+    match (word_size, key_words) {
+        (16, 4) => (32, 0),
+        (24, 3) => (36, 0),
+        (24, 4) => (36, 1),
+        (32, 3) => (42, 2),
+        (32, 4) => (44, 3),
+        (48, 2) => (52, 2),
+        (48, 3) => (54, 3),
+        (64, 2) => (68, 2),
+        (64, 3) => (69, 3),
+        (64, 4) => (72, 4),
+        _ => panic!("Word size and key length must match a variant."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_vector(word_size: u32, key: &[u64], plain: (u64, u64), cipher: (u64, u64)) {
+        let instance = Simon::new(word_size, key);
+        assert_eq!(cipher, instance.encrypt(plain.0, plain.1));
+        assert_eq!(plain, instance.decrypt(cipher.0, cipher.1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_invalid_key_length() {
+        Simon::new(48, &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn z_sequences() {
+        // z0 as printed in the specification, first bit on the left.
+        let z0 = "11111010001001010110000111001101111101000100101011000011100110";
+        for (i, c) in z0.chars().enumerate() {
+            assert_eq!(c == '1', (Z[0] >> i) & 1 == 1);
+        }
+    }
+
+    #[test]
+    fn simon_32() {
+        check_vector(
+            16,
+            &[0x1918, 0x1110, 0x0908, 0x0100],
+            (0x6565, 0x6877),
+            (0xc69b, 0xe9bb),
+        );
+    }
+
+    #[test]
+    fn simon_48() {
+        check_vector(
+            24,
+            &[0x121110, 0x0a0908, 0x020100],
+            (0x612067, 0x6e696c),
+            (0xdae5ac, 0x292cac),
+        );
+        check_vector(
+            24,
+            &[0x1a1918, 0x121110, 0x0a0908, 0x020100],
+            (0x726963, 0x20646e),
+            (0x6e06a5, 0xacf156),
+        );
+    }
+
+    #[test]
+    fn simon_64() {
+        check_vector(
+            32,
+            &[0x13121110, 0x0b0a0908, 0x03020100],
+            (0x6f722067, 0x6e696c63),
+            (0x5ca2e27f, 0x111a8fc8),
+        );
+        check_vector(
+            32,
+            &[0x1b1a1918, 0x13121110, 0x0b0a0908, 0x03020100],
+            (0x656b696c, 0x20646e75),
+            (0x44c8fc20, 0xb9dfa07a),
+        );
+    }
+
+    #[test]
+    fn simon_96() {
+        check_vector(
+            48,
+            &[0x0d0c0b0a0908, 0x050403020100],
+            (0x2072616c6c69, 0x702065687420),
+            (0x602807a462b4, 0x69063d8ff082),
+        );
+        check_vector(
+            48,
+            &[0x151413121110, 0x0d0c0b0a0908, 0x050403020100],
+            (0x746168742074, 0x73756420666f),
+            (0xecad1c6c451e, 0x3f59c5db1ae9),
+        );
+    }
+
+    #[test]
+    fn simon_128() {
+        check_vector(
+            64,
+            &[0x0f0e0d0c0b0a0908, 0x0706050403020100],
+            (0x6373656420737265, 0x6c6c657661727420),
+            (0x49681b1e1e54fe3f, 0x65aa832af84e0bbc),
+        );
+        check_vector(
+            64,
+            &[0x1716151413121110, 0x0f0e0d0c0b0a0908, 0x0706050403020100],
+            (0x206572656874206e, 0x6568772065626972),
+            (0xc4ac61effcdc0d4f, 0x6c9c8d6e2597b85b),
+        );
+        check_vector(
+            64,
+            &[
+                0x1f1e1d1c1b1a1918,
+                0x1716151413121110,
+                0x0f0e0d0c0b0a0908,
+                0x0706050403020100,
+            ],
+            (0x74206e69206d6f6f, 0x6d69732061207369),
+            (0x8d2b5579afc8a3a0, 0x3bf72a87efe7b868),
+        );
+    }
+
+    #[test]
+    fn simon_128_block_cipher() {
+        let key = [0x0f0e0d0c0b0a0908, 0x0706050403020100];
+        let instance = Simon128::new(&key);
+        let reference = Simon::new(64, &key);
+
+        let block = [7u8; 16];
+        let (x, y) = read_words(&block);
+        let (x, y) = reference.encrypt(x, y);
+        assert_eq!(write_words(x, y), instance.encrypt_block(&block));
+        assert_eq!(block, instance.decrypt_block(&write_words(x, y)));
+    }
+}
@@ -0,0 +1,305 @@
+//! # Speck
+//!
+//! `speck` implements the Speck family of lightweight block ciphers,
+//! designed by the NSA for software on constrained devices.
+//! Speck is an ARX cipher: its round function only uses modular additions,
+//! rotations and XORs, and the key schedule reuses the round function.
+//! Words have 16, 24, 32, 48 or 64 bits, and blocks are made of two words.
+//! Only Speck128 (64-bit words) implements `BlockCipher`.
+
+use cipher::block::{Block, BlockCipher, BLOCK_SIZE_BYTES};
+
+/// A Speck block cipher.
+pub struct Speck {
+    word_size: u32,
+    mask: u64,
+    alpha: u32,
+    beta: u32,
+    round_keys: Vec<u64>,
+}
+
+/// Speck with 128-bit blocks, which can be used as a `BlockCipher`.
+pub struct Speck128 {
+    speck: Speck,
+}
+
+// Public methods.
+impl Speck {
+    /// new creates a Speck cipher with the given word size (in bits) and
+    /// key.
+    /// Key words are given from the most significant to the least
+    /// significant, as in the specification: `(l[m-2], ..., l[0], k[0])`.
+    /// Each word must fit in the word size.
+    /// The valid key lengths depend on the word size: 4 words for 16-bit
+    /// words, 3 or 4 words for 24 and 32-bit words, 2 or 3 words for 48-bit
+    /// words, and 2, 3 or 4 words for 64-bit words.
+    /// new panics if the word size or key length is invalid.
+    pub fn new(word_size: u32, key: &[u64]) -> Speck {
+        let rounds = rounds(word_size, key.len());
+        let mask = u64::MAX >> (64 - word_size);
+        if key.iter().any(|k| k & !mask != 0) {
+            panic!("Key words must fit in the word size.");
+        }
+
+        let (alpha, beta) = if word_size == 16 { (7, 2) } else { (8, 3) };
+
+        let mut instance = Speck {
+            word_size,
+            mask,
+            alpha,
+            beta,
+            round_keys: vec![0u64; rounds],
+        };
+
+        instance.expand_key(key);
+
+        instance
+    }
+
+    /// encrypt a block of data, made of the words `(x, y)`.
+    pub fn encrypt(&self, x: u64, y: u64) -> (u64, u64) {
+        let mut x = x;
+        let mut y = y;
+
+        for k in self.round_keys.iter() {
+            let (xx, yy) = self.round(x, y, *k);
+            x = xx;
+            y = yy;
+        }
+
+        (x, y)
+    }
+
+    /// decrypt a block of data, made of the words `(x, y)`.
+    pub fn decrypt(&self, x: u64, y: u64) -> (u64, u64) {
+        let mut x = x;
+        let mut y = y;
+
+        for k in self.round_keys.iter().rev() {
+            y = self.rotate_right(y ^ x, self.beta);
+            x = self.rotate_left((x ^ k).wrapping_sub(y) & self.mask, self.alpha);
+        }
+
+        (x, y)
+    }
+}
+
+// Public methods.
+impl Speck128 {
+    /// new creates a Speck128 cipher with a key of 2, 3 or 4 words, from
+    /// the most significant to the least significant.
+    pub fn new(key: &[u64]) -> Speck128 {
+        Speck128 {
+            speck: Speck::new(64, key),
+        }
+    }
+}
+
+// Speck128 reads blocks as two little-endian words, `y` first.
+impl BlockCipher for Speck128 {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        let (x, y) = read_words(block);
+        let (x, y) = self.speck.encrypt(x, y);
+        write_words(x, y)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        let (x, y) = read_words(block);
+        let (x, y) = self.speck.decrypt(x, y);
+        write_words(x, y)
+    }
+}
+
+// Private methods.
+impl Speck {
+    /// Apply the key schedule, which runs the round function with the round
+    /// number as key.
+    fn expand_key(&mut self, key: &[u64]) {
+        let m = key.len();
+        let mut l: Vec<u64> = key[..m - 1].iter().rev().cloned().collect();
+        let mut k = key[m - 1];
+
+        for i in 0..self.round_keys.len() {
+            self.round_keys[i] = k;
+
+            let (ll, kk) = self.round(l[i], k, i as u64);
+            l.push(ll);
+            k = kk;
+        }
+    }
+
+    fn round(&self, x: u64, y: u64, k: u64) -> (u64, u64) {
+        let x = (self.rotate_right(x, self.alpha).wrapping_add(y) & self.mask) ^ k;
+        let y = self.rotate_left(y, self.beta) ^ x;
+        (x, y)
+    }
+
+    fn rotate_left(&self, x: u64, n: u32) -> u64 {
+        ((x << n) | (x >> (self.word_size - n))) & self.mask
+    }
+
+    fn rotate_right(&self, x: u64, n: u32) -> u64 {
+        ((x >> n) | (x << (self.word_size - n))) & self.mask
+    }
+}
+
+/// rounds returns the number of rounds for the given word size and number
+/// of key words.
+fn rounds(word_size: u32, key_words: usize) -> usize {
+    match (word_size, key_words) {
+        (16, 4) => 22,
+        (24, 3) => 22,
+        (24, 4) => 23,
+        (32, 3) => 26,
+        (32, 4) => 27,
+        (48, 2) => 28,
+        (48, 3) => 29,
+        (64, 2) => 32,
+        (64, 3) => 33,
+        (64, 4) => 34,
+        _ => panic!("Word size and key length must match a variant."),
+    }
+}
+
+/// read_words reads a block as two little-endian words, `y` first.
+pub(super) fn read_words(block: &Block) -> (u64, u64) {
+    let mut y = [0u8; 8];
+    let mut x = [0u8; 8];
+    y.copy_from_slice(&block[..8]);
+    x.copy_from_slice(&block[8..]);
+    (u64::from_le_bytes(x), u64::from_le_bytes(y))
+}
+
+/// write_words writes two words to a block in little-endian order, `y`
+/// first.
+pub(super) fn write_words(x: u64, y: u64) -> Block {
+    let mut block = [0u8; BLOCK_SIZE_BYTES];
+    block[..8].copy_from_slice(&y.to_le_bytes());
+    block[8..].copy_from_slice(&x.to_le_bytes());
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::block;
+
+    fn check_vector(word_size: u32, key: &[u64], plain: (u64, u64), cipher: (u64, u64)) {
+        let instance = Speck::new(word_size, key);
+        assert_eq!(cipher, instance.encrypt(plain.0, plain.1));
+        assert_eq!(plain, instance.decrypt(cipher.0, cipher.1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_invalid_key_length() {
+        Speck::new(16, &[0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_invalid_word_size() {
+        Speck::new(40, &[0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_key_word_too_big() {
+        Speck::new(16, &[0x10000, 0, 0, 0]);
+    }
+
+    #[test]
+    fn speck_32() {
+        check_vector(
+            16,
+            &[0x1918, 0x1110, 0x0908, 0x0100],
+            (0x6574, 0x694c),
+            (0xa868, 0x42f2),
+        );
+    }
+
+    #[test]
+    fn speck_48() {
+        check_vector(
+            24,
+            &[0x121110, 0x0a0908, 0x020100],
+            (0x20796c, 0x6c6172),
+            (0xc049a5, 0x385adc),
+        );
+        check_vector(
+            24,
+            &[0x1a1918, 0x121110, 0x0a0908, 0x020100],
+            (0x6d2073, 0x696874),
+            (0x735e10, 0xb6445d),
+        );
+    }
+
+    #[test]
+    fn speck_64() {
+        check_vector(
+            32,
+            &[0x13121110, 0x0b0a0908, 0x03020100],
+            (0x74614620, 0x736e6165),
+            (0x9f7952ec, 0x4175946c),
+        );
+        check_vector(
+            32,
+            &[0x1b1a1918, 0x13121110, 0x0b0a0908, 0x03020100],
+            (0x3b726574, 0x7475432d),
+            (0x8c6fa548, 0x454e028b),
+        );
+    }
+
+    #[test]
+    fn speck_96() {
+        check_vector(
+            48,
+            &[0x0d0c0b0a0908, 0x050403020100],
+            (0x65776f68202c, 0x656761737520),
+            (0x9e4d09ab7178, 0x62bdde8f79aa),
+        );
+        check_vector(
+            48,
+            &[0x151413121110, 0x0d0c0b0a0908, 0x050403020100],
+            (0x656d6974206e, 0x69202c726576),
+            (0x2bf31072228a, 0x7ae440252ee6),
+        );
+    }
+
+    #[test]
+    fn speck_128() {
+        check_vector(
+            64,
+            &[0x0f0e0d0c0b0a0908, 0x0706050403020100],
+            (0x6c61766975716520, 0x7469206564616d20),
+            (0xa65d985179783265, 0x7860fedf5c570d18),
+        );
+        check_vector(
+            64,
+            &[0x1716151413121110, 0x0f0e0d0c0b0a0908, 0x0706050403020100],
+            (0x7261482066656968, 0x43206f7420746e65),
+            (0x1be4cf3a13135566, 0xf9bc185de03c1886),
+        );
+        check_vector(
+            64,
+            &[
+                0x1f1e1d1c1b1a1918,
+                0x1716151413121110,
+                0x0f0e0d0c0b0a0908,
+                0x0706050403020100,
+            ],
+            (0x65736f6874206e49, 0x202e72656e6f6f70),
+            (0x4109010405c0f53e, 0x4eeeb48d9c188f43),
+        );
+    }
+
+    #[test]
+    fn speck_128_block_cipher() {
+        // Byte-oriented vector of the implementation guide.
+        let instance = Speck128::new(&[0x0f0e0d0c0b0a0908, 0x0706050403020100]);
+        let plain = block("206d616465206974206571756976616c");
+        let cipher = block("180d575cdffe60786532787951985da6");
+        assert_eq!(cipher, instance.encrypt_block(&plain));
+        assert_eq!(plain, instance.decrypt_block(&cipher));
+    }
+}
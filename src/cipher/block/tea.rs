@@ -0,0 +1,101 @@
+//! # TEA
+//!
+//! `tea` implements the Tiny Encryption Algorithm by Wheeler and Needham.
+//! TEA is a Feistel cipher with 64-bit blocks and 128-bit keys, and no key
+//! schedule: the key words are used directly in every cycle.
+//! Each key has three equivalent keys (flipping the most significant bit
+//! of two key words), so TEA is a poor choice for building hash functions.
+//! XTEA (in `xtea`) fixes the related-key weaknesses.
+
+/// The number of cycles (pairs of Feistel rounds).
+const CYCLES: u32 = 32;
+
+/// The key schedule constant, derived from the golden ratio.
+pub(super) const DELTA: u32 = 0x9e3779b9;
+
+/// A TEA block cipher.
+pub struct Tea {
+    key: [u32; 4],
+}
+
+// Public methods.
+impl Tea {
+    /// new creates a TEA cipher with the given key.
+    pub fn new(key: &[u32; 4]) -> Tea {
+        Tea { key: *key }
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, v0: u32, v1: u32) -> (u32, u32) {
+        let mut v0 = v0;
+        let mut v1 = v1;
+        let mut sum = 0u32;
+
+        for _ in 0..CYCLES {
+            sum = sum.wrapping_add(DELTA);
+            v0 = v0.wrapping_add(self.f(v1, sum, 0));
+            v1 = v1.wrapping_add(self.f(v0, sum, 2));
+        }
+
+        (v0, v1)
+    }
+
+    /// decrypt a block of data.
+    pub fn decrypt(&self, v0: u32, v1: u32) -> (u32, u32) {
+        let mut v0 = v0;
+        let mut v1 = v1;
+        let mut sum = DELTA.wrapping_mul(CYCLES);
+
+        for _ in 0..CYCLES {
+            v1 = v1.wrapping_sub(self.f(v0, sum, 2));
+            v0 = v0.wrapping_sub(self.f(v1, sum, 0));
+            sum = sum.wrapping_sub(DELTA);
+        }
+
+        (v0, v1)
+    }
+}
+
+// Private methods.
+impl Tea {
+    /// f is the round function, using the key words at index `k` and
+    /// `k + 1`.
+    fn f(&self, v: u32, sum: u32, k: usize) -> u32 {
+        ((v << 4).wrapping_add(self.key[k]))
+            ^ v.wrapping_add(sum)
+            ^ ((v >> 5).wrapping_add(self.key[k + 1]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_vector(key: &[u32; 4], plain: (u32, u32), cipher: (u32, u32)) {
+        let instance = Tea::new(key);
+        assert_eq!(cipher, instance.encrypt(plain.0, plain.1));
+        assert_eq!(plain, instance.decrypt(cipher.0, cipher.1));
+    }
+
+    #[test]
+    fn known_answers() {
+        let zero = [0u32; 4];
+        let key = [0x01234567, 0x12345678, 0x23456789, 0x3456789a];
+        check_vector(&zero, (0, 0), (0x41ea3a0a, 0x94baa940));
+        check_vector(&zero, (0x01020304, 0x05060708), (0x6a2f9cf3, 0xfccf3c55));
+        check_vector(&key, (0, 0), (0x34e943b0, 0x900f5dcb));
+        check_vector(&key, (0x01020304, 0x05060708), (0x773dc179, 0x878a81c0));
+    }
+
+    #[test]
+    fn equivalent_keys() {
+        let key = [0x01234567, 0x12345678, 0x23456789, 0x3456789a];
+        let equivalent = [key[0] ^ 0x80000000, key[1] ^ 0x80000000, key[2], key[3]];
+
+        let plain = (0xdeadbeef, 0x0badcafe);
+        assert_eq!(
+            Tea::new(&key).encrypt(plain.0, plain.1),
+            Tea::new(&equivalent).encrypt(plain.0, plain.1)
+        );
+    }
+}
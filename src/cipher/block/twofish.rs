@@ -0,0 +1,392 @@
+//! # Twofish
+//!
+//! `twofish` implements the Twofish block cipher.
+//! Twofish was one of the AES finalists.
+//! Its S-boxes depend on the key: they are built from two fixed 8-bit
+//! permutations (q0 and q1) interleaved with key material, and followed
+//! by a maximum distance separable (MDS) matrix.
+//! This implementation recomputes the S-boxes on every call instead of
+//! precomputing them, which makes it easier to follow but slow.
+
+use cipher::block::{read_le_words, write_le_words, Block, BlockCipher};
+
+/// The number of rounds.
+const ROUNDS: usize = 16;
+
+/// A Twofish block cipher.
+pub struct Twofish {
+    key: [u32; 40],
+    s: [u32; 4],
+    k: usize,
+}
+
+// Public methods.
+impl Twofish {
+    /// new creates a twofish cipher with the given key.
+    /// The key needs to contain 4, 6 or 8 words.
+    pub fn new(key: &[u32]) -> Twofish {
+        if key.len() != 4 && key.len() != 6 && key.len() != 8 {
+            panic!("Key length must be 4, 6 or 8.");
+        }
+
+        let mut instance = Twofish {
+            key: [0u32; 40],
+            s: [0u32; 4],
+            k: key.len() / 2,
+        };
+
+        instance.expand_key(key);
+
+        instance
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, a: u32, b: u32, c: u32, d: u32) -> (u32, u32, u32, u32) {
+        // Input whitening.
+        let mut r = [
+            a ^ self.key[0],
+            b ^ self.key[1],
+            c ^ self.key[2],
+            d ^ self.key[3],
+        ];
+
+        for i in 0..ROUNDS {
+            let (f0, f1) = self.f(r[0], r[1], i);
+            r[2] = (r[2] ^ f0).rotate_right(1);
+            r[3] = r[3].rotate_left(1) ^ f1;
+
+            r = [r[2], r[3], r[0], r[1]];
+        }
+
+        // Undo the last swap and apply output whitening.
+        (
+            r[2] ^ self.key[4],
+            r[3] ^ self.key[5],
+            r[0] ^ self.key[6],
+            r[1] ^ self.key[7],
+        )
+    }
+
+    /// decrypt a block of data.
+    pub fn decrypt(&self, a: u32, b: u32, c: u32, d: u32) -> (u32, u32, u32, u32) {
+        let mut r = [
+            c ^ self.key[6],
+            d ^ self.key[7],
+            a ^ self.key[4],
+            b ^ self.key[5],
+        ];
+
+        for i in (0..ROUNDS).rev() {
+            r = [r[2], r[3], r[0], r[1]];
+
+            let (f0, f1) = self.f(r[0], r[1], i);
+            r[2] = r[2].rotate_left(1) ^ f0;
+            r[3] = (r[3] ^ f1).rotate_right(1);
+        }
+
+        (
+            r[0] ^ self.key[0],
+            r[1] ^ self.key[1],
+            r[2] ^ self.key[2],
+            r[3] ^ self.key[3],
+        )
+    }
+}
+
+impl BlockCipher for Twofish {
+    fn encrypt_block(&self, block: &Block) -> Block {
+        let (a, b, c, d) = read_le_words(block);
+        let (a, b, c, d) = self.encrypt(a, b, c, d);
+        write_le_words(a, b, c, d)
+    }
+
+    fn decrypt_block(&self, block: &Block) -> Block {
+        let (a, b, c, d) = read_le_words(block);
+        let (a, b, c, d) = self.decrypt(a, b, c, d);
+        write_le_words(a, b, c, d)
+    }
+}
+
+// Private methods.
+impl Twofish {
+    /// Apply the key expansion algorithm.
+    fn expand_key(&mut self, key: &[u32]) {
+        let k = self.k;
+
+        // Even and odd key words feed the round subkeys.
+        let mut me = [0u32; 4];
+        let mut mo = [0u32; 4];
+        for i in 0..k {
+            me[i] = key[2 * i];
+            mo[i] = key[2 * i + 1];
+        }
+
+        // The S-box key words are derived with the Reed-Solomon code, and
+        // used in reverse order.
+        for i in 0..k {
+            let mut m = [0u8; 8];
+            m[..4].copy_from_slice(&key[2 * i].to_le_bytes());
+            m[4..].copy_from_slice(&key[2 * i + 1].to_le_bytes());
+            self.s[k - 1 - i] = rs(&m);
+        }
+
+        let rho = 0x01010101u32;
+        for i in 0..20 {
+            let a = h(2 * i as u32 * rho, &me[..k]);
+            let b = h((2 * i + 1) as u32 * rho, &mo[..k]).rotate_left(8);
+            self.key[2 * i] = a.wrapping_add(b);
+            self.key[2 * i + 1] = a.wrapping_add(b.wrapping_mul(2)).rotate_left(9);
+        }
+    }
+
+    /// f is the round function.
+    fn f(&self, r0: u32, r1: u32, round: usize) -> (u32, u32) {
+        let t0 = self.g(r0);
+        let t1 = self.g(r1.rotate_left(8));
+
+        // Pseudo-Hadamard transform and subkey addition.
+        let f0 = t0.wrapping_add(t1).wrapping_add(self.key[2 * round + 8]);
+        let f1 = t0
+            .wrapping_add(t1.wrapping_mul(2))
+            .wrapping_add(self.key[2 * round + 9]);
+
+        (f0, f1)
+    }
+
+    /// g applies the key-dependent S-boxes and the MDS matrix.
+    fn g(&self, x: u32) -> u32 {
+        h(x, &self.s[..self.k])
+    }
+}
+
+/// h applies the S-box layers keyed by l, then the MDS matrix.
+fn h(x: u32, l: &[u32]) -> u32 {
+    let mut y = x.to_le_bytes();
+    let mut lb = [[0u8; 4]; 4];
+    for i in 0..l.len() {
+        lb[i] = l[i].to_le_bytes();
+    }
+
+    if l.len() == 4 {
+        y = [
+            q1(y[0]) ^ lb[3][0],
+            q0(y[1]) ^ lb[3][1],
+            q0(y[2]) ^ lb[3][2],
+            q1(y[3]) ^ lb[3][3],
+        ];
+    }
+
+    if l.len() >= 3 {
+        y = [
+            q1(y[0]) ^ lb[2][0],
+            q1(y[1]) ^ lb[2][1],
+            q0(y[2]) ^ lb[2][2],
+            q0(y[3]) ^ lb[2][3],
+        ];
+    }
+
+    y = [
+        q1(q0(q0(y[0]) ^ lb[1][0]) ^ lb[0][0]),
+        q0(q0(q1(y[1]) ^ lb[1][1]) ^ lb[0][1]),
+        q1(q1(q0(y[2]) ^ lb[1][2]) ^ lb[0][2]),
+        q0(q1(q1(y[3]) ^ lb[1][3]) ^ lb[0][3]),
+    ];
+
+    mds(y)
+}
+
+/// mds multiplies a column by the MDS matrix.
+fn mds(y: [u8; 4]) -> u32 {
+    let mut z = [0u8; 4];
+    for i in 0..4 {
+        for j in 0..4 {
+            z[i] ^= gf_mul(MDS[i][j], y[j], MDS_POLY);
+        }
+    }
+
+    u32::from_le_bytes(z)
+}
+
+/// rs multiplies 8 key bytes by the Reed-Solomon matrix.
+fn rs(m: &[u8; 8]) -> u32 {
+    let mut s = [0u8; 4];
+    for i in 0..4 {
+        for j in 0..8 {
+            s[i] ^= gf_mul(RS[i][j], m[j], RS_POLY);
+        }
+    }
+
+    u32::from_le_bytes(s)
+}
+
+/// gf_mul multiplies two elements of GF(2^8) modulo the given polynomial.
+fn gf_mul(a: u8, b: u8, poly: u16) -> u8 {
+    let mut a = u16::from(a);
+    let mut b = b;
+    let mut res = 0u16;
+    while b != 0 {
+        if b & 1 != 0 {
+            res ^= a;
+        }
+
+        a <<= 1;
+        if a & 0x100 != 0 {
+            a ^= poly;
+        }
+
+        b >>= 1;
+    }
+
+    res as u8
+}
+
+fn q0(x: u8) -> u8 {
+    q(&Q0, x)
+}
+
+fn q1(x: u8) -> u8 {
+    q(&Q1, x)
+}
+
+/// q builds an 8-bit permutation from four 4-bit permutations.
+fn q(t: &[[u8; 16]; 4], x: u8) -> u8 {
+    let a0 = x >> 4;
+    let b0 = x & 0xf;
+    let a1 = a0 ^ b0;
+    let b1 = a0 ^ ror4(b0) ^ ((a0 << 3) & 0xf);
+    let a2 = t[0][a1 as usize];
+    let b2 = t[1][b1 as usize];
+    let a3 = a2 ^ b2;
+    let b3 = a2 ^ ror4(b2) ^ ((a2 << 3) & 0xf);
+    let a4 = t[2][a3 as usize];
+    let b4 = t[3][b3 as usize];
+    (b4 << 4) | a4
+}
+
+/// ror4 rotates a nibble to the right by one bit.
+fn ror4(x: u8) -> u8 {
+    ((x >> 1) | (x << 3)) & 0xf
+}
+
+/// Primitive polynomial of the MDS matrix field: x^8 + x^6 + x^5 + x^3 + 1.
+const MDS_POLY: u16 = 0x169;
+
+/// Primitive polynomial of the Reed-Solomon field: x^8 + x^6 + x^3 + x^2 + 1.
+const RS_POLY: u16 = 0x14d;
+
+const MDS: [[u8; 4]; 4] = [
+    [0x01, 0xef, 0x5b, 0x5b],
+    [0x5b, 0xef, 0xef, 0x01],
+    [0xef, 0x5b, 0x01, 0xef],
+    [0xef, 0x01, 0xef, 0x5b],
+];
+
+const RS: [[u8; 8]; 4] = [
+    [0x01, 0xa4, 0x55, 0x87, 0x5a, 0x58, 0xdb, 0x9e],
+    [0xa4, 0x56, 0x82, 0xf3, 0x1e, 0xc6, 0x68, 0xe5],
+    [0x02, 0xa1, 0xfc, 0xc1, 0x47, 0xae, 0x3d, 0x19],
+    [0xa4, 0x55, 0x87, 0x5a, 0x58, 0xdb, 0x9e, 0x03],
+];
+
+/// The 4-bit permutations t0..t3 of q0.
+const Q0: [[u8; 16]; 4] = [
+    [
+        0x8, 0x1, 0x7, 0xd, 0x6, 0xf, 0x3, 0x2, 0x0, 0xb, 0x5, 0x9, 0xe, 0xc, 0xa, 0x4,
+    ],
+    [
+        0xe, 0xc, 0xb, 0x8, 0x1, 0x2, 0x3, 0x5, 0xf, 0x4, 0xa, 0x6, 0x7, 0x0, 0x9, 0xd,
+    ],
+    [
+        0xb, 0xa, 0x5, 0xe, 0x6, 0xd, 0x9, 0x0, 0xc, 0x8, 0xf, 0x3, 0x2, 0x4, 0x7, 0x1,
+    ],
+    [
+        0xd, 0x7, 0xf, 0x4, 0x1, 0x2, 0x6, 0xe, 0x9, 0xb, 0x3, 0x0, 0x8, 0x5, 0xc, 0xa,
+    ],
+];
+
+/// The 4-bit permutations t0..t3 of q1.
+const Q1: [[u8; 16]; 4] = [
+    [
+        0x2, 0x8, 0xb, 0xd, 0xf, 0x7, 0x6, 0xe, 0x3, 0x1, 0x9, 0x4, 0x0, 0xa, 0xc, 0x5,
+    ],
+    [
+        0x1, 0xe, 0x2, 0xb, 0x4, 0xc, 0x3, 0x7, 0x6, 0xd, 0xa, 0x5, 0xf, 0x9, 0x0, 0x8,
+    ],
+    [
+        0x4, 0xc, 0x7, 0x5, 0x1, 0x6, 0x9, 0xa, 0x0, 0xe, 0xd, 0x8, 0x2, 0xb, 0x3, 0xf,
+    ],
+    [
+        0xb, 0x9, 0x5, 0x1, 0xc, 0x3, 0xd, 0xe, 0x6, 0x4, 0x7, 0xf, 0x2, 0x0, 0x8, 0xa,
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::{block, words};
+
+    fn check_vector(key: &str, plain: &str, cipher: &str) {
+        let instance = Twofish::new(&words(key));
+        assert_eq!(block(cipher), instance.encrypt_block(&block(plain)));
+        assert_eq!(block(plain), instance.decrypt_block(&block(cipher)));
+    }
+
+    #[test]
+    fn q_permutations() {
+        // q0 and q1 are permutations and their first values are those of
+        // the specification tables.
+        let mut seen0 = [false; 256];
+        let mut seen1 = [false; 256];
+        for x in 0..256 {
+            seen0[q0(x as u8) as usize] = true;
+            seen1[q1(x as u8) as usize] = true;
+        }
+
+        assert!(seen0.iter().all(|b| *b));
+        assert!(seen1.iter().all(|b| *b));
+        assert_eq!([0xa9, 0x67, 0xb3, 0xe8], [q0(0), q0(1), q0(2), q0(3)]);
+        assert_eq!([0x75, 0xf3, 0xc6, 0xf4], [q1(0), q1(1), q1(2), q1(3)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_invalid_key_length() {
+        Twofish::new(&[0u32; 5]);
+    }
+
+    #[test]
+    fn kat_128() {
+        check_vector(
+            "00000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "9f589f5cf6122c32b6bfec2f2ae8c35a",
+        );
+    }
+
+    #[test]
+    fn kat_192() {
+        check_vector(
+            "0123456789abcdeffedcba98765432100011223344556677",
+            "00000000000000000000000000000000",
+            "cfd1d2e5a9be9cdf501f13b892bd2248",
+        );
+    }
+
+    #[test]
+    fn kat_256() {
+        check_vector(
+            "0123456789abcdeffedcba987654321000112233445566778899aabbccddeeff",
+            "00000000000000000000000000000000",
+            "37527be0052334b89f0cfccae87cfa20",
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let instance = Twofish::new(&[42u32; 8]);
+        let (e1, e2, e3, e4) = instance.encrypt(2, 4, 24, 42);
+        let (d1, d2, d3, d4) = instance.decrypt(e1, e2, e3, e4);
+
+        assert_eq!((2, 4, 24, 42), (d1, d2, d3, d4));
+    }
+}
@@ -0,0 +1,96 @@
+//! # XTEA
+//!
+//! `xtea` implements XTEA, the extended Tiny Encryption Algorithm.
+//! XTEA keeps the structure of TEA but changes the order of the shifts and
+//! XORs, and picks the key word of each round from the running sum, which
+//! removes TEA's equivalent keys.
+
+use cipher::block::tea::DELTA;
+
+/// The number of cycles (pairs of Feistel rounds).
+const CYCLES: u32 = 32;
+
+/// An XTEA block cipher.
+pub struct Xtea {
+    key: [u32; 4],
+}
+
+// Public methods.
+impl Xtea {
+    /// new creates an XTEA cipher with the given key.
+    pub fn new(key: &[u32; 4]) -> Xtea {
+        Xtea { key: *key }
+    }
+
+    /// encrypt a block of data.
+    pub fn encrypt(&self, v0: u32, v1: u32) -> (u32, u32) {
+        let mut v0 = v0;
+        let mut v1 = v1;
+        let mut sum = 0u32;
+
+        for _ in 0..CYCLES {
+            v0 = v0.wrapping_add(self.f(v1, sum, sum));
+            sum = sum.wrapping_add(DELTA);
+            v1 = v1.wrapping_add(self.f(v0, sum, sum >> 11));
+        }
+
+        (v0, v1)
+    }
+
+    /// decrypt a block of data.
+    pub fn decrypt(&self, v0: u32, v1: u32) -> (u32, u32) {
+        let mut v0 = v0;
+        let mut v1 = v1;
+        let mut sum = DELTA.wrapping_mul(CYCLES);
+
+        for _ in 0..CYCLES {
+            v1 = v1.wrapping_sub(self.f(v0, sum, sum >> 11));
+            sum = sum.wrapping_sub(DELTA);
+            v0 = v0.wrapping_sub(self.f(v1, sum, sum));
+        }
+
+        (v0, v1)
+    }
+}
+
+// Private methods.
+impl Xtea {
+    /// f is the round function; the two low bits of `k` select the key
+    /// word.
+    fn f(&self, v: u32, sum: u32, k: u32) -> u32 {
+        (((v << 4) ^ (v >> 5)).wrapping_add(v)) ^ sum.wrapping_add(self.key[(k & 3) as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_vector(key: &[u32; 4], plain: (u32, u32), cipher: (u32, u32)) {
+        let instance = Xtea::new(key);
+        assert_eq!(cipher, instance.encrypt(plain.0, plain.1));
+        assert_eq!(plain, instance.decrypt(cipher.0, cipher.1));
+    }
+
+    #[test]
+    fn known_answers() {
+        let zero = [0u32; 4];
+        let key = [0x01234567, 0x12345678, 0x23456789, 0x3456789a];
+        check_vector(&zero, (0, 0), (0xdee9d4d8, 0xf7131ed9));
+        check_vector(&zero, (0x01020304, 0x05060708), (0x065c1b89, 0x75c6a816));
+        check_vector(&key, (0, 0), (0x1ff9a026, 0x1ac64264));
+        check_vector(&key, (0x01020304, 0x05060708), (0x8c67155b, 0x2ef91ead));
+    }
+
+    #[test]
+    fn no_tea_equivalent_keys() {
+        let key = [0x01234567, 0x12345678, 0x23456789, 0x3456789a];
+        let flipped = [key[0] ^ 0x80000000, key[1] ^ 0x80000000, key[2], key[3]];
+
+        let plain = (0xdeadbeef, 0x0badcafe);
+        assert_ne!(
+            Xtea::new(&key).encrypt(plain.0, plain.1),
+            Xtea::new(&flipped).encrypt(plain.0, plain.1)
+        );
+    }
+}
@@ -5,4 +5,5 @@
 //! a shared secret key.
 
 pub mod block;
+pub mod mode;
 pub mod stream;
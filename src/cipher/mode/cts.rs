@@ -0,0 +1,325 @@
+//! # Ciphertext stealing
+//!
+//! `cts` implements CBC mode with ciphertext stealing, as defined in the
+//! addendum to NIST SP 800-38A.
+//! Ciphertext stealing keeps the ciphertext the same length as the
+//! message: the last partial block is padded with bits "stolen" from the
+//! previous ciphertext block.
+//! The three variants only differ in the order of the last two blocks.
+//! None of them provide integrity, so they should be combined with a MAC.
+
+use std::error;
+use std::fmt;
+
+use cipher::block::mars::Mars;
+use cipher::block::{xor, Block, BlockCipher, BLOCK_SIZE_BYTES};
+
+/// The ciphertext stealing variants of SP 800-38A.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The last two blocks are kept in CBC order: the partial block comes
+    /// second to last.
+    Cs1,
+    /// The last two blocks are swapped, unless the message is a multiple
+    /// of the block size (in which case this is plain CBC).
+    Cs2,
+    /// The last two blocks are always swapped (as in Kerberos).
+    Cs3,
+}
+
+/// Errors returned by ciphertext stealing encryption and decryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The input is shorter than a block: there is nothing to steal from.
+    InputTooShort,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InputTooShort => write!(f, "input is shorter than a block"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A CBC cipher with ciphertext stealing.
+pub struct Cts<C: BlockCipher> {
+    cipher: C,
+    variant: Variant,
+}
+
+/// A CBC cipher with ciphertext stealing instantiated with Mars.
+pub type MarsCts = Cts<Mars>;
+
+// Public methods.
+impl<C: BlockCipher> Cts<C> {
+    /// new creates a ciphertext stealing cipher with the given variant.
+    pub fn new(cipher: C, variant: Variant) -> Cts<C> {
+        Cts { cipher, variant }
+    }
+
+    /// encrypt a message with a given IV.
+    /// The IV must be unpredictable.
+    /// The message must be at least one block long.
+    pub fn encrypt(&self, message: &[u8], iv: Block) -> Result<Vec<u8>, Error> {
+        if message.len() < BLOCK_SIZE_BYTES {
+            return Err(Error::InputTooShort);
+        }
+
+        let n = message.len().div_ceil(BLOCK_SIZE_BYTES);
+        let d = message.len() - (n - 1) * BLOCK_SIZE_BYTES;
+
+        let mut res = Vec::with_capacity(n * BLOCK_SIZE_BYTES);
+        let mut previous = iv;
+        for chunk in message.chunks(BLOCK_SIZE_BYTES) {
+            // The last partial block is padded with zeroes.
+            let mut block = [0u8; BLOCK_SIZE_BYTES];
+            block[..chunk.len()].copy_from_slice(chunk);
+
+            previous = self.cipher.encrypt_block(&xor(&block, &previous));
+            res.extend_from_slice(&previous);
+        }
+
+        if n > 1 {
+            // Drop the stolen bytes from C(n-1) to get the CS1 ciphertext.
+            let stolen = (n - 2) * BLOCK_SIZE_BYTES + d;
+            res.drain(stolen..(n - 1) * BLOCK_SIZE_BYTES);
+
+            if self.swaps(d) {
+                swap_last_blocks(&mut res, d);
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// decrypt a cipher with a given IV.
+    pub fn decrypt(&self, cipher: &[u8], iv: Block) -> Result<Vec<u8>, Error> {
+        if cipher.len() < BLOCK_SIZE_BYTES {
+            return Err(Error::InputTooShort);
+        }
+
+        let n = cipher.len().div_ceil(BLOCK_SIZE_BYTES);
+        let d = cipher.len() - (n - 1) * BLOCK_SIZE_BYTES;
+
+        let mut c = Vec::from(cipher);
+        if n > 1 && self.swaps(d) {
+            // Bring the ciphertext back to the CS1 order.
+            unswap_last_blocks(&mut c, d);
+        }
+
+        let mut res = Vec::with_capacity(cipher.len());
+        let mut previous = iv;
+
+        // Regular CBC decryption of the first n-2 blocks.
+        let regular = if n > 1 { n - 2 } else { n - 1 };
+        for i in 0..regular {
+            let block = to_block(&c[i * BLOCK_SIZE_BYTES..(i + 1) * BLOCK_SIZE_BYTES]);
+            res.extend_from_slice(&xor(&self.cipher.decrypt_block(&block), &previous));
+            previous = block;
+        }
+
+        if n == 1 {
+            let block = to_block(&c);
+            res.extend_from_slice(&xor(&self.cipher.decrypt_block(&block), &previous));
+            return Ok(res);
+        }
+
+        // C(n-1)* is followed by Cn.
+        let offset = (n - 2) * BLOCK_SIZE_BYTES;
+        let partial = &c[offset..offset + d];
+        let last = to_block(&c[offset + d..]);
+
+        // Decrypting Cn gives Pn xor C(n-1), and Pn was padded with zeroes:
+        // the tail of the result is the stolen part of C(n-1).
+        let z = self.cipher.decrypt_block(&last);
+        let mut full = z;
+        full[..d].copy_from_slice(partial);
+
+        res.extend_from_slice(&xor(&self.cipher.decrypt_block(&full), &previous));
+        res.extend_from_slice(&xor(&z, &full)[..d]);
+
+        Ok(res)
+    }
+}
+
+// Private methods.
+impl<C: BlockCipher> Cts<C> {
+    /// swaps returns true if the variant swaps the last two blocks when
+    /// the last block contains d bytes.
+    fn swaps(&self, d: usize) -> bool {
+        match self.variant {
+            Variant::Cs1 => false,
+            Variant::Cs2 => d != BLOCK_SIZE_BYTES,
+            Variant::Cs3 => true,
+        }
+    }
+}
+
+/// swap_last_blocks turns C(n-1)* || Cn into Cn || C(n-1)*, where C(n-1)*
+/// contains d bytes.
+fn swap_last_blocks(c: &mut [u8], d: usize) {
+    let l = c.len();
+    c[l - BLOCK_SIZE_BYTES - d..].rotate_left(d);
+}
+
+/// unswap_last_blocks turns Cn || C(n-1)* into C(n-1)* || Cn, where C(n-1)*
+/// contains d bytes.
+fn unswap_last_blocks(c: &mut [u8], d: usize) {
+    let l = c.len();
+    c[l - BLOCK_SIZE_BYTES - d..].rotate_right(d);
+}
+
+fn to_block(data: &[u8]) -> Block {
+    let mut block = [0u8; BLOCK_SIZE_BYTES];
+    block.copy_from_slice(data);
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::block::aes::Aes;
+    use test_utils::hex;
+
+    const TEST_KEY: [u32; 4] = [1, 2, 3, 4];
+    const TEST_IV: Block = [24u8; 16];
+    const VARIANTS: [Variant; 3] = [Variant::Cs1, Variant::Cs2, Variant::Cs3];
+
+    fn cbc(cipher: &Mars, message: &[u8], iv: Block) -> Vec<u8> {
+        let mut res = Vec::new();
+        let mut previous = iv;
+        for chunk in message.chunks(16) {
+            previous = cipher.encrypt_block(&xor(&to_block(chunk), &previous));
+            res.extend_from_slice(&previous);
+        }
+
+        res
+    }
+
+    #[test]
+    fn swap_and_unswap() {
+        // Only the last 19 bytes are moved: the first 2 bytes belong to
+        // earlier blocks.
+        let mut c: Vec<u8> = (0..21).collect();
+        swap_last_blocks(&mut c, 3);
+        assert_eq!(&[0, 1], &c[..2]);
+        assert_eq!((5..21).collect::<Vec<u8>>(), &c[2..18]);
+        assert_eq!(&[2, 3, 4], &c[18..]);
+
+        unswap_last_blocks(&mut c, 3);
+        assert_eq!((0..21).collect::<Vec<u8>>(), c);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let cipher = Mars::new(&TEST_KEY);
+        for variant in VARIANTS.iter() {
+            let cts = Cts::new(&cipher, *variant);
+            for l in 16..70 {
+                let message: Vec<u8> = (0..l as u8).collect();
+                let encrypted = cts.encrypt(&message, TEST_IV).unwrap();
+                assert_eq!(message.len(), encrypted.len());
+                assert_eq!(message, cts.decrypt(&encrypted, TEST_IV).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn single_block_is_cbc() {
+        let cipher = Mars::new(&TEST_KEY);
+        let message = [42u8; 16];
+        let expected = cbc(&cipher, &message, TEST_IV);
+
+        for variant in VARIANTS.iter() {
+            let cts = Cts::new(&cipher, *variant);
+            assert_eq!(expected, cts.encrypt(&message, TEST_IV).unwrap());
+            assert_eq!(message.to_vec(), cts.decrypt(&expected, TEST_IV).unwrap());
+        }
+    }
+
+    #[test]
+    fn full_blocks() {
+        let cipher = Mars::new(&TEST_KEY);
+        let message = [42u8; 48];
+        let expected = cbc(&cipher, &message, TEST_IV);
+
+        // CS1 and CS2 are plain CBC when no stealing is needed.
+        let cs1 = Cts::new(&cipher, Variant::Cs1);
+        assert_eq!(expected, cs1.encrypt(&message, TEST_IV).unwrap());
+        let cs2 = Cts::new(&cipher, Variant::Cs2);
+        assert_eq!(expected, cs2.encrypt(&message, TEST_IV).unwrap());
+
+        // CS3 still swaps the last two blocks.
+        let cs3 = Cts::new(&cipher, Variant::Cs3);
+        let encrypted = cs3.encrypt(&message, TEST_IV).unwrap();
+        assert_eq!(&expected[..16], &encrypted[..16]);
+        assert_eq!(&expected[32..], &encrypted[16..32]);
+        assert_eq!(&expected[16..32], &encrypted[32..]);
+    }
+
+    #[test]
+    fn partial_block() {
+        let cipher = Mars::new(&TEST_KEY);
+        let message: Vec<u8> = (0..37).collect();
+
+        // CS1 is the CBC encryption of the zero-padded message, minus the
+        // stolen bytes of the second to last block.
+        let mut padded = message.clone();
+        padded.resize(48, 0);
+        let full = cbc(&cipher, &padded, TEST_IV);
+        let cs1 = Cts::new(&cipher, Variant::Cs1)
+            .encrypt(&message, TEST_IV)
+            .unwrap();
+        assert_eq!(&full[..16], &cs1[..16]);
+        assert_eq!(&full[16..21], &cs1[16..21]);
+        assert_eq!(&full[32..], &cs1[21..]);
+
+        // CS2 and CS3 both put the full block before the partial one.
+        for variant in [Variant::Cs2, Variant::Cs3].iter() {
+            let encrypted = Cts::new(&cipher, *variant)
+                .encrypt(&message, TEST_IV)
+                .unwrap();
+            assert_eq!(&full[..16], &encrypted[..16]);
+            assert_eq!(&full[32..], &encrypted[16..32]);
+            assert_eq!(&full[16..21], &encrypted[32..]);
+        }
+    }
+
+    #[test]
+    fn aes_cs3_spec() {
+        // RFC 3962 appendix B: Kerberos uses CBC-CS3 with a zero IV.
+        let cipher = Aes::new(&hex("636869636b656e207465726979616b69"));
+        let cts = Cts::new(&cipher, Variant::Cs3);
+        let message = "I would like the General Gau's Chicken, please, and wonton soup.".as_bytes();
+        let vectors = [
+            (17, "c6353568f2bf8cb4d8a580362da7ff7f97"),
+            (31, "fc00783e0efdb2c1d445d4c8eff7ed2297687268d6ecccc0c07b25e25ecfe5"),
+            (32, "39312523a78662d5be7fcbcc98ebf5a897687268d6ecccc0c07b25e25ecfe584"),
+            (47, "97687268d6ecccc0c07b25e25ecfe584b3fffd940c16a18c1b5549d2f838029e39312523a78662d5be7fcbcc98ebf5"),
+            (48, "97687268d6ecccc0c07b25e25ecfe5849dad8bbb96c4cdc03bc103e1a194bbd839312523a78662d5be7fcbcc98ebf5a8"),
+            (64, "97687268d6ecccc0c07b25e25ecfe58439312523a78662d5be7fcbcc98ebf5a84807efe836ee89a526730dbc2f7bc8409dad8bbb96c4cdc03bc103e1a194bbd8"),
+        ];
+
+        for (l, expected) in vectors.iter() {
+            let expected = hex(expected);
+            assert_eq!(expected, cts.encrypt(&message[..*l], [0u8; 16]).unwrap());
+            assert_eq!(
+                &message[..*l],
+                cts.decrypt(&expected, [0u8; 16]).unwrap().as_slice()
+            );
+        }
+    }
+
+    #[test]
+    fn input_too_short() {
+        let cipher = Mars::new(&TEST_KEY);
+        for variant in VARIANTS.iter() {
+            let cts = Cts::new(&cipher, *variant);
+            assert_eq!(Err(Error::InputTooShort), cts.encrypt(&[0u8; 15], TEST_IV));
+            assert_eq!(Err(Error::InputTooShort), cts.decrypt(&[0u8; 15], TEST_IV));
+        }
+    }
+}
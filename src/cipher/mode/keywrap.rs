@@ -0,0 +1,468 @@
+//! # Key wrap
+//!
+//! `keywrap` implements the key wrap algorithm (RFC 3394) and its padded
+//! variant (RFC 5649).
+//! Key wrap encrypts and authenticates key material under a key-encryption
+//! key, without needing a nonce.
+//! Both RFCs are written for AES but work with any 128-bit block cipher.
+
+use std::error;
+use std::fmt;
+
+use cipher::block::mars::Mars;
+use cipher::block::{BlockCipher, BLOCK_SIZE_BYTES};
+use mac::constant_time_eq;
+
+/// The size of a semiblock (in bytes).
+/// Key wrap processes data in 64-bit semiblocks.
+pub const SEMIBLOCK_SIZE_BYTES: usize = 8;
+
+/// The default initial value of RFC 3394.
+pub const DEFAULT_IV: Semiblock = [0xa6, 0xa6, 0xa6, 0xa6, 0xa6, 0xa6, 0xa6, 0xa6];
+
+/// The constant prefix of the alternative initial value of RFC 5649.
+/// It is followed by the 32-bit big-endian length of the key data.
+pub const AIV_PREFIX: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+/// A 64-bit semiblock.
+pub type Semiblock = [u8; SEMIBLOCK_SIZE_BYTES];
+
+/// Errors returned by key wrapping and unwrapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The key data has a length that cannot be wrapped.
+    InvalidKeyDataLength,
+    /// The wrapped key has a length that cannot be unwrapped.
+    InvalidWrappedKeyLength,
+    /// The integrity check value does not match: the wrapped key was
+    /// tampered with or the key-encryption key is wrong.
+    IntegrityCheckFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidKeyDataLength => write!(f, "invalid key data length"),
+            Error::InvalidWrappedKeyLength => write!(f, "invalid wrapped key length"),
+            Error::IntegrityCheckFailed => write!(f, "integrity check failed"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A key wrap cipher.
+pub struct KeyWrap<C: BlockCipher> {
+    kek: C,
+}
+
+/// A key wrap cipher instantiated with Mars.
+pub type MarsKeyWrap = KeyWrap<Mars>;
+
+// Public methods.
+impl<C: BlockCipher> KeyWrap<C> {
+    /// new creates a key wrap cipher from the key-encryption block cipher.
+    pub fn new(kek: C) -> KeyWrap<C> {
+        KeyWrap { kek }
+    }
+
+    /// wrap key data (RFC 3394).
+    /// The key data must contain at least two semiblocks and its length
+    /// must be a multiple of the semiblock size.
+    pub fn wrap(&self, key_data: &[u8]) -> Result<Vec<u8>, Error> {
+        if key_data.len() < 2 * SEMIBLOCK_SIZE_BYTES
+            || !key_data.len().is_multiple_of(SEMIBLOCK_SIZE_BYTES)
+        {
+            return Err(Error::InvalidKeyDataLength);
+        }
+
+        let mut r = to_semiblocks(key_data);
+        let a = self.w(DEFAULT_IV, &mut r);
+
+        Ok(from_semiblocks(a, &r))
+    }
+
+    /// unwrap a wrapped key (RFC 3394) and check its integrity.
+    pub fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>, Error> {
+        if wrapped.len() < 3 * SEMIBLOCK_SIZE_BYTES
+            || !wrapped.len().is_multiple_of(SEMIBLOCK_SIZE_BYTES)
+        {
+            return Err(Error::InvalidWrappedKeyLength);
+        }
+
+        let mut a = [0u8; SEMIBLOCK_SIZE_BYTES];
+        a.copy_from_slice(&wrapped[..SEMIBLOCK_SIZE_BYTES]);
+        let mut r = to_semiblocks(&wrapped[SEMIBLOCK_SIZE_BYTES..]);
+
+        let a = self.w_inverse(a, &mut r);
+        if !constant_time_eq(&a, &DEFAULT_IV) {
+            return Err(Error::IntegrityCheckFailed);
+        }
+
+        Ok(from_semiblocks_only(&r))
+    }
+
+    /// wrap_with_padding wraps key data of any non-zero length (RFC 5649).
+    pub fn wrap_with_padding(&self, key_data: &[u8]) -> Result<Vec<u8>, Error> {
+        if key_data.is_empty() || key_data.len() > u32::MAX as usize {
+            return Err(Error::InvalidKeyDataLength);
+        }
+
+        let mut aiv = [0u8; SEMIBLOCK_SIZE_BYTES];
+        aiv[..4].copy_from_slice(&AIV_PREFIX);
+        aiv[4..].copy_from_slice(&(key_data.len() as u32).to_be_bytes());
+
+        let padded_len = key_data.len().div_ceil(SEMIBLOCK_SIZE_BYTES) * SEMIBLOCK_SIZE_BYTES;
+        let mut padded = Vec::from(key_data);
+        padded.resize(padded_len, 0);
+
+        if padded_len == SEMIBLOCK_SIZE_BYTES {
+            // A single semiblock is encrypted directly with the AIV.
+            let mut block = [0u8; BLOCK_SIZE_BYTES];
+            block[..SEMIBLOCK_SIZE_BYTES].copy_from_slice(&aiv);
+            block[SEMIBLOCK_SIZE_BYTES..].copy_from_slice(&padded);
+            return Ok(Vec::from(&self.kek.encrypt_block(&block)[..]));
+        }
+
+        let mut r = to_semiblocks(&padded);
+        let a = self.w(aiv, &mut r);
+
+        Ok(from_semiblocks(a, &r))
+    }
+
+    /// unwrap_with_padding unwraps a key wrapped with padding (RFC 5649)
+    /// and checks its integrity.
+    pub fn unwrap_with_padding(&self, wrapped: &[u8]) -> Result<Vec<u8>, Error> {
+        if wrapped.len() < 2 * SEMIBLOCK_SIZE_BYTES
+            || !wrapped.len().is_multiple_of(SEMIBLOCK_SIZE_BYTES)
+        {
+            return Err(Error::InvalidWrappedKeyLength);
+        }
+
+        let (a, r) = if wrapped.len() == BLOCK_SIZE_BYTES {
+            let mut block = [0u8; BLOCK_SIZE_BYTES];
+            block.copy_from_slice(wrapped);
+            let block = self.kek.decrypt_block(&block);
+
+            let mut a = [0u8; SEMIBLOCK_SIZE_BYTES];
+            a.copy_from_slice(&block[..SEMIBLOCK_SIZE_BYTES]);
+            (a, to_semiblocks(&block[SEMIBLOCK_SIZE_BYTES..]))
+        } else {
+            let mut a = [0u8; SEMIBLOCK_SIZE_BYTES];
+            a.copy_from_slice(&wrapped[..SEMIBLOCK_SIZE_BYTES]);
+            let mut r = to_semiblocks(&wrapped[SEMIBLOCK_SIZE_BYTES..]);
+            (self.w_inverse(a, &mut r), r)
+        };
+
+        let padded = from_semiblocks_only(&r);
+
+        // Check the AIV prefix, the message length indicator and the
+        // padding together so that failures are indistinguishable.
+        let mli = u32::from_be_bytes([a[4], a[5], a[6], a[7]]) as usize;
+        let mut valid = constant_time_eq(&a[..4], &AIV_PREFIX);
+        valid &= mli > padded.len() - SEMIBLOCK_SIZE_BYTES && mli <= padded.len();
+        if valid {
+            let mut padding = 0u8;
+            for b in &padded[mli..] {
+                padding |= *b;
+            }
+
+            valid &= padding == 0;
+        }
+
+        if !valid {
+            return Err(Error::IntegrityCheckFailed);
+        }
+
+        Ok(Vec::from(&padded[..mli]))
+    }
+}
+
+// Private methods.
+impl<C: BlockCipher> KeyWrap<C> {
+    /// w is the wrapping function: 6n encryption steps over n semiblocks.
+    /// It returns the final integrity check register.
+    fn w(&self, iv: Semiblock, r: &mut [Semiblock]) -> Semiblock {
+        let n = r.len();
+        let mut a = iv;
+
+        for j in 0..6 {
+            for (i, ri) in r.iter_mut().enumerate() {
+                let b = self.kek.encrypt_block(&concat(&a, ri));
+                let t = (n * j + i + 1) as u64;
+
+                a.copy_from_slice(&b[..SEMIBLOCK_SIZE_BYTES]);
+                xor_counter(&mut a, t);
+                ri.copy_from_slice(&b[SEMIBLOCK_SIZE_BYTES..]);
+            }
+        }
+
+        a
+    }
+
+    /// w_inverse is the unwrapping function: it undoes the 6n steps of w
+    /// in reverse order and returns the recovered integrity check register.
+    fn w_inverse(&self, c0: Semiblock, r: &mut [Semiblock]) -> Semiblock {
+        let n = r.len();
+        let mut a = c0;
+
+        for j in (0..6).rev() {
+            for i in (0..n).rev() {
+                let t = (n * j + i + 1) as u64;
+                xor_counter(&mut a, t);
+                let b = self.kek.decrypt_block(&concat(&a, &r[i]));
+
+                a.copy_from_slice(&b[..SEMIBLOCK_SIZE_BYTES]);
+                r[i].copy_from_slice(&b[SEMIBLOCK_SIZE_BYTES..]);
+            }
+        }
+
+        a
+    }
+}
+
+/// xor_counter xors the big-endian step counter into a semiblock.
+fn xor_counter(a: &mut Semiblock, t: u64) {
+    let t = t.to_be_bytes();
+    for i in 0..SEMIBLOCK_SIZE_BYTES {
+        a[i] ^= t[i];
+    }
+}
+
+fn concat(a: &Semiblock, b: &Semiblock) -> [u8; BLOCK_SIZE_BYTES] {
+    let mut res = [0u8; BLOCK_SIZE_BYTES];
+    res[..SEMIBLOCK_SIZE_BYTES].copy_from_slice(a);
+    res[SEMIBLOCK_SIZE_BYTES..].copy_from_slice(b);
+    res
+}
+
+fn to_semiblocks(data: &[u8]) -> Vec<Semiblock> {
+    data.chunks(SEMIBLOCK_SIZE_BYTES)
+        .map(|c| {
+            let mut s = [0u8; SEMIBLOCK_SIZE_BYTES];
+            s.copy_from_slice(c);
+            s
+        })
+        .collect()
+}
+
+fn from_semiblocks(a: Semiblock, r: &[Semiblock]) -> Vec<u8> {
+    let mut res = Vec::from(&a[..]);
+    res.extend(from_semiblocks_only(r));
+    res
+}
+
+fn from_semiblocks_only(r: &[Semiblock]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(r.len() * SEMIBLOCK_SIZE_BYTES);
+    for s in r {
+        res.extend_from_slice(s);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::block::aes::Aes;
+    use cipher::block::Block;
+    use std::cell::Cell;
+    use test_utils::hex;
+
+    const TEST_KEY: [u32; 4] = [0x03020100, 0x07060504, 0x0b0a0908, 0x0f0e0d0c];
+
+    /// A block cipher that only counts how many times it is called.
+    struct CountingCipher {
+        calls: Cell<usize>,
+    }
+
+    impl BlockCipher for CountingCipher {
+        fn encrypt_block(&self, block: &Block) -> Block {
+            self.calls.set(self.calls.get() + 1);
+            *block
+        }
+
+        fn decrypt_block(&self, block: &Block) -> Block {
+            self.calls.set(self.calls.get() + 1);
+            *block
+        }
+    }
+
+    #[test]
+    fn wrap_uses_6n_steps() {
+        for n in 2..6 {
+            let kw = KeyWrap::new(CountingCipher {
+                calls: Cell::new(0),
+            });
+            let wrapped = kw.wrap(&vec![0u8; 8 * n]).unwrap();
+            assert_eq!(6 * n, kw.kek.calls.get());
+
+            kw.kek.calls.set(0);
+            kw.unwrap(&wrapped).unwrap();
+            assert_eq!(6 * n, kw.kek.calls.get());
+        }
+    }
+
+    #[test]
+    fn wrap_xors_step_counter() {
+        // With an identity cipher the semiblocks are left untouched and A
+        // is only xored with every step counter t, from 1 to 6n.
+        let kw = KeyWrap::new(CountingCipher {
+            calls: Cell::new(0),
+        });
+        let n = 3u64;
+        let wrapped = kw.wrap(&[0u8; 24]).unwrap();
+
+        let mut expected = DEFAULT_IV;
+        for t in 1..(6 * n + 1) {
+            xor_counter(&mut expected, t);
+        }
+
+        assert_eq!(&expected[..], &wrapped[..8]);
+    }
+
+    #[test]
+    fn wrap_and_unwrap() {
+        let kw = MarsKeyWrap::new(Mars::new(&TEST_KEY));
+        for n in 2..6 {
+            let key_data: Vec<u8> = (0..8 * n as u8).collect();
+            let wrapped = kw.wrap(&key_data).unwrap();
+            assert_eq!(key_data.len() + 8, wrapped.len());
+            assert_eq!(key_data, kw.unwrap(&wrapped).unwrap());
+        }
+    }
+
+    #[test]
+    fn wrap_invalid_length() {
+        let kw = MarsKeyWrap::new(Mars::new(&TEST_KEY));
+        assert_eq!(Err(Error::InvalidKeyDataLength), kw.wrap(&[0u8; 8]));
+        assert_eq!(Err(Error::InvalidKeyDataLength), kw.wrap(&[0u8; 17]));
+        assert_eq!(Err(Error::InvalidWrappedKeyLength), kw.unwrap(&[0u8; 16]));
+        assert_eq!(Err(Error::InvalidWrappedKeyLength), kw.unwrap(&[0u8; 25]));
+    }
+
+    #[test]
+    fn unwrap_tampered() {
+        let kw = MarsKeyWrap::new(Mars::new(&TEST_KEY));
+        let mut wrapped = kw.wrap(&[42u8; 16]).unwrap();
+        wrapped[12] ^= 1;
+        assert_eq!(Err(Error::IntegrityCheckFailed), kw.unwrap(&wrapped));
+    }
+
+    #[test]
+    fn unwrap_wrong_key() {
+        let wrapped = MarsKeyWrap::new(Mars::new(&TEST_KEY))
+            .wrap(&[42u8; 16])
+            .unwrap();
+        let kw = MarsKeyWrap::new(Mars::new(&[0u32; 4]));
+        assert_eq!(Err(Error::IntegrityCheckFailed), kw.unwrap(&wrapped));
+    }
+
+    #[test]
+    fn wrap_and_unwrap_with_padding() {
+        let kw = MarsKeyWrap::new(Mars::new(&TEST_KEY));
+        for l in 1..42usize {
+            let key_data: Vec<u8> = (0..l as u8).collect();
+            let wrapped = kw.wrap_with_padding(&key_data).unwrap();
+            assert_eq!(8 + l.div_ceil(8) * 8, wrapped.len());
+            assert_eq!(key_data, kw.unwrap_with_padding(&wrapped).unwrap());
+        }
+    }
+
+    #[test]
+    fn wrap_with_padding_single_semiblock() {
+        // Key data of at most 8 bytes is encrypted as a single block.
+        let cipher = Mars::new(&TEST_KEY);
+        let wrapped = KeyWrap::new(&cipher).wrap_with_padding(&[1, 2, 3]).unwrap();
+
+        let mut block = [0u8; 16];
+        block.copy_from_slice(&wrapped);
+        assert_eq!(
+            [0xa6, 0x59, 0x59, 0xa6, 0, 0, 0, 3, 1, 2, 3, 0, 0, 0, 0, 0],
+            cipher.decrypt_block(&block)
+        );
+    }
+
+    #[test]
+    fn wrap_with_padding_invalid_length() {
+        let kw = MarsKeyWrap::new(Mars::new(&TEST_KEY));
+        assert_eq!(Err(Error::InvalidKeyDataLength), kw.wrap_with_padding(&[]));
+        assert_eq!(
+            Err(Error::InvalidWrappedKeyLength),
+            kw.unwrap_with_padding(&[0u8; 8])
+        );
+        assert_eq!(
+            Err(Error::InvalidWrappedKeyLength),
+            kw.unwrap_with_padding(&[0u8; 20])
+        );
+    }
+
+    #[test]
+    fn unwrap_with_padding_checks_padding() {
+        // Wrap 16 bytes with an AIV that claims only 9 of them are key
+        // data: the non-zero padding must be rejected.
+        let cipher = Mars::new(&TEST_KEY);
+        let kw = KeyWrap::new(&cipher);
+        let mut r = to_semiblocks(&[42u8; 16]);
+        let a = kw.w([0xa6, 0x59, 0x59, 0xa6, 0, 0, 0, 9], &mut r);
+        let wrapped = from_semiblocks(a, &r);
+
+        assert_eq!(
+            Err(Error::IntegrityCheckFailed),
+            kw.unwrap_with_padding(&wrapped)
+        );
+    }
+
+    #[test]
+    fn unwrap_with_padding_checks_length_indicator() {
+        let cipher = Mars::new(&TEST_KEY);
+        let kw = KeyWrap::new(&cipher);
+        let mut r = to_semiblocks(&[0u8; 16]);
+        let a = kw.w([0xa6, 0x59, 0x59, 0xa6, 0, 0, 0, 8], &mut r);
+        let wrapped = from_semiblocks(a, &r);
+
+        assert_eq!(
+            Err(Error::IntegrityCheckFailed),
+            kw.unwrap_with_padding(&wrapped)
+        );
+    }
+
+    #[test]
+    fn unwrap_with_padding_rejects_plain_wrap() {
+        let kw = MarsKeyWrap::new(Mars::new(&TEST_KEY));
+        let wrapped = kw.wrap(&[42u8; 16]).unwrap();
+        assert_eq!(
+            Err(Error::IntegrityCheckFailed),
+            kw.unwrap_with_padding(&wrapped)
+        );
+    }
+
+    #[test]
+    fn aes_wrap_spec() {
+        // RFC 3394 section 4.1.
+        let kw = KeyWrap::new(Aes::new(&hex("000102030405060708090a0b0c0d0e0f")));
+        let key_data = hex("00112233445566778899aabbccddeeff");
+        let wrapped = hex("1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5");
+
+        assert_eq!(wrapped, kw.wrap(&key_data).unwrap());
+        assert_eq!(key_data, kw.unwrap(&wrapped).unwrap());
+    }
+
+    #[test]
+    fn aes_wrap_with_padding_spec() {
+        // RFC 5649 section 6.
+        let kw = KeyWrap::new(Aes::new(&hex(
+            "5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8",
+        )));
+
+        let key_data = hex("c37b7e6492584340bed12207808941155068f738");
+        let wrapped = hex("138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a");
+        assert_eq!(wrapped, kw.wrap_with_padding(&key_data).unwrap());
+        assert_eq!(key_data, kw.unwrap_with_padding(&wrapped).unwrap());
+
+        let key_data = hex("466f7250617369");
+        let wrapped = hex("afbeb0f07dfbf5419200f2ccb50bb24f");
+        assert_eq!(wrapped, kw.wrap_with_padding(&key_data).unwrap());
+        assert_eq!(key_data, kw.unwrap_with_padding(&wrapped).unwrap());
+    }
+}
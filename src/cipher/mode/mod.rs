@@ -0,0 +1,9 @@
+//! # Modes of operation
+//!
+//! `mode` contains modes of operation built on top of the block ciphers
+//! of this crate.
+//! They are generic over the `BlockCipher` trait.
+
+pub mod cts;
+pub mod keywrap;
+pub mod siv;
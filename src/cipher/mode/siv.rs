@@ -0,0 +1,359 @@
+//! # SIV
+//!
+//! `siv` implements the SIV mode of operation (RFC 5297).
+//! SIV is a deterministic authenticated encryption mode: the IV is
+//! derived from the associated data and the message, which makes it
+//! resistant to nonce misuse and suitable for key wrapping.
+//! Encrypting the same message with the same associated data always gives
+//! the same ciphertext, which leaks message equality.
+
+use std::error;
+use std::fmt;
+
+use cipher::block::mars::Mars;
+use cipher::block::{xor, Block, BlockCipher, BLOCK_SIZE_BYTES};
+use mac::cmac::{cmac, dbl, Cmac};
+use mac::constant_time_eq;
+
+/// The maximum number of associated data components.
+/// S2V accepts at most 127 inputs and the message is always the last one.
+pub const MAX_ASSOCIATED_DATA: usize = 126;
+
+/// Errors returned by SIV encryption and decryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// More than `MAX_ASSOCIATED_DATA` associated data components were given.
+    TooManyComponents,
+    /// The ciphertext is too short to contain a synthetic IV.
+    CiphertextTooShort,
+    /// The synthetic IV does not match the decrypted message and associated data.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::TooManyComponents => write!(f, "too many associated data components"),
+            Error::CiphertextTooShort => write!(f, "ciphertext is shorter than the synthetic iv"),
+            Error::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A SIV cipher.
+/// It uses two instances of the same block cipher: one for S2V (the
+/// authentication part) and one for CTR (the encryption part).
+pub struct Siv<C: BlockCipher> {
+    mac: C,
+    ctr: C,
+}
+
+/// A SIV cipher instantiated with Mars.
+pub type MarsSiv = Siv<Mars>;
+
+// Public methods.
+impl<C: BlockCipher> Siv<C> {
+    /// new creates a SIV cipher from its S2V and CTR block ciphers.
+    /// The two ciphers must use independent keys.
+    pub fn new(mac: C, ctr: C) -> Siv<C> {
+        Siv { mac, ctr }
+    }
+
+    /// encrypt a message with the given associated data components.
+    /// The result is the synthetic IV followed by the encrypted message.
+    pub fn encrypt(&self, message: &[u8], associated_data: &[&[u8]]) -> Result<Vec<u8>, Error> {
+        if associated_data.len() > MAX_ASSOCIATED_DATA {
+            return Err(Error::TooManyComponents);
+        }
+
+        let v = self.s2v(associated_data, message);
+
+        let mut res = Vec::with_capacity(BLOCK_SIZE_BYTES + message.len());
+        res.extend_from_slice(&v);
+        res.extend_from_slice(&self.ctr_xor(v, message));
+
+        Ok(res)
+    }
+
+    /// decrypt a cipher with the given associated data components.
+    /// The associated data must be the same (and in the same order) as
+    /// the one used for encryption.
+    pub fn decrypt(&self, cipher: &[u8], associated_data: &[&[u8]]) -> Result<Vec<u8>, Error> {
+        if associated_data.len() > MAX_ASSOCIATED_DATA {
+            return Err(Error::TooManyComponents);
+        }
+
+        if cipher.len() < BLOCK_SIZE_BYTES {
+            return Err(Error::CiphertextTooShort);
+        }
+
+        let mut v = [0u8; BLOCK_SIZE_BYTES];
+        v.copy_from_slice(&cipher[..BLOCK_SIZE_BYTES]);
+
+        let message = self.ctr_xor(v, &cipher[BLOCK_SIZE_BYTES..]);
+        let t = self.s2v(associated_data, &message);
+
+        if !constant_time_eq(&t, &v) {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        Ok(message)
+    }
+}
+
+impl Siv<Mars> {
+    /// from_key creates a Mars SIV cipher from a single key.
+    /// As in RFC 5297, the first half of the key is used for S2V and the
+    /// second half for CTR.
+    /// The key needs to contain an even number of words, between 8 and 28.
+    pub fn from_key(key: &[u32]) -> Siv<Mars> {
+        if !key.len().is_multiple_of(2) {
+            panic!("Key length must be even.");
+        }
+
+        let (k1, k2) = key.split_at(key.len() / 2);
+        Siv::new(Mars::new(k1), Mars::new(k2))
+    }
+}
+
+// Private methods.
+impl<C: BlockCipher> Siv<C> {
+    /// s2v computes the synthetic IV of the given strings.
+    /// The message is always the last S2V input.
+    fn s2v(&self, associated_data: &[&[u8]], message: &[u8]) -> Block {
+        let mut d = cmac(&self.mac, &[0u8; BLOCK_SIZE_BYTES]);
+
+        for ad in associated_data {
+            d = xor(&dbl(&d), &cmac(&self.mac, ad));
+        }
+
+        if message.len() >= BLOCK_SIZE_BYTES {
+            // xorend: xor d into the last block of the message.
+            let (head, tail) = message.split_at(message.len() - BLOCK_SIZE_BYTES);
+            let mut last = [0u8; BLOCK_SIZE_BYTES];
+            last.copy_from_slice(tail);
+
+            let mut mac = Cmac::new(&self.mac);
+            mac.update(head);
+            mac.update(&xor(&last, &d));
+            mac.finalize()
+        } else {
+            cmac(&self.mac, &xor(&dbl(&d), &pad(message)))
+        }
+    }
+
+    /// ctr_xor xors the data with the CTR key stream started at the given
+    /// synthetic IV.
+    fn ctr_xor(&self, v: Block, data: &[u8]) -> Vec<u8> {
+        let mut counter = ctr_iv(v);
+
+        let mut res = Vec::from(data);
+        for chunk in res.chunks_mut(BLOCK_SIZE_BYTES) {
+            let key_stream = self.ctr.encrypt_block(&counter);
+            for i in 0..chunk.len() {
+                chunk[i] ^= key_stream[i];
+            }
+
+            increment(&mut counter);
+        }
+
+        res
+    }
+}
+
+/// ctr_iv clears the 31st and 63rd bits (counting from the right) of the
+/// synthetic IV, so that implementations may use 32-bit counters.
+fn ctr_iv(v: Block) -> Block {
+    let mut q = v;
+    q[8] &= 0x7f;
+    q[12] &= 0x7f;
+    q
+}
+
+/// increment adds one to a big-endian 128-bit counter.
+fn increment(counter: &mut Block) {
+    for b in counter.iter_mut().rev() {
+        *b = b.wrapping_add(1);
+        if *b != 0 {
+            break;
+        }
+    }
+}
+
+/// pad appends a single 1 bit and as many 0 bits as needed to fill a block.
+fn pad(data: &[u8]) -> Block {
+    let mut res = [0u8; BLOCK_SIZE_BYTES];
+    res[..data.len()].copy_from_slice(data);
+    res[data.len()] = 0x80;
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::block::aes::Aes;
+    use test_utils::hex;
+
+    const TEST_KEY: [u32; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    #[test]
+    fn pad_spec() {
+        assert_eq!(
+            [0x42, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            pad(&[0x42])
+        );
+        assert_eq!(
+            [0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            pad(&[])
+        );
+    }
+
+    #[test]
+    fn ctr_iv_clears_bits() {
+        assert_eq!(
+            [
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff, 0x7f, 0xff,
+                0xff, 0xff,
+            ],
+            ctr_iv([0xff; 16])
+        );
+    }
+
+    #[test]
+    fn increment_carries() {
+        let mut counter = [0u8; 16];
+        counter[14] = 0x01;
+        counter[15] = 0xff;
+        increment(&mut counter);
+        assert_eq!(0x02, counter[14]);
+        assert_eq!(0x00, counter[15]);
+
+        let mut counter = [0xff; 16];
+        increment(&mut counter);
+        assert_eq!([0u8; 16], counter);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_key_odd_length() {
+        Siv::from_key(&[0u32; 9]);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let siv = MarsSiv::from_key(&TEST_KEY);
+        let message = "there is no spoon".as_bytes();
+        let ad: [&[u8]; 2] = ["header".as_bytes(), "nonce".as_bytes()];
+
+        let cipher = siv.encrypt(message, &ad).unwrap();
+        assert_eq!(BLOCK_SIZE_BYTES + message.len(), cipher.len());
+
+        let decrypted = siv.decrypt(&cipher, &ad).unwrap();
+        assert_eq!(message, decrypted.as_slice());
+    }
+
+    #[test]
+    fn mars_ctr_key_stream() {
+        // There are no published Mars SIV vectors: check that the message
+        // is encrypted with Mars in CTR mode, under the second half of the
+        // key, starting at the synthetic IV with bits 31 and 63 cleared.
+        let siv = MarsSiv::from_key(&TEST_KEY);
+        let ctr = Mars::new(&TEST_KEY[4..]);
+        let message = [0u8; 2 * BLOCK_SIZE_BYTES];
+        let cipher = siv.encrypt(&message, &[]).unwrap();
+
+        let mut counter = [0u8; BLOCK_SIZE_BYTES];
+        counter.copy_from_slice(&cipher[..BLOCK_SIZE_BYTES]);
+        counter = ctr_iv(counter);
+        assert_eq!(
+            &ctr.encrypt_block(&counter)[..],
+            &cipher[BLOCK_SIZE_BYTES..2 * BLOCK_SIZE_BYTES]
+        );
+
+        increment(&mut counter);
+        assert_eq!(
+            &ctr.encrypt_block(&counter)[..],
+            &cipher[2 * BLOCK_SIZE_BYTES..]
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_lengths() {
+        let siv = MarsSiv::from_key(&TEST_KEY);
+        for l in [0, 1, 15, 16, 17, 32, 33].iter() {
+            let message = vec![42u8; *l];
+            let cipher = siv.encrypt(&message, &[]).unwrap();
+            assert_eq!(message, siv.decrypt(&cipher, &[]).unwrap());
+        }
+    }
+
+    #[test]
+    fn encrypt_is_deterministic() {
+        let siv = MarsSiv::from_key(&TEST_KEY);
+        let message = "there is no spoon".as_bytes();
+        let ad: [&[u8]; 1] = ["header".as_bytes()];
+
+        assert_eq!(
+            siv.encrypt(message, &ad).unwrap(),
+            siv.encrypt(message, &ad).unwrap()
+        );
+        assert_ne!(
+            siv.encrypt(message, &ad).unwrap(),
+            siv.encrypt(message, &[]).unwrap()
+        );
+    }
+
+    #[test]
+    fn associated_data_order_matters() {
+        let siv = MarsSiv::from_key(&TEST_KEY);
+        let message = "there is no spoon".as_bytes();
+        let ad: [&[u8]; 2] = ["a".as_bytes(), "b".as_bytes()];
+        let swapped: [&[u8]; 2] = ["b".as_bytes(), "a".as_bytes()];
+
+        let cipher = siv.encrypt(message, &ad).unwrap();
+        assert_eq!(
+            Err(Error::AuthenticationFailed),
+            siv.decrypt(&cipher, &swapped)
+        );
+    }
+
+    #[test]
+    fn decrypt_tampered_cipher() {
+        let siv = MarsSiv::from_key(&TEST_KEY);
+        let mut cipher = siv.encrypt("there is no spoon".as_bytes(), &[]).unwrap();
+        cipher[20] ^= 1;
+
+        assert_eq!(Err(Error::AuthenticationFailed), siv.decrypt(&cipher, &[]));
+    }
+
+    #[test]
+    fn decrypt_cipher_too_short() {
+        let siv = MarsSiv::from_key(&TEST_KEY);
+        assert_eq!(Err(Error::CiphertextTooShort), siv.decrypt(&[0u8; 15], &[]));
+    }
+
+    #[test]
+    fn too_many_components() {
+        let siv = MarsSiv::from_key(&TEST_KEY);
+        let ad: Vec<&[u8]> = vec![&[]; MAX_ASSOCIATED_DATA + 1];
+
+        assert_eq!(Err(Error::TooManyComponents), siv.encrypt(&[], &ad));
+        assert_eq!(Err(Error::TooManyComponents), siv.decrypt(&[0u8; 16], &ad));
+        assert!(siv.encrypt(&[], &ad[1..]).is_ok());
+    }
+
+    #[test]
+    fn aes_siv_spec() {
+        // RFC 5297 appendix A.1.
+        let key = hex("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let siv = Siv::new(Aes::new(&key[..16]), Aes::new(&key[16..]));
+        let ad = hex("101112131415161718191a1b1c1d1e1f2021222324252627");
+        let message = hex("112233445566778899aabbccddee");
+        let expected = hex("85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c");
+
+        assert_eq!(expected, siv.encrypt(&message, &[&ad]).unwrap());
+        assert_eq!(message, siv.decrypt(&expected, &[&ad]).unwrap());
+    }
+}
@@ -0,0 +1,356 @@
+//! # A5/1 and A5/2
+//!
+//! `a5` implements the A5/1 and A5/2 stream ciphers used to encrypt GSM
+//! voice calls.
+//! Both ciphers combine short LFSRs with irregular clocking, and generate
+//! a new key stream for every 4.6ms frame from the 64-bit session key and
+//! the 22-bit frame number: the first 114 bits encrypt the downlink burst
+//! and the next 114 bits the uplink burst.
+//! A5/1 can be broken with time-memory tradeoffs, and A5/2 (the weakened
+//! export version) in real time from a few frames of ciphertext.
+//! They are only provided to demonstrate these weaknesses.
+
+/// The size of a private key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 8;
+/// The size of a frame number (in bits).
+pub const FRAME_SIZE_BITS: usize = 22;
+/// The size of the key stream of a single burst (in bits).
+pub const BURST_SIZE_BITS: usize = 114;
+
+/// A symmetric private key.
+pub type Key = [u8; KEY_SIZE_BYTES];
+
+/// A LFSR shifting to the left, with its feedback inserted as bit 0.
+#[derive(Debug)]
+struct Register {
+    value: u32,
+    mask: u32,
+    taps: u32,
+}
+
+impl Register {
+    fn new(size: u32, taps: u32) -> Register {
+        Register {
+            value: 0,
+            mask: (1 << size) - 1,
+            taps,
+        }
+    }
+
+    fn clock(&mut self) {
+        let feedback = (self.value & self.taps).count_ones() & 1;
+        self.value = ((self.value << 1) & self.mask) | feedback;
+    }
+
+    fn bit(&self, i: u32) -> u8 {
+        ((self.value >> i) & 1) as u8
+    }
+
+    fn msb(&self) -> u8 {
+        self.bit(31 - self.mask.leading_zeros())
+    }
+
+    /// load clocks the register and XORs a key or frame bit into it.
+    fn load(&mut self, b: u8) {
+        self.clock();
+        self.value ^= u32::from(b);
+    }
+}
+
+/// An A5/1 stream cipher.
+/// Three LFSRs of 19, 22 and 23 bits are clocked irregularly: at each
+/// step, only the registers whose clocking bit agrees with the majority of
+/// the three clocking bits are clocked.
+#[derive(Debug)]
+pub struct A51 {
+    r1: Register,
+    r2: Register,
+    r3: Register,
+}
+
+// Public methods.
+impl A51 {
+    /// new creates an A5/1 cipher with the given key and frame number.
+    pub fn new(key: Key, frame: u32) -> A51 {
+        let mut instance = A51 {
+            r1: Register::new(19, 0x07_2000),
+            r2: Register::new(22, 0x30_0000),
+            r3: Register::new(23, 0x70_0080),
+        };
+
+        for b in input_bits(&key, frame) {
+            instance.r1.load(b);
+            instance.r2.load(b);
+            instance.r3.load(b);
+        }
+
+        // The registers are mixed without producing any output.
+        for _i in 0..100 {
+            instance.clock();
+        }
+
+        instance
+    }
+
+    /// encrypt encrypts a given message.
+    pub fn encrypt(&mut self, message: &[u8]) -> Vec<u8> {
+        message.iter().map(|b| b ^ self.clock_byte()).collect()
+    }
+
+    /// decrypt decrypts a given message.
+    pub fn decrypt(&mut self, cipher: &[u8]) -> Vec<u8> {
+        // Decryption is exactly the same thing as encryption.
+        self.encrypt(cipher)
+    }
+}
+
+// Private methods.
+impl A51 {
+    /// clock clocks the registers whose clocking bit agrees with the
+    /// majority, and returns the key stream bit.
+    fn clock(&mut self) -> u8 {
+        let (c1, c2, c3) = (self.r1.bit(8), self.r2.bit(10), self.r3.bit(10));
+        let m = majority(c1, c2, c3);
+
+        if c1 == m {
+            self.r1.clock();
+        }
+        if c2 == m {
+            self.r2.clock();
+        }
+        if c3 == m {
+            self.r3.clock();
+        }
+
+        self.r1.msb() ^ self.r2.msb() ^ self.r3.msb()
+    }
+
+    fn clock_byte(&mut self) -> u8 {
+        let mut b = 0u8;
+
+        for i in 0..8 {
+            b |= self.clock() << (7 - i);
+        }
+
+        b
+    }
+}
+
+/// An A5/2 stream cipher.
+/// A5/2 uses the three registers of A5/1, but their clocking is controlled
+/// by a fourth register, and their output goes through a nonlinear
+/// majority function.
+/// Since R4 is clocked regularly, guessing its 16 unknown bits makes the
+/// whole cipher linear, which is the basis of the known attacks.
+#[derive(Debug)]
+pub struct A52 {
+    r1: Register,
+    r2: Register,
+    r3: Register,
+    r4: Register,
+}
+
+// Public methods.
+impl A52 {
+    /// new creates an A5/2 cipher with the given key and frame number.
+    pub fn new(key: Key, frame: u32) -> A52 {
+        let mut instance = A52 {
+            r1: Register::new(19, 0x07_2000),
+            r2: Register::new(22, 0x30_0000),
+            r3: Register::new(23, 0x70_0080),
+            r4: Register::new(17, 0x01_0800),
+        };
+
+        instance.load(&key, frame);
+
+        // The registers are mixed without producing any output.
+        for _i in 0..99 {
+            instance.clock();
+        }
+
+        instance
+    }
+
+    /// encrypt encrypts a given message.
+    pub fn encrypt(&mut self, message: &[u8]) -> Vec<u8> {
+        message.iter().map(|b| b ^ self.clock_byte()).collect()
+    }
+
+    /// decrypt decrypts a given message.
+    pub fn decrypt(&mut self, cipher: &[u8]) -> Vec<u8> {
+        // Decryption is exactly the same thing as encryption.
+        self.encrypt(cipher)
+    }
+}
+
+// Private methods.
+impl A52 {
+    /// load loads the key and frame number into all registers, and then
+    /// forces one bit of each register to 1 so that no register is zero.
+    fn load(&mut self, key: &Key, frame: u32) {
+        for b in input_bits(key, frame) {
+            self.r1.load(b);
+            self.r2.load(b);
+            self.r3.load(b);
+            self.r4.load(b);
+        }
+
+        self.r1.value |= 1 << 15;
+        self.r2.value |= 1 << 16;
+        self.r3.value |= 1 << 18;
+        self.r4.value |= 1 << 10;
+    }
+
+    /// clock clocks the registers whose R4 clocking bit agrees with the
+    /// majority, clocks R4, and returns the key stream bit.
+    fn clock(&mut self) -> u8 {
+        let (c1, c2, c3) = (self.r4.bit(10), self.r4.bit(3), self.r4.bit(7));
+        let m = majority(c1, c2, c3);
+
+        if c1 == m {
+            self.r1.clock();
+        }
+        if c2 == m {
+            self.r2.clock();
+        }
+        if c3 == m {
+            self.r3.clock();
+        }
+        self.r4.clock();
+
+        let (r1, r2, r3) = (&self.r1, &self.r2, &self.r3);
+        r1.msb()
+            ^ r2.msb()
+            ^ r3.msb()
+            ^ majority(r1.bit(12), r1.bit(14) ^ 1, r1.bit(15))
+            ^ majority(r2.bit(9), r2.bit(13), r2.bit(16) ^ 1)
+            ^ majority(r3.bit(13) ^ 1, r3.bit(16), r3.bit(18))
+    }
+
+    fn clock_byte(&mut self) -> u8 {
+        let mut b = 0u8;
+
+        for i in 0..8 {
+            b |= self.clock() << (7 - i);
+        }
+
+        b
+    }
+}
+
+/// input_bits returns the 64 key bits followed by the 22 frame number bits,
+/// least significant bit first.
+/// It panics if the frame number does not fit in 22 bits.
+fn input_bits(key: &Key, frame: u32) -> Vec<u8> {
+    if frame >> FRAME_SIZE_BITS != 0 {
+        panic!("Frame number must fit in 22 bits.");
+    }
+
+    let key_bits = (0..8 * KEY_SIZE_BYTES).map(|i| (key[i / 8] >> (i % 8)) & 1);
+    let frame_bits = (0..FRAME_SIZE_BITS).map(|i| ((frame >> i) & 1) as u8);
+
+    key_bits.chain(frame_bits).collect()
+}
+
+fn majority(a: u8, b: u8, c: u8) -> u8 {
+    (a & b) | (a & c) | (b & c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    /// bursts splits 228 key stream bits into the downlink and uplink
+    /// bursts, most significant bit first.
+    fn bursts(key_stream: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let bit = |i: usize| (key_stream[i / 8] >> (7 - i % 8)) & 1;
+        let burst = |offset: usize| {
+            let mut res = vec![0u8; BURST_SIZE_BITS.div_ceil(8)];
+            for i in 0..BURST_SIZE_BITS {
+                res[i / 8] |= bit(offset + i) << (7 - i % 8);
+            }
+            res
+        };
+
+        (burst(0), burst(BURST_SIZE_BITS))
+    }
+
+    #[test]
+    fn a51_reference_vector() {
+        // Test vector of the reference implementation by Briceno, Goldberg
+        // and Wagner.
+        let key = [0x12, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+        let mut cipher = A51::new(key, 0x134);
+
+        let (downlink, uplink) = bursts(&cipher.encrypt(&[0u8; 29]));
+        assert_eq!(hex("534eaa582fe8151ab6e1855a728c00"), downlink);
+        assert_eq!(hex("24fd35a35d5fb6526d32f906df1ac0"), uplink);
+    }
+
+    #[test]
+    fn a52_reference_vector() {
+        // Test vector of the reference implementation by Briceno, Goldberg
+        // and Wagner.
+        let key = [0x00, 0xfc, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        let mut cipher = A52::new(key, 0x21);
+
+        let (downlink, uplink) = bursts(&cipher.encrypt(&[0u8; 29]));
+        assert_eq!(hex("f4512cac13593764460b722dadd500"), downlink);
+        assert_eq!(hex("4800d4328e16a14dcd7b9722265100"), uplink);
+    }
+
+    #[test]
+    fn a52_load() {
+        let mut cipher = A52 {
+            r1: Register::new(19, 0x07_2000),
+            r2: Register::new(22, 0x30_0000),
+            r3: Register::new(23, 0x70_0080),
+            r4: Register::new(17, 0x01_0800),
+        };
+
+        cipher.load(&[0u8; KEY_SIZE_BYTES], 0);
+
+        assert_eq!(1 << 15, cipher.r1.value);
+        assert_eq!(1 << 16, cipher.r2.value);
+        assert_eq!(1 << 18, cipher.r3.value);
+        assert_eq!(1 << 10, cipher.r4.value);
+    }
+
+    #[test]
+    fn frames_use_distinct_key_streams() {
+        let key = [42u8; KEY_SIZE_BYTES];
+
+        let a51 = A51::new(key, 0).encrypt(&[0u8; 15]);
+        assert_ne!(a51, A51::new(key, 1).encrypt(&[0u8; 15]));
+
+        let a52 = A52::new(key, 0).encrypt(&[0u8; 15]);
+        assert_ne!(a52, A52::new(key, 1).encrypt(&[0u8; 15]));
+        assert_ne!(a51, a52);
+    }
+
+    #[test]
+    #[should_panic]
+    fn frame_too_large() {
+        A51::new([42u8; KEY_SIZE_BYTES], 1 << FRAME_SIZE_BITS);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let mut encrypt_cipher = A51::new([42u8; KEY_SIZE_BYTES], 24);
+        let encrypted = encrypt_cipher.encrypt("there is no spoon".as_bytes());
+
+        let mut decrypt_cipher = A51::new([42u8; KEY_SIZE_BYTES], 24);
+        let decrypted = decrypt_cipher.decrypt(&encrypted);
+
+        assert_eq!("there is no spoon".as_bytes(), decrypted.as_slice());
+
+        let mut encrypt_cipher = A52::new([42u8; KEY_SIZE_BYTES], 24);
+        let encrypted = encrypt_cipher.encrypt("there is no spoon".as_bytes());
+
+        let mut decrypt_cipher = A52::new([42u8; KEY_SIZE_BYTES], 24);
+        let decrypted = decrypt_cipher.decrypt(&encrypted);
+
+        assert_eq!("there is no spoon".as_bytes(), decrypted.as_slice());
+    }
+}
@@ -0,0 +1,415 @@
+//! # Grain-128a
+//!
+//! `grain` implements the Grain-128a stream cipher, the successor of
+//! Grain from the hardware profile of the eSTREAM portfolio.
+//! Grain-128a combines a 128-bit LFSR with a 128-bit NFSR, filtered by a
+//! nonlinear output function.
+//! When the first IV bit is set, Grain-128a also authenticates the message:
+//! half of the output bits are used as key stream, and the other half feed
+//! a 32-bit tag accumulator.
+
+use std::error;
+use std::fmt;
+
+use mac::constant_time_eq;
+
+/// The size of a private key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 16;
+/// The size of the initialization vector (in bytes).
+pub const IV_SIZE_BYTES: usize = 12;
+/// The size of an authentication tag (in bytes).
+pub const TAG_SIZE_BYTES: usize = 4;
+
+/// An initialization vector.
+pub type IV = [u8; IV_SIZE_BYTES];
+/// A symmetric private key.
+pub type Key = [u8; KEY_SIZE_BYTES];
+/// An authentication tag.
+pub type Tag = [u8; TAG_SIZE_BYTES];
+
+/// The taps of the NFSR added linearly to the output.
+const OUTPUT_TAPS: [u32; 7] = [2, 15, 36, 45, 64, 73, 89];
+
+/// Errors returned by Grain-128a authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Authentication was not enabled by the IV.
+    AuthenticationDisabled,
+    /// The tag does not match the message.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::AuthenticationDisabled => write!(f, "authentication is disabled"),
+            Error::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A Grain-128a stream cipher.
+#[derive(Debug)]
+pub struct Grain128a {
+    lfsr: u128,
+    nfsr: u128,
+    auth: Option<Authenticator>,
+}
+
+/// The accumulator and shift register of the authentication mode.
+#[derive(Debug)]
+struct Authenticator {
+    accumulator: u32,
+    register: u32,
+}
+
+// Public methods.
+impl Grain128a {
+    /// new creates a Grain-128a cipher with the given key and IV.
+    /// Authentication is enabled if the first IV bit (the most significant
+    /// bit of the first byte) is set.
+    pub fn new(iv: IV, key: Key) -> Grain128a {
+        let mut instance = Grain128a {
+            lfsr: 0,
+            nfsr: 0,
+            auth: None,
+        };
+
+        instance.init(&iv, &key);
+
+        instance
+    }
+
+    /// encrypt encrypts a given message.
+    pub fn encrypt(&mut self, message: &[u8]) -> Vec<u8> {
+        self.process(message, false)
+    }
+
+    /// decrypt decrypts a given message.
+    pub fn decrypt(&mut self, cipher: &[u8]) -> Vec<u8> {
+        self.process(cipher, true)
+    }
+
+    /// finalize returns the tag of the processed message.
+    pub fn finalize(self) -> Result<Tag, Error> {
+        match self.auth {
+            // The message is padded with a single 1 bit, and the tag bits
+            // are output most significant bit first.
+            Some(auth) => Ok((auth.accumulator ^ auth.register)
+                .reverse_bits()
+                .to_be_bytes()),
+            None => Err(Error::AuthenticationDisabled),
+        }
+    }
+
+    /// verify checks the tag of the processed message in constant time.
+    pub fn verify(self, tag: &Tag) -> Result<(), Error> {
+        let expected = self.finalize()?;
+        if !constant_time_eq(&expected, tag) {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        Ok(())
+    }
+}
+
+// Private methods.
+impl Grain128a {
+    /// init loads the key and IV, clocks the cipher 256 times with the
+    /// output fed back into both registers, and fills the authenticator
+    /// with the next 64 output bits if authentication is enabled.
+    fn init(&mut self, iv: &IV, key: &Key) {
+        // Bit i of the registers holds b_i and s_i; key and IV bits are
+        // loaded most significant bit first.
+        for i in 0..128 {
+            if (key[i / 8] >> (7 - i % 8)) & 1 == 1 {
+                self.nfsr |= 1 << i;
+            }
+        }
+
+        for i in 0..96 {
+            if (iv[i / 8] >> (7 - i % 8)) & 1 == 1 {
+                self.lfsr |= 1 << i;
+            }
+        }
+        // The LFSR is padded with 31 ones and a zero.
+        self.lfsr |= 0x7fffffff << 96;
+
+        for _ in 0..256 {
+            let y = self.output();
+            self.clock(y);
+        }
+
+        if iv[0] & 0x80 != 0 {
+            let mut auth = Authenticator {
+                accumulator: 0,
+                register: 0,
+            };
+            for i in 0..32 {
+                auth.accumulator |= u32::from(self.pre_output()) << i;
+            }
+            for i in 0..32 {
+                auth.register |= u32::from(self.pre_output()) << i;
+            }
+            self.auth = Some(auth);
+        }
+    }
+
+    /// output computes the pre-output bit y from the current state.
+    fn output(&self) -> u8 {
+        let b = |i: u32| ((self.nfsr >> i) & 1) as u8;
+        let s = |i: u32| ((self.lfsr >> i) & 1) as u8;
+
+        let h = (b(12) & s(8))
+            ^ (s(13) & s(20))
+            ^ (b(95) & s(42))
+            ^ (s(60) & s(79))
+            ^ (b(12) & b(95) & s(94));
+
+        OUTPUT_TAPS.iter().fold(h ^ s(93), |acc, i| acc ^ b(*i))
+    }
+
+    /// clock shifts both registers; the feedback bit is XORed into the
+    /// new bits during initialization.
+    fn clock(&mut self, feedback: u8) {
+        let b = |i: u32| ((self.nfsr >> i) & 1) as u8;
+        let s = |i: u32| ((self.lfsr >> i) & 1) as u8;
+
+        let new_s = s(0) ^ s(7) ^ s(38) ^ s(70) ^ s(81) ^ s(96) ^ feedback;
+        let new_b = s(0)
+            ^ b(0)
+            ^ b(26)
+            ^ b(56)
+            ^ b(91)
+            ^ b(96)
+            ^ (b(3) & b(67))
+            ^ (b(11) & b(13))
+            ^ (b(17) & b(18))
+            ^ (b(27) & b(59))
+            ^ (b(40) & b(48))
+            ^ (b(61) & b(65))
+            ^ (b(68) & b(84))
+            ^ (b(88) & b(92) & b(93) & b(95))
+            ^ (b(22) & b(24) & b(25))
+            ^ (b(70) & b(78) & b(82))
+            ^ feedback;
+
+        self.lfsr = (self.lfsr >> 1) | (u128::from(new_s) << 127);
+        self.nfsr = (self.nfsr >> 1) | (u128::from(new_b) << 127);
+    }
+
+    /// pre_output returns the next pre-output bit and clocks the cipher.
+    fn pre_output(&mut self) -> u8 {
+        let y = self.output();
+        self.clock(0);
+        y
+    }
+
+    /// process XORs the input with the key stream, most significant bit
+    /// first.
+    /// With authentication, only the even pre-output bits are used as key
+    /// stream: each message bit adds the shift register to the
+    /// accumulator, and the odd pre-output bits are shifted into the
+    /// register.
+    fn process(&mut self, input: &[u8], decrypting: bool) -> Vec<u8> {
+        let mut res = Vec::with_capacity(input.len());
+
+        for b in input {
+            let mut out = 0u8;
+            for i in 0..8 {
+                let bit = (b >> (7 - i)) & 1;
+                let z = self.pre_output();
+                out |= (bit ^ z) << (7 - i);
+
+                if self.auth.is_some() {
+                    let message_bit = if decrypting { bit ^ z } else { bit };
+                    let y = self.pre_output();
+                    if let Some(ref mut auth) = self.auth {
+                        auth.update(message_bit, y);
+                    }
+                }
+            }
+            res.push(out);
+        }
+
+        res
+    }
+}
+
+impl Authenticator {
+    /// update accumulates a message bit, and shifts a pre-output bit into
+    /// the register.
+    fn update(&mut self, message_bit: u8, y: u8) {
+        if message_bit == 1 {
+            self.accumulator ^= self.register;
+        }
+        self.register = (self.register >> 1) | (u32::from(y) << 31);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    fn key_and_iv(key: &str, iv: &str) -> (Key, IV) {
+        let mut k = [0u8; KEY_SIZE_BYTES];
+        k.copy_from_slice(&hex(key));
+        let mut v = [0u8; IV_SIZE_BYTES];
+        v.copy_from_slice(&hex(iv));
+        (k, v)
+    }
+
+    #[test]
+    fn specification_vectors() {
+        // Pre-output test vectors of the specification (without
+        // authentication, the pre-output is the key stream).
+        let (key, iv) = key_and_iv(
+            "00000000000000000000000000000000",
+            "000000000000000000000000",
+        );
+        let mut cipher = Grain128a::new(iv, key);
+        assert_eq!(
+            hex(
+                "c0207f221660650b6a952ae26586136fa0904140c8621cfe8660c0dec0969e94\
+                 36f4ace92cf1ebb7"
+            ),
+            cipher.encrypt(&[0u8; 40])
+        );
+
+        let (key, iv) = key_and_iv(
+            "0123456789abcdef123456789abcdef0",
+            "0123456789abcdef12345678",
+        );
+        let mut cipher = Grain128a::new(iv, key);
+        assert_eq!(
+            hex(
+                "f88720c13f46e6a43c07eeed89161a4dd73bd6b8be8b6b116879714ebb630e0a\
+                 4c12f0399412982c"
+            ),
+            cipher.encrypt(&[0u8; 40])
+        );
+    }
+
+    #[test]
+    fn authentication_vectors() {
+        // With authentication, the first 64 pre-output bits of the
+        // specification (564b362219bd90e3 for the zero key) fill the
+        // accumulator and the shift register.
+        // The tags were computed with an independent bit-level model of
+        // the specification, which reproduces the pre-output vectors above.
+        let (key, iv) = key_and_iv(
+            "00000000000000000000000000000000",
+            "800000000000000000000000",
+        );
+        let cipher = Grain128a::new(iv, key);
+        let auth = cipher.auth.as_ref().unwrap();
+        assert_eq!(
+            hex("564b3622"),
+            auth.accumulator.reverse_bits().to_be_bytes()
+        );
+        assert_eq!(hex("19bd90e3"), auth.register.reverse_bits().to_be_bytes());
+
+        let zero = (
+            "00000000000000000000000000000000",
+            "800000000000000000000000",
+        );
+        let other = (
+            "0123456789abcdef123456789abcdef0",
+            "8123456789abcdef12345678",
+        );
+        let vectors = [
+            (zero, "", "", "4ff6a6c1"),
+            (zero, "0123456789abcdef", "0d2b1f2ebc83da7e", "f071e2d8"),
+            (other, "", "", "d2d1bda8"),
+            (other, "00", "a4", "845ad287"),
+            (other, "0123456789abcdef", "a49d971c976bf596", "edbdef02"),
+        ];
+        for ((key, iv), message, stream, tag) in vectors.iter() {
+            let (key, iv) = key_and_iv(key, iv);
+            let message = hex(message);
+            let expected: Vec<u8> = message
+                .iter()
+                .zip(hex(stream).iter())
+                .map(|(m, z)| m ^ z)
+                .collect();
+
+            let mut cipher = Grain128a::new(iv, key);
+            assert_eq!(expected, cipher.encrypt(&message));
+            assert_eq!(hex(tag), cipher.finalize().unwrap());
+        }
+    }
+
+    #[test]
+    fn authentication_uses_even_bits() {
+        let (key, iv) = key_and_iv(
+            "0123456789abcdef123456789abcdef0",
+            "8123456789abcdef12345678",
+        );
+        let mut cipher = Grain128a::new(iv, key);
+        let mut raw = Grain128a {
+            lfsr: cipher.lfsr,
+            nfsr: cipher.nfsr,
+            auth: None,
+        };
+
+        let stream = cipher.encrypt(&[0u8; 8]);
+        let pre_output = raw.encrypt(&[0u8; 16]);
+        for i in 0..64 {
+            let z = (stream[i / 8] >> (7 - i % 8)) & 1;
+            let y = (pre_output[i / 4] >> (7 - 2 * (i % 4))) & 1;
+            assert_eq!(y, z);
+        }
+    }
+
+    #[test]
+    fn encrypt_and_verify() {
+        let (key, iv) = key_and_iv(
+            "0123456789abcdef123456789abcdef0",
+            "8123456789abcdef12345678",
+        );
+        let message = "there is no spoon".as_bytes();
+
+        let mut cipher = Grain128a::new(iv, key);
+        let encrypted = cipher.encrypt(message);
+        let tag = cipher.finalize().unwrap();
+
+        let mut cipher = Grain128a::new(iv, key);
+        assert_eq!(message, cipher.decrypt(&encrypted).as_slice());
+        assert_eq!(Ok(()), cipher.verify(&tag));
+
+        let mut tampered = encrypted.clone();
+        tampered[3] ^= 0x10;
+        let mut cipher = Grain128a::new(iv, key);
+        cipher.decrypt(&tampered);
+        assert_eq!(Err(Error::AuthenticationFailed), cipher.verify(&tag));
+
+        // The padding bit makes the tag depend on the message length.
+        let mut cipher = Grain128a::new(iv, key);
+        cipher.decrypt(&encrypted[..encrypted.len() - 1]);
+        assert_eq!(Err(Error::AuthenticationFailed), cipher.verify(&tag));
+    }
+
+    #[test]
+    fn authentication_disabled() {
+        let (key, iv) = key_and_iv(
+            "0123456789abcdef123456789abcdef0",
+            "0123456789abcdef12345678",
+        );
+        let cipher = Grain128a::new(iv, key);
+        assert_eq!(Err(Error::AuthenticationDisabled), cipher.finalize());
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let mut encrypt_cipher = Grain128a::new([24u8; IV_SIZE_BYTES], [42u8; KEY_SIZE_BYTES]);
+        let encrypted = encrypt_cipher.encrypt("there is no spoon".as_bytes());
+
+        let mut decrypt_cipher = Grain128a::new([24u8; IV_SIZE_BYTES], [42u8; KEY_SIZE_BYTES]);
+        let decrypted = decrypt_cipher.decrypt(&encrypted);
+
+        assert_eq!("there is no spoon".as_bytes(), decrypted.as_slice());
+    }
+}
@@ -0,0 +1,207 @@
+//! # HC-128
+//!
+//! `hc128` implements the HC-128 stream cipher, from the software profile
+//! of the eSTREAM portfolio.
+//! HC-128 keeps two secret tables of 512 words, which are updated with
+//! nonlinear feedback functions and used as S-boxes for each other.
+//! Setting up a key and IV takes 1024 steps, but generating the key
+//! stream is then very fast.
+
+/// The size of a private key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 16;
+/// The size of the initialization vector (in bytes).
+pub const IV_SIZE_BYTES: usize = 16;
+
+/// An initialization vector.
+pub type IV = [u8; IV_SIZE_BYTES];
+/// A symmetric private key.
+pub type Key = [u8; KEY_SIZE_BYTES];
+
+/// The size of each table (in words).
+const TABLE_SIZE: usize = 512;
+
+/// An HC-128 stream cipher.
+pub struct Hc128 {
+    p: [u32; TABLE_SIZE],
+    q: [u32; TABLE_SIZE],
+    counter: usize,
+    word: [u8; 4],
+    used: usize,
+}
+
+// Public methods.
+impl Hc128 {
+    /// new creates an HC-128 cipher with the given key and IV.
+    pub fn new(iv: IV, key: Key) -> Hc128 {
+        let mut instance = Hc128 {
+            p: [0u32; TABLE_SIZE],
+            q: [0u32; TABLE_SIZE],
+            counter: 0,
+            word: [0u8; 4],
+            used: 4,
+        };
+
+        instance.init(&iv, &key);
+
+        instance
+    }
+
+    /// encrypt encrypts a given message.
+    pub fn encrypt(&mut self, message: &[u8]) -> Vec<u8> {
+        message.iter().map(|b| b ^ self.next_byte()).collect()
+    }
+
+    /// decrypt decrypts a given message.
+    pub fn decrypt(&mut self, cipher: &[u8]) -> Vec<u8> {
+        // Decryption is exactly the same thing as encryption.
+        self.encrypt(cipher)
+    }
+}
+
+// Private methods.
+impl Hc128 {
+    /// init expands the key and IV into the tables, and runs the cipher
+    /// 1024 steps, feeding the output back into the tables.
+    fn init(&mut self, iv: &IV, key: &Key) {
+        let mut w = [0u32; 1280];
+        for i in 0..8 {
+            w[i] = read_word(key, i % 4);
+            w[i + 8] = read_word(iv, i % 4);
+        }
+
+        for i in 16..1280 {
+            w[i] = f2(w[i - 2])
+                .wrapping_add(w[i - 7])
+                .wrapping_add(f1(w[i - 15]))
+                .wrapping_add(w[i - 16])
+                .wrapping_add(i as u32);
+        }
+
+        self.p.copy_from_slice(&w[256..768]);
+        self.q.copy_from_slice(&w[768..1280]);
+
+        // During initialization, the key stream word replaces the updated
+        // element.
+        for _ in 0..1024 {
+            let (updated, s) = self.step();
+            if updated < TABLE_SIZE {
+                self.p[updated] = s;
+            } else {
+                self.q[updated - TABLE_SIZE] = s;
+            }
+        }
+
+        self.counter = 0;
+    }
+
+    /// step updates one element of the tables and returns its position
+    /// (in the concatenation of P and Q) and the key stream word.
+    fn step(&mut self) -> (usize, u32) {
+        let j = self.counter % TABLE_SIZE;
+        let first = self.counter < TABLE_SIZE;
+        self.counter = (self.counter + 1) % (2 * TABLE_SIZE);
+
+        if first {
+            let p = &mut self.p;
+            p[j] = p[j].wrapping_add(g1(p[sub(j, 3)], p[sub(j, 10)], p[sub(j, 511)]));
+            (j, h(&self.q, p[sub(j, 12)]) ^ p[j])
+        } else {
+            let q = &mut self.q;
+            q[j] = q[j].wrapping_add(g2(q[sub(j, 3)], q[sub(j, 10)], q[sub(j, 511)]));
+            (TABLE_SIZE + j, h(&self.p, q[sub(j, 12)]) ^ q[j])
+        }
+    }
+
+    /// next_byte returns the next key stream byte; words are used in
+    /// little-endian order.
+    fn next_byte(&mut self) -> u8 {
+        if self.used == 4 {
+            let (_, s) = self.step();
+            self.word = s.to_le_bytes();
+            self.used = 0;
+        }
+
+        self.used += 1;
+        self.word[self.used - 1]
+    }
+}
+
+/// sub subtracts modulo the table size.
+fn sub(j: usize, n: usize) -> usize {
+    (j + TABLE_SIZE - n) % TABLE_SIZE
+}
+
+fn f1(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+fn f2(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+fn g1(x: u32, y: u32, z: u32) -> u32 {
+    (x.rotate_right(10) ^ z.rotate_right(23)).wrapping_add(y.rotate_right(8))
+}
+
+fn g2(x: u32, y: u32, z: u32) -> u32 {
+    (x.rotate_left(10) ^ z.rotate_left(23)).wrapping_add(y.rotate_left(8))
+}
+
+/// h uses the other table as an S-box, indexed by the bytes 0 and 2 of x.
+fn h(table: &[u32; TABLE_SIZE], x: u32) -> u32 {
+    let x0 = (x & 0xff) as usize;
+    let x2 = ((x >> 16) & 0xff) as usize;
+    table[x0].wrapping_add(table[256 + x2])
+}
+
+fn read_word(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[4 * i],
+        bytes[4 * i + 1],
+        bytes[4 * i + 2],
+        bytes[4 * i + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    fn check_vector(key: &str, iv: &str, stream: &str) {
+        let mut k = [0u8; KEY_SIZE_BYTES];
+        k.copy_from_slice(&hex(key));
+        let mut v = [0u8; IV_SIZE_BYTES];
+        v.copy_from_slice(&hex(iv));
+
+        let mut cipher = Hc128::new(v, k);
+        assert_eq!(hex(stream), cipher.encrypt(&[0u8; 64]));
+    }
+
+    #[test]
+    fn estream_vectors() {
+        check_vector(
+            "00000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "82001573a003fd3b7fd72ffb0eaf63aac62f12deb629dca72785a66268ec758b\
+             1edb36900560898178e0ad009abf1f491330dc1c246e3d6cb264f6900271d59c",
+        );
+        check_vector(
+            "0558abfe51a4f74a9df04396e93c8fe2",
+            "167de44bb21980e74eb51c83ea51b81f",
+            "4f864bf3c96d0363b1903f0739189138f6ed2bc0af583feea0cea66ba7e06e63\
+             fb28bf8b3ca0031d24abb511c57dd17bfc2861c32400072cb680df2e58a5cecc",
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let mut encrypt_cipher = Hc128::new([24u8; IV_SIZE_BYTES], [42u8; KEY_SIZE_BYTES]);
+        let encrypted = encrypt_cipher.encrypt("there is no spoon".as_bytes());
+
+        let mut decrypt_cipher = Hc128::new([24u8; IV_SIZE_BYTES], [42u8; KEY_SIZE_BYTES]);
+        let decrypted = decrypt_cipher.decrypt(&encrypted);
+
+        assert_eq!("there is no spoon".as_bytes(), decrypted.as_slice());
+    }
+}
@@ -0,0 +1,200 @@
+//! # Kreyvium
+//!
+//! `kreyvium` implements the Kreyvium stream cipher, a variant of Trivium
+//! with 128-bit keys and IVs.
+//! Kreyvium keeps the three registers of Trivium, and adds two 128-bit
+//! registers holding the key and the IV, which are rotated and injected
+//! into the state at every clock cycle.
+//! Like Trivium, this cipher is meant for hardware implementations.
+
+use super::trivium::clock_registers;
+
+/// The size of a private key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 16;
+/// The size of the initialization vector (in bytes).
+pub const IV_SIZE_BYTES: usize = 16;
+
+/// An initialization vector.
+pub type IV = [u8; IV_SIZE_BYTES];
+/// A symmetric private key.
+pub type Key = [u8; KEY_SIZE_BYTES];
+
+/// A Kreyvium stream cipher.
+#[derive(Debug)]
+pub struct Kreyvium {
+    r1: [u8; 12], // only the first 93 bits are used
+    r2: [u8; 11], // only the first 84 bits are used
+    r3: [u8; 14], // only the first 111 bits are used
+    key_register: u128,
+    iv_register: u128,
+}
+
+// Public methods.
+impl Kreyvium {
+    /// new creates a Kreyvium cipher with the given key and IV.
+    pub fn new(iv: IV, key: Key) -> Kreyvium {
+        let mut instance = Kreyvium {
+            r1: [0u8; 12],
+            r2: [0u8; 11],
+            r3: [0u8; 14],
+            key_register: 0,
+            iv_register: 0,
+        };
+
+        instance.init(&iv, &key);
+        instance.warm_up();
+
+        instance
+    }
+
+    /// encrypt encrypts a given message.
+    pub fn encrypt(&mut self, message: &[u8]) -> Vec<u8> {
+        let mut encrypted: Vec<u8> = Vec::new();
+
+        for b in message.iter() {
+            let k = self.clock_byte();
+            encrypted.push(b ^ k);
+        }
+
+        encrypted
+    }
+
+    /// decrypt decrypts a given message.
+    pub fn decrypt(&mut self, cipher: &[u8]) -> Vec<u8> {
+        // Decryption is exactly the same thing as encryption.
+        self.encrypt(cipher)
+    }
+}
+
+// Private methods.
+impl Kreyvium {
+    /// init loads the key into register 1 and the IV into registers 2 and
+    /// 3. Key and IV bits are numbered least significant bit first, as in
+    /// the reference implementation.
+    fn init(&mut self, iv: &IV, key: &Key) {
+        let bit = |bytes: &[u8], i: usize| (bytes[i / 8] >> (i % 8)) & 1;
+        let set = |r: &mut [u8], i: usize, b: u8| r[i / 8] |= b << (7 - i % 8);
+
+        for i in 0..93 {
+            set(&mut self.r1, i, bit(key, i));
+        }
+        for i in 0..84 {
+            set(&mut self.r2, i, bit(iv, i));
+        }
+        for i in 84..128 {
+            set(&mut self.r3, i - 84, bit(iv, i));
+        }
+        // The rest of register 3 is filled with ones, except for its last
+        // bit.
+        for i in 44..110 {
+            set(&mut self.r3, i, 1);
+        }
+
+        // The key and IV registers hold the key and the IV in reverse
+        // order: their most significant bit is injected first.
+        self.key_register = u128::from_le_bytes(*key);
+        self.iv_register = u128::from_le_bytes(*iv);
+    }
+
+    fn warm_up(&mut self) {
+        for _i in 0..1152 {
+            self.clock();
+        }
+    }
+
+    /// clock simulates one clock cycle and returns the key stream bit.
+    /// The return value will be either 0 or 1.
+    fn clock(&mut self) -> u8 {
+        let key_bit = (self.key_register >> 127) as u8;
+        let iv_bit = (self.iv_register >> 127) as u8;
+        self.key_register = self.key_register.rotate_left(1);
+        self.iv_register = self.iv_register.rotate_left(1);
+
+        let (linear, _) =
+            clock_registers(&mut self.r1, &mut self.r2, &mut self.r3, key_bit, iv_bit);
+
+        linear
+    }
+
+    /// clock_byte simulates 8 clock cycles and returns a key stream byte,
+    /// least significant bit first.
+    fn clock_byte(&mut self) -> u8 {
+        let mut b = 0u8;
+
+        for i in 0..8 {
+            b |= self.clock() << i;
+        }
+
+        b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub const TEST_IV: IV = [24u8; IV_SIZE_BYTES];
+    pub const TEST_KEY: Key = [42u8; KEY_SIZE_BYTES];
+
+    #[test]
+    fn kreyvium_init() {
+        let mut cipher = Kreyvium {
+            r1: [0u8; 12],
+            r2: [0u8; 11],
+            r3: [0u8; 14],
+            key_register: 0,
+            iv_register: 0,
+        };
+
+        cipher.init(&[0u8; IV_SIZE_BYTES], &[1u8; KEY_SIZE_BYTES]);
+
+        assert_eq!(
+            [128u8, 128u8, 128u8, 128u8, 128u8, 128u8, 128u8, 128u8, 128u8, 128u8, 128u8, 128u8],
+            cipher.r1
+        );
+        assert_eq!([0u8; 11], cipher.r2);
+        assert_eq!(
+            [
+                0u8, 0u8, 0u8, 0u8, 0u8, 15u8, 255u8, 255u8, 255u8, 255u8, 255u8, 255u8, 255u8,
+                252u8
+            ],
+            cipher.r3
+        );
+        assert_eq!(0x01010101010101010101010101010101u128, cipher.key_register);
+    }
+
+    #[test]
+    fn designers_vector() {
+        let mut cipher = Kreyvium::new([0u8; IV_SIZE_BYTES], [0u8; KEY_SIZE_BYTES]);
+        assert_eq!(
+            vec![0x26, 0xdc, 0xf1, 0xf4, 0xbc, 0x0f, 0x19, 0x22],
+            cipher.encrypt(&[0u8; 8])
+        );
+    }
+
+    #[test]
+    fn whole_key_and_iv_are_used() {
+        // Unlike Trivium, the last bits of the key and the IV change the
+        // key stream.
+        let reference = Kreyvium::new(TEST_IV, TEST_KEY).encrypt(&[0u8; 16]);
+
+        let mut key = TEST_KEY;
+        key[KEY_SIZE_BYTES - 1] ^= 0x80;
+        assert_ne!(reference, Kreyvium::new(TEST_IV, key).encrypt(&[0u8; 16]));
+
+        let mut iv = TEST_IV;
+        iv[IV_SIZE_BYTES - 1] ^= 0x80;
+        assert_ne!(reference, Kreyvium::new(iv, TEST_KEY).encrypt(&[0u8; 16]));
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let mut encrypt_cipher = Kreyvium::new(TEST_IV, TEST_KEY);
+        let encrypted = encrypt_cipher.encrypt("there is no spoon".as_bytes());
+
+        let mut decrypt_cipher = Kreyvium::new(TEST_IV, TEST_KEY);
+        let decrypted = decrypt_cipher.decrypt(&encrypted);
+
+        assert_eq!("there is no spoon".as_bytes(), decrypted.as_slice());
+    }
+}
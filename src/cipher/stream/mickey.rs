@@ -0,0 +1,212 @@
+//! # MICKEY 2.0
+//!
+//! `mickey` implements the MICKEY 2.0 stream cipher, from the hardware
+//! profile of the eSTREAM portfolio.
+//! MICKEY (Mutual Irregular Clocking KEYstream generator) uses two
+//! 100-bit registers: a linear register R and a nonlinear register S,
+//! each one controlling how the other is clocked.
+//! Each clock produces a single key stream bit, so like Trivium this
+//! software implementation is slow.
+
+/// The size of a private key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 10;
+/// The maximum size of the initialization vector (in bytes).
+pub const MAX_IV_SIZE_BYTES: usize = 10;
+
+/// A symmetric private key.
+pub type Key = [u8; KEY_SIZE_BYTES];
+
+/// The number of bits of each register.
+const REGISTER_BITS: u32 = 100;
+
+/// The mask of the register bits.
+const REGISTER_MASK: u128 = (1 << REGISTER_BITS) - 1;
+
+/// The feedback taps of R: bit i is set if r_i is a tap.
+const RTAPS: u128 = 0x03_df87818f_b5546660_1279327b;
+
+/// The COMP0 and COMP1 sequences of S, for bits 1 to 98.
+const COMP0: u128 = 0x06_057ebfea_7942a809_6aa97a30;
+const COMP1: u128 = 0x01_91c23dd7_e3a21d63_dd629e9a;
+
+/// The feedback sequences of S, selected by the control bit.
+const FB0: u128 = 0x01_9cec5802_af4a9381_9ffa7faf;
+const FB1: u128 = 0x08_40fbc52b_4911b063_4c8cb877;
+
+/// The bits of S that are updated with COMP0 and COMP1.
+const COMP_MASK: u128 = REGISTER_MASK & !1 & !(1 << 99);
+
+/// A MICKEY 2.0 stream cipher.
+#[derive(Debug)]
+pub struct Mickey {
+    r: u128,
+    s: u128,
+}
+
+// Public methods.
+impl Mickey {
+    /// new creates a MICKEY 2.0 cipher with the given key and IV.
+    /// The IV can contain between 0 and 10 bytes.
+    pub fn new(iv: &[u8], key: Key) -> Mickey {
+        if iv.len() > MAX_IV_SIZE_BYTES {
+            panic!("IV length must be at most 10 bytes.");
+        }
+
+        let mut instance = Mickey { r: 0, s: 0 };
+
+        instance.init(iv, &key);
+
+        instance
+    }
+
+    /// encrypt encrypts a given message.
+    pub fn encrypt(&mut self, message: &[u8]) -> Vec<u8> {
+        message.iter().map(|b| b ^ self.clock_byte()).collect()
+    }
+
+    /// decrypt decrypts a given message.
+    pub fn decrypt(&mut self, cipher: &[u8]) -> Vec<u8> {
+        // Decryption is exactly the same thing as encryption.
+        self.encrypt(cipher)
+    }
+}
+
+// Private methods.
+impl Mickey {
+    /// init loads the IV and key bits (most significant bit first) and
+    /// runs 100 preclock cycles, all in mixing mode.
+    fn init(&mut self, iv: &[u8], key: &Key) {
+        for b in iv.iter().chain(key.iter()) {
+            for i in 0..8 {
+                self.clock(true, (b >> (7 - i)) & 1 == 1);
+            }
+        }
+
+        for _ in 0..REGISTER_BITS {
+            self.clock(true, false);
+        }
+    }
+
+    /// clock clocks both registers; in mixing mode, a bit of S is also
+    /// fed into R.
+    fn clock(&mut self, mixing: bool, input: bool) {
+        let control_r = bit(self.s, 34) ^ bit(self.r, 67);
+        let control_s = bit(self.s, 67) ^ bit(self.r, 33);
+        let input_r = if mixing {
+            input ^ bit(self.s, 50)
+        } else {
+            input
+        };
+
+        self.r = clock_r(self.r, input_r, control_r);
+        self.s = clock_s(self.s, input, control_s);
+    }
+
+    /// clock_byte returns 8 key stream bits, most significant bit first.
+    fn clock_byte(&mut self) -> u8 {
+        let mut b = 0u8;
+
+        for i in 0..8 {
+            let z = (self.r ^ self.s) & 1;
+            b |= (z as u8) << (7 - i);
+            self.clock(false, false);
+        }
+
+        b
+    }
+}
+
+fn bit(x: u128, i: u32) -> bool {
+    (x >> i) & 1 == 1
+}
+
+/// clock_r shifts R with linear feedback; when the control bit is set, R is
+/// also XORed with its previous value.
+fn clock_r(r: u128, input: bool, control: bool) -> u128 {
+    let feedback = bit(r, REGISTER_BITS - 1) ^ input;
+
+    let mut next = (r << 1) & REGISTER_MASK;
+    if feedback {
+        next ^= RTAPS;
+    }
+    if control {
+        next ^= r;
+    }
+
+    next
+}
+
+/// clock_s shifts S with nonlinear mixing of neighbouring bits, and applies
+/// the feedback sequence selected by the control bit.
+fn clock_s(s: u128, input: bool, control: bool) -> u128 {
+    let feedback = bit(s, REGISTER_BITS - 1) ^ input;
+
+    let mixed = (s ^ COMP0) & ((s >> 1) ^ COMP1) & COMP_MASK;
+    let mut next = ((s << 1) ^ mixed) & REGISTER_MASK;
+    if feedback {
+        next ^= if control { FB1 } else { FB0 };
+    }
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    fn check_vector(key: &str, iv: &str, stream: &str) {
+        let mut k = [0u8; KEY_SIZE_BYTES];
+        k.copy_from_slice(&hex(key));
+
+        let expected = hex(stream);
+        let mut cipher = Mickey::new(&hex(iv), k);
+        assert_eq!(expected, cipher.encrypt(&vec![0u8; expected.len()]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_iv_too_big() {
+        Mickey::new(&[0u8; 11], [0u8; KEY_SIZE_BYTES]);
+    }
+
+    #[test]
+    fn rtaps() {
+        let taps = [
+            0, 1, 3, 4, 5, 6, 9, 12, 13, 16, 19, 20, 21, 22, 25, 28, 37, 38, 41, 42, 45, 46, 50,
+            52, 54, 56, 58, 60, 61, 63, 64, 65, 66, 67, 71, 72, 79, 80, 81, 82, 87, 88, 89, 90, 91,
+            92, 94, 95, 96, 97,
+        ];
+        assert_eq!(RTAPS, taps.iter().fold(0u128, |acc, t| acc | (1 << t)));
+    }
+
+    #[test]
+    fn specification_vectors() {
+        check_vector(
+            "123456789abcdef01234",
+            "21436587",
+            "9821e10c5ed28d32bbc3d1fb15e93a15",
+        );
+        check_vector(
+            "f11a5627ce43b61f8912",
+            "9c532f8ac3ea4b2ea0f5",
+            "21a0436619cb9f3f6f1fb303f56a09a9",
+        );
+        check_vector(
+            "3b80fcc8dd3ecef3b5e3",
+            "",
+            "f897054b8961f325d3b443e19c977877",
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let mut encrypt_cipher = Mickey::new(&[24u8; 8], [42u8; KEY_SIZE_BYTES]);
+        let encrypted = encrypt_cipher.encrypt("there is no spoon".as_bytes());
+
+        let mut decrypt_cipher = Mickey::new(&[24u8; 8], [42u8; KEY_SIZE_BYTES]);
+        let decrypted = decrypt_cipher.decrypt(&encrypted);
+
+        assert_eq!("there is no spoon".as_bytes(), decrypted.as_slice());
+    }
+}
@@ -2,5 +2,13 @@
 //!
 //! `stream` contains a collection of stream cipher implementations.
 
+pub mod a5;
+pub mod grain;
+pub mod hc128;
+pub mod kreyvium;
+pub mod mickey;
+pub mod rabbit;
+pub mod rc4;
 pub mod salsa20;
+pub mod sosemanuk;
 pub mod trivium;
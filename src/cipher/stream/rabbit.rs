@@ -0,0 +1,213 @@
+//! # Rabbit
+//!
+//! `rabbit` implements the Rabbit stream cipher (RFC 4503), from the
+//! software profile of the eSTREAM portfolio.
+//! Rabbit iterates a system of eight coupled nonlinear functions, built
+//! from 32-bit squarings, driven by counters.
+//! Each iteration produces 128 bits of key stream.
+//! The IV is optional: without one, the state only depends on the key.
+
+/// The size of a private key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 16;
+/// The size of the initialization vector (in bytes).
+pub const IV_SIZE_BYTES: usize = 8;
+
+/// An initialization vector.
+pub type IV = [u8; IV_SIZE_BYTES];
+/// A symmetric private key.
+pub type Key = [u8; KEY_SIZE_BYTES];
+
+/// The constants added to the counters.
+const A: [u32; 8] = [
+    0x4d34d34d, 0xd34d34d3, 0x34d34d34, 0x4d34d34d, 0xd34d34d3, 0x34d34d34, 0x4d34d34d, 0xd34d34d3,
+];
+
+/// A Rabbit stream cipher.
+pub struct Rabbit {
+    x: [u32; 8],
+    c: [u32; 8],
+    carry: u32,
+    block: [u8; 16],
+    used: usize,
+}
+
+// Public methods.
+impl Rabbit {
+    /// new creates a Rabbit cipher with the given key and IV.
+    pub fn new(iv: IV, key: Key) -> Rabbit {
+        let mut instance = Rabbit::without_iv(key);
+        instance.setup_iv(&iv);
+
+        instance
+    }
+
+    /// without_iv creates a Rabbit cipher with the given key and no IV.
+    /// The key must only be used once.
+    pub fn without_iv(key: Key) -> Rabbit {
+        let mut instance = Rabbit {
+            x: [0u32; 8],
+            c: [0u32; 8],
+            carry: 0,
+            block: [0u8; 16],
+            used: 16,
+        };
+
+        instance.setup_key(&key);
+
+        instance
+    }
+
+    /// encrypt encrypts a given message.
+    pub fn encrypt(&mut self, message: &[u8]) -> Vec<u8> {
+        message.iter().map(|b| b ^ self.next_byte()).collect()
+    }
+
+    /// decrypt decrypts a given message.
+    pub fn decrypt(&mut self, cipher: &[u8]) -> Vec<u8> {
+        // Decryption is exactly the same thing as encryption.
+        self.encrypt(cipher)
+    }
+}
+
+// Private methods.
+impl Rabbit {
+    /// setup_key loads the eight 16-bit subkeys into the state and
+    /// counters, and iterates the system four times.
+    fn setup_key(&mut self, key: &Key) {
+        let mut k = [0u32; 8];
+        for i in 0..8 {
+            k[i] = u32::from(u16::from_le_bytes([key[2 * i], key[2 * i + 1]]));
+        }
+
+        for j in 0..8 {
+            if j % 2 == 0 {
+                self.x[j] = (k[(j + 1) % 8] << 16) | k[j];
+                self.c[j] = (k[(j + 4) % 8] << 16) | k[(j + 5) % 8];
+            } else {
+                self.x[j] = (k[(j + 5) % 8] << 16) | k[(j + 4) % 8];
+                self.c[j] = (k[j] << 16) | k[(j + 1) % 8];
+            }
+        }
+
+        for _ in 0..4 {
+            self.next_state();
+        }
+
+        for j in 0..8 {
+            self.c[j] ^= self.x[(j + 4) % 8];
+        }
+    }
+
+    /// setup_iv mixes the IV into the counters, and iterates the system
+    /// four times.
+    fn setup_iv(&mut self, iv: &IV) {
+        let i0 = u32::from_le_bytes([iv[0], iv[1], iv[2], iv[3]]);
+        let i2 = u32::from_le_bytes([iv[4], iv[5], iv[6], iv[7]]);
+        let i1 = (i2 & 0xffff0000) | (i0 >> 16);
+        let i3 = (i2 << 16) | (i0 & 0x0000ffff);
+
+        let ivs = [i0, i1, i2, i3];
+        for j in 0..8 {
+            self.c[j] ^= ivs[j % 4];
+        }
+
+        for _ in 0..4 {
+            self.next_state();
+        }
+    }
+
+    /// next_state updates the counters and iterates the system once.
+    fn next_state(&mut self) {
+        for (c, &a) in self.c.iter_mut().zip(A.iter()) {
+            let t = u64::from(*c) + u64::from(a) + u64::from(self.carry);
+            self.carry = (t >> 32) as u32;
+            *c = t as u32;
+        }
+
+        let mut g = [0u32; 8];
+        for (j, gj) in g.iter_mut().enumerate() {
+            *gj = g_function(self.x[j], self.c[j]);
+        }
+
+        for j in 0..8 {
+            // Even words mix the two previous g values rotated by 16, odd
+            // words mix the previous one rotated by 8 and the one before.
+            self.x[j] = if j % 2 == 0 {
+                g[j].wrapping_add(g[(j + 7) % 8].rotate_left(16))
+                    .wrapping_add(g[(j + 6) % 8].rotate_left(16))
+            } else {
+                g[j].wrapping_add(g[(j + 7) % 8].rotate_left(8))
+                    .wrapping_add(g[(j + 6) % 8])
+            };
+        }
+    }
+
+    /// next_byte returns the next key stream byte.
+    fn next_byte(&mut self) -> u8 {
+        if self.used == 16 {
+            self.next_state();
+
+            let x = &self.x;
+            let s = [
+                x[0] ^ (x[5] >> 16) ^ (x[3] << 16),
+                x[2] ^ (x[7] >> 16) ^ (x[5] << 16),
+                x[4] ^ (x[1] >> 16) ^ (x[7] << 16),
+                x[6] ^ (x[3] >> 16) ^ (x[1] << 16),
+            ];
+
+            for (chunk, w) in self.block.chunks_mut(4).zip(s.iter()) {
+                chunk.copy_from_slice(&w.to_le_bytes());
+            }
+
+            self.used = 0;
+        }
+
+        self.used += 1;
+        self.block[self.used - 1]
+    }
+}
+
+/// g_function squares the sum of its inputs, and XORs the two halves of
+/// the 64-bit result.
+fn g_function(x: u32, c: u32) -> u32 {
+    let u = u64::from(x.wrapping_add(c));
+    let square = u * u;
+    (square ^ (square >> 32)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    #[test]
+    fn key_setup_without_iv() {
+        // RFC 4503 appendix A.1, with bytes in memory order.
+        let mut cipher = Rabbit::without_iv([0u8; KEY_SIZE_BYTES]);
+        assert_eq!(
+            hex("02f74a1c26456bf5ecd6a536f05457b1a78ac689476c697b390c9cc515d8e888"),
+            cipher.encrypt(&[0u8; 32])
+        );
+    }
+
+    #[test]
+    fn key_and_iv_setup() {
+        // RFC 4503 appendix A.2, with bytes in memory order.
+        let mut cipher = Rabbit::new([0u8; IV_SIZE_BYTES], [0u8; KEY_SIZE_BYTES]);
+        assert_eq!(
+            hex("edb70567375dcd7cd89554f85e27a7c6"),
+            cipher.encrypt(&[0u8; 16])
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let mut encrypt_cipher = Rabbit::new([24u8; IV_SIZE_BYTES], [42u8; KEY_SIZE_BYTES]);
+        let encrypted = encrypt_cipher.encrypt("there is no spoon".as_bytes());
+
+        let mut decrypt_cipher = Rabbit::new([24u8; IV_SIZE_BYTES], [42u8; KEY_SIZE_BYTES]);
+        let decrypted = decrypt_cipher.decrypt(&encrypted);
+
+        assert_eq!("there is no spoon".as_bytes(), decrypted.as_slice());
+    }
+}
@@ -3,6 +3,9 @@
 //! `salsa20` implements the Salsa20 stream cipher.
 //! Salsa20 is a hash function that can be used in
 //! counter mode to act as a stream cipher.
+//! The hash function itself (the Salsa20 core) is exposed as `hash`, and
+//! its reduced-round variants, such as the Salsa20/8 core used by scrypt,
+//! as `hash_rounds`.
 
 /// A Salsa20 stream cipher.
 pub struct Salsa20 {
@@ -18,10 +21,8 @@ impl Salsa20 {
             k1: [0u8; 16],
         };
 
-        for i in 0..16 {
-            instance.k0[i] = key[i];
-            instance.k1[i] = key[16 + i];
-        }
+        instance.k0.copy_from_slice(&key[..16]);
+        instance.k1.copy_from_slice(&key[16..]);
 
         instance
     }
@@ -46,7 +47,7 @@ impl Salsa20 {
                 current = key_expansion(self.k0, self.k1, n);
             }
 
-            res[i] = res[i] ^ current[i % 64];
+            res[i] ^= current[i % 64];
         }
 
         res
@@ -100,14 +101,28 @@ fn little_endian(b: [u8; 4]) -> u32 {
     b[0] as u32 + ((b[1] as u32) << 8) + ((b[2] as u32) << 16) + ((b[3] as u32) << 24)
 }
 
-fn hash(b: [u8; 64]) -> [u8; 64] {
+/// hash computes the Salsa20 hash of a 64-byte block.
+/// It is not a collision-resistant hash function: it is designed to be
+/// used with constants in part of its input, as in the key stream and in
+/// Rumba20.
+pub fn hash(b: [u8; 64]) -> [u8; 64] {
+    hash_rounds(b, 20)
+}
+
+/// hash_rounds computes the Salsa20 hash of a 64-byte block with the given
+/// number of rounds, which must be even.
+pub fn hash_rounds(b: [u8; 64], rounds: usize) -> [u8; 64] {
+    if !rounds.is_multiple_of(2) {
+        panic!("Number of rounds must be even.");
+    }
+
     let mut x = [0u32; 16];
     for i in 0..16 {
         x[i] = little_endian([b[4 * i], b[4 * i + 1], b[4 * i + 2], b[4 * i + 3]]);
     }
 
     let mut z = x;
-    for _ in 0..10 {
+    for _ in 0..rounds / 2 {
         z = double_round(z);
     }
 
@@ -123,7 +138,9 @@ fn hash(b: [u8; 64]) -> [u8; 64] {
     res
 }
 
-fn key_expansion(k0: [u8; 16], k1: [u8; 16], n: [u8; 16]) -> [u8; 64] {
+/// key_expansion computes the key stream block for the 32-byte key (k0, k1)
+/// and the 16-byte input n (nonce and block counter).
+pub(crate) fn key_expansion(k0: [u8; 16], k1: [u8; 16], n: [u8; 16]) -> [u8; 64] {
     let mut to_hash = [0u8; 64];
 
     // sigma0
@@ -132,9 +149,7 @@ fn key_expansion(k0: [u8; 16], k1: [u8; 16], n: [u8; 16]) -> [u8; 64] {
     to_hash[2] = 112;
     to_hash[3] = 97;
 
-    for i in 4..20 {
-        to_hash[i] = k0[i - 4];
-    }
+    to_hash[4..20].copy_from_slice(&k0);
 
     // sigma1
     to_hash[20] = 110;
@@ -142,9 +157,7 @@ fn key_expansion(k0: [u8; 16], k1: [u8; 16], n: [u8; 16]) -> [u8; 64] {
     to_hash[22] = 32;
     to_hash[23] = 51;
 
-    for i in 24..40 {
-        to_hash[i] = n[i - 24];
-    }
+    to_hash[24..40].copy_from_slice(&n);
 
     // sigma2
     to_hash[40] = 50;
@@ -152,9 +165,7 @@ fn key_expansion(k0: [u8; 16], k1: [u8; 16], n: [u8; 16]) -> [u8; 64] {
     to_hash[42] = 98;
     to_hash[43] = 121;
 
-    for i in 44..60 {
-        to_hash[i] = k1[i - 44];
-    }
+    to_hash[44..60].copy_from_slice(&k1);
 
     // sigma3
     to_hash[60] = 116;
@@ -335,6 +346,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hash_rounds_spec() {
+        // The Salsa20/8 core test vector from RFC 7914.
+        let input = [
+            0x7e, 0x87, 0x9a, 0x21, 0x4f, 0x3e, 0xc9, 0x86, 0x7c, 0xa9, 0x40, 0xe6, 0x41, 0x71,
+            0x8f, 0x26, 0xba, 0xee, 0x55, 0x5b, 0x8c, 0x61, 0xc1, 0xb5, 0x0d, 0xf8, 0x46, 0x11,
+            0x6d, 0xcd, 0x3b, 0x1d, 0xee, 0x24, 0xf3, 0x19, 0xdf, 0x9b, 0x3d, 0x85, 0x14, 0x12,
+            0x1e, 0x4b, 0x5a, 0xc5, 0xaa, 0x32, 0x76, 0x02, 0x1d, 0x29, 0x09, 0xc7, 0x48, 0x29,
+            0xed, 0xeb, 0xc6, 0x8d, 0xb8, 0xb8, 0xc2, 0x5e,
+        ];
+        let output = [
+            0xa4, 0x1f, 0x85, 0x9c, 0x66, 0x08, 0xcc, 0x99, 0x3b, 0x81, 0xca, 0xcb, 0x02, 0x0c,
+            0xef, 0x05, 0x04, 0x4b, 0x21, 0x81, 0xa2, 0xfd, 0x33, 0x7d, 0xfd, 0x7b, 0x1c, 0x63,
+            0x96, 0x68, 0x2f, 0x29, 0xb4, 0x39, 0x31, 0x68, 0xe3, 0xc9, 0xe6, 0xbc, 0xfe, 0x6b,
+            0xc5, 0xb7, 0xa0, 0x6d, 0x96, 0xba, 0xe4, 0x24, 0xcc, 0x10, 0x2c, 0x91, 0x74, 0x5c,
+            0x24, 0xad, 0x67, 0x3d, 0xc7, 0x61, 0x8f, 0x81,
+        ];
+        assert_eq!(output[..], hash_rounds(input, 8)[..]);
+
+        assert_eq!(hash(input)[..], hash_rounds(input, 20)[..]);
+
+        // With no rounds, the hash doubles each word.
+        let doubled: Vec<u8> = input
+            .chunks(4)
+            .flat_map(|w| {
+                let w = little_endian([w[0], w[1], w[2], w[3]]);
+                w.wrapping_add(w).to_le_bytes().to_vec()
+            })
+            .collect();
+        assert_eq!(doubled[..], hash_rounds(input, 0)[..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Number of rounds must be even.")]
+    fn hash_rounds_odd() {
+        hash_rounds([0u8; 64], 7);
+    }
+
     #[test]
     fn key_expansion_spec() {
         let k0: [u8; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
@@ -359,8 +408,8 @@ mod tests {
     #[test]
     fn encrypt_and_decrypt() {
         let mut k = [0u8; 32];
-        for i in 0..32 {
-            k[i] = i as u8;
+        for (i, b) in k.iter_mut().enumerate() {
+            *b = i as u8;
         }
 
         let c = Salsa20::new(k);
@@ -5,6 +5,12 @@
 //! implementation will not be as efficient as it could be.
 //! For real applications a software stream cipher should be used
 //! (such as Salsa20).
+//!
+//! Earlier versions of this module loaded the IV and key into the wrong
+//! registers, packed key stream bits most significant bit first and mixed
+//! the AND terms into the key stream.
+//! The key stream now matches the eSTREAM reference implementation, so
+//! messages encrypted by earlier versions cannot be decrypted anymore.
 
 /// The size of a private key (in bytes).
 pub const KEY_SIZE_BYTES: usize = 10;
@@ -16,6 +22,10 @@ pub type IV = [u8; IV_SIZE_BYTES];
 /// A symmetric private key.
 pub type Key = [u8; KEY_SIZE_BYTES];
 
+/// The size of the complete cipher state (in bytes): the three registers
+/// followed by the IV and the key.
+pub(crate) const STATE_SIZE_BYTES: usize = 12 + 11 + 14 + IV_SIZE_BYTES + KEY_SIZE_BYTES;
+
 /// A Trivium stream cipher.
 #[derive(Debug)]
 pub struct Trivium {
@@ -34,8 +44,8 @@ impl Trivium {
             r1: [0u8; 12],
             r2: [0u8; 11],
             r3: [0u8; 14],
-            iv: iv,
-            key: key,
+            iv,
+            key,
         };
 
         instance.init();
@@ -48,7 +58,7 @@ impl Trivium {
     pub fn encrypt(&mut self, message: &[u8]) -> Vec<u8> {
         let mut encrypted: Vec<u8> = Vec::new();
 
-        for (_, b) in message.iter().enumerate() {
+        for b in message.iter() {
             let k = self.clock_byte();
             encrypted.push(b ^ k);
         }
@@ -65,13 +75,17 @@ impl Trivium {
 
 // Private methods.
 impl Trivium {
+    /// init loads the key into register 1 and the IV into register 2, and
+    /// sets the last three bits of register 3.
+    /// As in the reference implementation, key and IV bits are numbered
+    /// least significant bit first, and bit i is loaded at position 79 - i.
     fn init(&mut self) {
-        for i in 0..IV_SIZE_BYTES {
-            self.r1[i] = self.iv[i];
-        }
+        let bit = |bytes: &[u8], i: usize| (bytes[i / 8] >> (i % 8)) & 1;
+        let set = |r: &mut [u8], i: usize, b: u8| r[i / 8] |= b << (7 - i % 8);
 
-        for i in 0..KEY_SIZE_BYTES {
-            self.r2[i] = self.key[i]
+        for i in 0..80 {
+            set(&mut self.r1, 79 - i, bit(&self.key, i));
+            set(&mut self.r2, 79 - i, bit(&self.iv, i));
         }
 
         self.r3[13] = 14u8;
@@ -85,57 +99,18 @@ impl Trivium {
 
     /// clock simulates one clock cycle and returns the key stream bit.
     /// The return value will be either 0 or 1.
-    /// This is highly inefficient: the naive implementation has to shift
-    /// every byte and compute single-bit results over bytes.
-    /// This cipher was really meant for hardware implementations.
     fn clock(&mut self) -> u8 {
-        // Register 1
-        // Out = XOR(r1[65], r1[92], AND(r1[90], r1[91]))
-        let out1: u8 =
-            1u8 & (self.r1[8] >> 6 ^ self.r1[11] >> 3 ^ (self.r1[11] >> 4 & self.r1[11] >> 5));
-
-        // Register 2
-        // Out = XOR(r2[68], r2[83], AND(r2[81], r2[82]))
-        let out2: u8 =
-            1u8 & (self.r2[8] >> 3 ^ self.r2[10] >> 4 ^ (self.r2[10] >> 5 & self.r2[10] >> 6));
-
-        // Register 3
-        // Out = XOR(r3[65], r3[110], AND(r3[108], r3[109]))
-        let out3: u8 =
-            1u8 & (self.r3[8] >> 6 ^ self.r3[13] >> 1 ^ (self.r3[13] >> 2 & self.r3[13] >> 3));
-
-        // Register 1
-        // In = XOR(out(r3), r1[68])
-        let in1: u8 = 1u8 & (out3 ^ self.r1[8] >> 3);
-
-        // Register 2
-        // In = XOR(out(r1), r2[77])
-        let in2: u8 = 1u8 & (out1 ^ self.r2[9] >> 2);
-
-        // Register 3
-        // In = XOR(out(r2), r3[86])
-        let in3: u8 = 1u8 & (out2 ^ self.r3[10] >> 1);
-
-        // Shift everything to the right
-        Trivium::shift(&mut self.r1);
-        Trivium::shift(&mut self.r2);
-        Trivium::shift(&mut self.r3);
-
-        // Insert new first bit
-        self.r1[0] = (in1 << 7) + self.r1[0];
-        self.r2[0] = (in2 << 7) + self.r2[0];
-        self.r3[0] = (in3 << 7) + self.r3[0];
-
-        // Return key stream bit
-        out1 ^ out2 ^ out3
+        let (linear, _) = clock_registers(&mut self.r1, &mut self.r2, &mut self.r3, 0, 0);
+        linear
     }
 
-    /// clock_byte simulates 8 clock cycles and returns a key stream byte.
-    fn clock_byte(&mut self) -> u8 {
+    /// clock_byte simulates 8 clock cycles and returns a key stream byte,
+    /// least significant bit first.
+    pub(crate) fn clock_byte(&mut self) -> u8 {
         let mut b = 0u8;
 
         for i in 0..8 {
-            b |= self.clock() << (7 - i);
+            b |= self.clock() << i;
         }
 
         b
@@ -152,9 +127,100 @@ impl Trivium {
     }
 }
 
+// Methods used by the Trivium random number generator.
+impl Trivium {
+    /// state returns the complete cipher state.
+    pub(crate) fn state(&self) -> [u8; STATE_SIZE_BYTES] {
+        let mut state = [0u8; STATE_SIZE_BYTES];
+        state[..12].copy_from_slice(&self.r1);
+        state[12..23].copy_from_slice(&self.r2);
+        state[23..37].copy_from_slice(&self.r3);
+        state[37..47].copy_from_slice(&self.iv);
+        state[47..].copy_from_slice(&self.key);
+        state
+    }
+
+    /// from_state restores a cipher from a state returned by state.
+    pub(crate) fn from_state(state: &[u8; STATE_SIZE_BYTES]) -> Trivium {
+        let mut instance = Trivium {
+            r1: [0u8; 12],
+            r2: [0u8; 11],
+            r3: [0u8; 14],
+            iv: [0u8; IV_SIZE_BYTES],
+            key: [0u8; KEY_SIZE_BYTES],
+        };
+
+        instance.r1.copy_from_slice(&state[..12]);
+        instance.r2.copy_from_slice(&state[12..23]);
+        instance.r3.copy_from_slice(&state[23..37]);
+        instance.iv.copy_from_slice(&state[37..47]);
+        instance.key.copy_from_slice(&state[47..]);
+
+        instance
+    }
+}
+
+/// clock_registers simulates one clock cycle of the three registers, which
+/// are shared with Kreyvium.
+/// key_bit is added to the output of register 3 and iv_bit to the input of
+/// register 2; Trivium sets both to 0.
+/// It returns the linear and the nonlinear (AND) parts of the register
+/// outputs, each either 0 or 1: the key stream bit is the linear part,
+/// while the register inputs use both.
+/// This is highly inefficient: the naive implementation has to shift
+/// every byte and compute single-bit results over bytes.
+/// This cipher was really meant for hardware implementations.
+pub(super) fn clock_registers(
+    r1: &mut [u8; 12],
+    r2: &mut [u8; 11],
+    r3: &mut [u8; 14],
+    key_bit: u8,
+    iv_bit: u8,
+) -> (u8, u8) {
+    // Register 1
+    // Out = XOR(r1[65], r1[92], AND(r1[90], r1[91]))
+    let lin1: u8 = 1u8 & (r1[8] >> 6 ^ r1[11] >> 3);
+    let and1: u8 = 1u8 & (r1[11] >> 4 & r1[11] >> 5);
+
+    // Register 2
+    // Out = XOR(r2[68], r2[83], AND(r2[81], r2[82]))
+    let lin2: u8 = 1u8 & (r2[8] >> 3 ^ r2[10] >> 4);
+    let and2: u8 = 1u8 & (r2[10] >> 5 & r2[10] >> 6);
+
+    // Register 3
+    // Out = XOR(r3[65], r3[110], AND(r3[108], r3[109]), key_bit)
+    let lin3: u8 = 1u8 & (r3[8] >> 6 ^ r3[13] >> 1 ^ key_bit);
+    let and3: u8 = 1u8 & (r3[13] >> 2 & r3[13] >> 3);
+
+    // Register 1
+    // In = XOR(out(r3), r1[68])
+    let in1: u8 = 1u8 & (lin3 ^ and3 ^ r1[8] >> 3);
+
+    // Register 2
+    // In = XOR(out(r1), r2[77], iv_bit)
+    let in2: u8 = 1u8 & (lin1 ^ and1 ^ r2[9] >> 2 ^ iv_bit);
+
+    // Register 3
+    // In = XOR(out(r2), r3[86])
+    let in3: u8 = 1u8 & (lin2 ^ and2 ^ r3[10] >> 1);
+
+    // Shift everything to the right
+    Trivium::shift(r1);
+    Trivium::shift(r2);
+    Trivium::shift(r3);
+
+    // Insert new first bit
+    r1[0] += in1 << 7;
+    r2[0] += in2 << 7;
+    r3[0] += in3 << 7;
+
+    (lin1 ^ lin2 ^ lin3, and1 ^ and2 ^ and3)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_utils::hex;
 
     pub const TEST_IV: IV = [24u8; IV_SIZE_BYTES];
     pub const TEST_KEY: Key = [42u8; KEY_SIZE_BYTES];
@@ -178,31 +244,53 @@ mod tests {
 
         cipher.init();
 
+        // Key and IV bytes end up in reverse order.
         assert_eq!(
-            [
-                24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 0u8, 0u8
-            ],
+            [42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 0u8, 0u8],
             cipher.r1
         );
         assert_eq!(
-            [
-                42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 42u8, 0u8
-            ],
+            [24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 24u8, 0u8],
             cipher.r2
         );
         assert_eq!(
-            [
-                0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 14u8
-            ],
+            [0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 14u8],
             cipher.r3
         )
     }
 
+    #[test]
+    fn init_reverses_key_and_iv() {
+        let mut key = [0u8; KEY_SIZE_BYTES];
+        let mut iv = [0u8; IV_SIZE_BYTES];
+        for i in 0..KEY_SIZE_BYTES {
+            key[i] = i as u8;
+            iv[i] = 0x80 | i as u8;
+        }
+        let mut cipher = Trivium {
+            iv,
+            key,
+            r1: [0u8; 12],
+            r2: [0u8; 11],
+            r3: [0u8; 14],
+        };
+
+        cipher.init();
+
+        // Bit i goes to position 79 - i: the bytes are reversed, but the
+        // bits of each byte keep their order.
+        assert_eq!([9u8, 8, 7, 6, 5, 4, 3, 2, 1, 0, 0, 0], cipher.r1);
+        assert_eq!(
+            [0x89u8, 0x88, 0x87, 0x86, 0x85, 0x84, 0x83, 0x82, 0x81, 0x80, 0],
+            cipher.r2
+        );
+    }
+
     #[test]
     fn new_trivium_warm_up() {
         let cipher = Trivium::new(TEST_IV, TEST_KEY);
-        assert_ne!(TEST_IV, &cipher.r1[..IV_SIZE_BYTES]);
-        assert_ne!(TEST_KEY, &cipher.r2[..KEY_SIZE_BYTES]);
+        assert_ne!(TEST_KEY, &cipher.r1[..KEY_SIZE_BYTES]);
+        assert_ne!(TEST_IV, &cipher.r2[..IV_SIZE_BYTES]);
     }
 
     #[test]
@@ -222,4 +310,38 @@ mod tests {
 
         assert_eq!("there is no spoon".as_bytes(), decrypted.as_slice());
     }
+
+    #[test]
+    fn estream_vectors() {
+        // Trivium verified test vectors from the eSTREAM portfolio: set 1
+        // vector 0, set 2 vector 0 and set 6 vector 0 (key stream bytes 0
+        // to 63).
+        let vectors = [
+            (
+                "80000000000000000000",
+                "00000000000000000000",
+                "38eb86ff730d7a9caf8df13a4420540dbb7b651464c87501552041c249f29a64d2fbf515610921ebe06c8f92cecf7f8098ff20cccc6a62b97be8ef7454fc80f9",
+            ),
+            (
+                "00000000000000000000",
+                "00000000000000000000",
+                "fbe0bf265859051b517a2e4e239fc97f563203161907cf2de7a8790fa1b2e9cdf75292030268b7382b4c1a759aa2599a285549986e74805903801a4cb5a5d4f2",
+            ),
+            (
+                "0053a6f94c9ff24598eb",
+                "0d74db42a91077de45ac",
+                "f4cd954a717f26a7d6930830c4e7cf0819f80e03f25f342c64adc66aba7f8a8e6eaa49f23632ae3cd41a7bd290a0132f81c6d4043b6e397d7388f3a03b5fe358",
+            ),
+        ];
+
+        for (key, iv, stream) in vectors.iter() {
+            let mut k = [0u8; KEY_SIZE_BYTES];
+            k.copy_from_slice(&hex(key));
+            let mut v = [0u8; IV_SIZE_BYTES];
+            v.copy_from_slice(&hex(iv));
+
+            let mut cipher = Trivium::new(v, k);
+            assert_eq!(hex(stream), cipher.encrypt(&[0u8; 64]));
+        }
+    }
 }
@@ -0,0 +1,495 @@
+//! # BLAKE2b
+//!
+//! `blake2b` implements the BLAKE2b hash function (RFC 7693) and its
+//! parallel variant BLAKE2bp.
+//! BLAKE2b works on 64-bit words and is optimized for 64-bit platforms.
+//! Its compression function mixes a 16-word state with the G function,
+//! which is the ChaCha variant of the Salsa20 quarter round (see
+//! `cipher::stream::salsa20`), with a message word added in each half and
+//! different rotation distances.
+//! BLAKE2b can be keyed, which makes it a MAC without the nested
+//! construction of HMAC, and takes a salt and a personalization string to
+//! separate the hashes of different applications.
+//!
+//! BLAKE2bp splits the input into 128-byte blocks dealt to 4 BLAKE2b
+//! leaves, whose digests are then hashed by a root: the leaves can be
+//! computed in parallel, so it is faster than BLAKE2b on long inputs, but
+//! produces different digests.
+
+use super::sha2::SHA512_IV;
+use super::{BlockHasher, Hasher};
+
+/// The size of a block (in bytes).
+pub const BLOCK_SIZE_BYTES: usize = 128;
+/// The maximum size of a digest (in bytes).
+pub const MAX_OUTPUT_SIZE_BYTES: usize = 64;
+/// The maximum size of a key (in bytes).
+pub const MAX_KEY_SIZE_BYTES: usize = 64;
+/// The maximum size of a salt (in bytes).
+pub const SALT_SIZE_BYTES: usize = 16;
+/// The maximum size of a personalization string (in bytes).
+pub const PERSONALIZATION_SIZE_BYTES: usize = 16;
+/// The number of leaves of BLAKE2bp.
+pub const PARALLELISM_DEGREE: usize = 4;
+
+/// The number of rounds of the compression function.
+const ROUNDS: usize = 12;
+
+/// The message word permutations of each round, shared with BLAKE2s.
+pub(crate) const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The tree parameters of a node.
+#[derive(Debug, Clone, Copy)]
+struct Tree {
+    fanout: u8,
+    depth: u8,
+    leaf_length: u32,
+    node_offset: u64,
+    node_depth: u8,
+    inner_length: u8,
+    last_node: bool,
+}
+
+/// The tree parameters of sequential hashing.
+const SEQUENTIAL: Tree = Tree {
+    fanout: 1,
+    depth: 1,
+    leaf_length: 0,
+    node_offset: 0,
+    node_depth: 0,
+    inner_length: 0,
+    last_node: false,
+};
+
+/// A BLAKE2b hasher.
+#[derive(Debug, Clone)]
+pub struct Blake2b {
+    h: [u64; 8],
+    initial: [u64; 8],
+    key_block: Option<[u8; BLOCK_SIZE_BYTES]>,
+    buffer: [u8; BLOCK_SIZE_BYTES],
+    buffer_len: usize,
+    counter: u128,
+    output_size: usize,
+    last_node: bool,
+}
+
+// Public methods.
+impl Blake2b {
+    /// new creates a BLAKE2b hasher with the given digest size (in bytes).
+    /// It panics if the size is not between 1 and 64.
+    pub fn new(output_size: usize) -> Blake2b {
+        Blake2b::with_params(output_size, &[], &[], &[])
+    }
+
+    /// with_params creates a BLAKE2b hasher with the given digest size, key,
+    /// salt and personalization string, all of which may be empty.
+    /// The salt and personalization string are padded with zeros.
+    /// It panics if the digest size is not between 1 and 64, if the key is
+    /// longer than 64 bytes, or if the salt or personalization string is
+    /// longer than 16 bytes.
+    pub fn with_params(
+        output_size: usize,
+        key: &[u8],
+        salt: &[u8],
+        personalization: &[u8],
+    ) -> Blake2b {
+        Blake2b::with_tree(output_size, key, salt, personalization, &SEQUENTIAL, true)
+    }
+}
+
+// Private methods.
+impl Blake2b {
+    /// with_tree creates a hasher for a node of a tree.
+    /// The root of BLAKE2bp has the key length in its parameters, but does
+    /// not absorb the key.
+    fn with_tree(
+        output_size: usize,
+        key: &[u8],
+        salt: &[u8],
+        personalization: &[u8],
+        tree: &Tree,
+        absorb_key: bool,
+    ) -> Blake2b {
+        if output_size == 0 || output_size > MAX_OUTPUT_SIZE_BYTES {
+            panic!("Output size must be between 1 and 64.");
+        }
+        if key.len() > MAX_KEY_SIZE_BYTES {
+            panic!("Key length must be at most 64.");
+        }
+        if salt.len() > SALT_SIZE_BYTES {
+            panic!("Salt length must be at most 16.");
+        }
+        if personalization.len() > PERSONALIZATION_SIZE_BYTES {
+            panic!("Personalization length must be at most 16.");
+        }
+
+        let mut params = [0u8; 64];
+        params[0] = output_size as u8;
+        params[1] = key.len() as u8;
+        params[2] = tree.fanout;
+        params[3] = tree.depth;
+        params[4..8].copy_from_slice(&tree.leaf_length.to_le_bytes());
+        params[8..16].copy_from_slice(&tree.node_offset.to_le_bytes());
+        params[16] = tree.node_depth;
+        params[17] = tree.inner_length;
+        params[32..32 + salt.len()].copy_from_slice(salt);
+        params[48..48 + personalization.len()].copy_from_slice(personalization);
+
+        let mut h = SHA512_IV;
+        for i in 0..8 {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&params[8 * i..8 * (i + 1)]);
+            h[i] ^= u64::from_le_bytes(word);
+        }
+
+        // A key is absorbed as a first block padded with zeros.
+        let key_block = if key.is_empty() || !absorb_key {
+            None
+        } else {
+            let mut block = [0u8; BLOCK_SIZE_BYTES];
+            block[..key.len()].copy_from_slice(key);
+            Some(block)
+        };
+
+        let mut hasher = Blake2b {
+            h,
+            initial: h,
+            key_block,
+            buffer: [0u8; BLOCK_SIZE_BYTES],
+            buffer_len: 0,
+            counter: 0,
+            output_size,
+            last_node: tree.last_node,
+        };
+        hasher.reset();
+        hasher
+    }
+
+    /// compress processes the buffer, which is the last block if `last` is
+    /// set.
+    fn compress(&mut self, last: bool) {
+        let mut m = [0u64; 16];
+        for (mi, chunk) in m.iter_mut().zip(self.buffer.chunks(8)) {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(chunk);
+            *mi = u64::from_le_bytes(word);
+        }
+
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&SHA512_IV);
+        v[12] ^= self.counter as u64;
+        v[13] ^= (self.counter >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+            if self.last_node {
+                v[15] = !v[15];
+            }
+        }
+
+        for round in 0..ROUNDS {
+            let s = &SIGMA[round % 10];
+            // Mix the columns, then the diagonals.
+            g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+
+    /// finalize_full pads and compresses the last block, and returns the
+    /// whole state, whatever the digest size.
+    fn finalize_full(mut self) -> [u8; MAX_OUTPUT_SIZE_BYTES] {
+        self.counter += self.buffer_len as u128;
+        for b in self.buffer[self.buffer_len..].iter_mut() {
+            *b = 0;
+        }
+        self.compress(true);
+
+        let mut digest = [0u8; MAX_OUTPUT_SIZE_BYTES];
+        for i in 0..8 {
+            digest[8 * i..8 * (i + 1)].copy_from_slice(&self.h[i].to_le_bytes());
+        }
+
+        digest
+    }
+}
+
+impl Default for Blake2b {
+    fn default() -> Blake2b {
+        Blake2b::new(MAX_OUTPUT_SIZE_BYTES)
+    }
+}
+
+impl Hasher for Blake2b {
+    const OUTPUT_SIZE_BYTES: usize = MAX_OUTPUT_SIZE_BYTES;
+
+    fn update(&mut self, data: &[u8]) {
+        for b in data {
+            // The last block is treated differently, so a full buffer is
+            // only processed once we know more data follows.
+            if self.buffer_len == BLOCK_SIZE_BYTES {
+                self.counter += BLOCK_SIZE_BYTES as u128;
+                self.compress(false);
+                self.buffer_len = 0;
+            }
+
+            self.buffer[self.buffer_len] = *b;
+            self.buffer_len += 1;
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        let output_size = self.output_size;
+        self.finalize_full()[..output_size].to_vec()
+    }
+
+    fn reset(&mut self) {
+        self.h = self.initial;
+        self.counter = 0;
+        match self.key_block {
+            Some(block) => {
+                self.buffer = block;
+                self.buffer_len = BLOCK_SIZE_BYTES;
+            }
+            None => {
+                self.buffer = [0u8; BLOCK_SIZE_BYTES];
+                self.buffer_len = 0;
+            }
+        }
+    }
+}
+
+impl BlockHasher for Blake2b {
+    const BLOCK_SIZE_BYTES: usize = BLOCK_SIZE_BYTES;
+}
+
+/// A BLAKE2bp hasher.
+#[derive(Debug, Clone)]
+pub struct Blake2bp {
+    leaves: Vec<Blake2b>,
+    root: Blake2b,
+    length: u128,
+}
+
+// Public methods.
+impl Blake2bp {
+    /// new creates a BLAKE2bp hasher with the given digest size (in bytes).
+    /// It panics if the size is not between 1 and 64.
+    pub fn new(output_size: usize) -> Blake2bp {
+        Blake2bp::with_key(output_size, &[])
+    }
+
+    /// with_key creates a keyed BLAKE2bp hasher with the given digest size.
+    /// It panics if the size is not between 1 and 64, or if the key is
+    /// longer than 64 bytes.
+    pub fn with_key(output_size: usize, key: &[u8]) -> Blake2bp {
+        let mut tree = Tree {
+            fanout: PARALLELISM_DEGREE as u8,
+            depth: 2,
+            leaf_length: 0,
+            node_offset: 0,
+            node_depth: 0,
+            inner_length: MAX_OUTPUT_SIZE_BYTES as u8,
+            last_node: false,
+        };
+
+        let mut leaves = Vec::new();
+        for i in 0..PARALLELISM_DEGREE {
+            tree.node_offset = i as u64;
+            tree.last_node = i == PARALLELISM_DEGREE - 1;
+            leaves.push(Blake2b::with_tree(output_size, key, &[], &[], &tree, true));
+        }
+
+        tree.node_offset = 0;
+        tree.node_depth = 1;
+        tree.last_node = true;
+        let root = Blake2b::with_tree(output_size, key, &[], &[], &tree, false);
+
+        Blake2bp {
+            leaves,
+            root,
+            length: 0,
+        }
+    }
+}
+
+impl Default for Blake2bp {
+    fn default() -> Blake2bp {
+        Blake2bp::new(MAX_OUTPUT_SIZE_BYTES)
+    }
+}
+
+impl Hasher for Blake2bp {
+    const OUTPUT_SIZE_BYTES: usize = MAX_OUTPUT_SIZE_BYTES;
+
+    fn update(&mut self, mut data: &[u8]) {
+        // Blocks are dealt to the leaves in turn.
+        while !data.is_empty() {
+            let block = (self.length / BLOCK_SIZE_BYTES as u128) as usize;
+            let offset = (self.length % BLOCK_SIZE_BYTES as u128) as usize;
+            let n = (BLOCK_SIZE_BYTES - offset).min(data.len());
+            self.leaves[block % PARALLELISM_DEGREE].update(&data[..n]);
+            self.length += n as u128;
+            data = &data[n..];
+        }
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        // The leaves always output their whole state, whatever the digest
+        // size.
+        for leaf in self.leaves {
+            self.root.update(&leaf.finalize_full());
+        }
+
+        self.root.finalize()
+    }
+
+    fn reset(&mut self) {
+        for leaf in self.leaves.iter_mut() {
+            leaf.reset();
+        }
+        self.root.reset();
+        self.length = 0;
+    }
+}
+
+/// g mixes two message words into four words of the state.
+fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    /// sequence returns the bytes 0, 1, 2, ... used by the official test
+    /// vectors, as inputs and keys.
+    fn sequence(n: usize) -> Vec<u8> {
+        (0..n).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn rfc7693() {
+        // RFC 7693, appendix A.
+        assert_eq!(
+            hex("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"),
+            Blake2b::digest(b"abc")
+        );
+    }
+
+    #[test]
+    fn keyed() {
+        // Official keyed test vectors (blake2b-kat.txt).
+        for (n, digest) in [
+            (0, "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568"),
+            (1, "961f6dd1e4dd30f63901690c512e78e4b45e4742ed197c3c5e45c549fd25f2e4187b0bc9fe30492b16b0d0bc4ef9b0f34c7003fac09a5ef1532e69430234cebd"),
+            (255, "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e92484be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461"),
+        ]
+        .iter()
+        {
+            let mut hasher = Blake2b::with_params(64, &sequence(64), &[], &[]);
+            hasher.update(&sequence(*n));
+            assert_eq!(hex(digest), hasher.finalize());
+        }
+    }
+
+    #[test]
+    fn params() {
+        // The official test vectors only cover keyed hashing: check that the
+        // digest size, key length, salt and personalization string are
+        // loaded in the parameter block (RFC 7693, section 2.5).
+        let hasher = Blake2b::with_params(20, b"secret", b"pepper", b"blue-avocado");
+        assert_eq!(SHA512_IV[0] ^ 0x0101_0614, hasher.initial[0]);
+        assert_eq!(&SHA512_IV[1..4], &hasher.initial[1..4]);
+        assert_eq!(SHA512_IV[4] ^ 0x7265_7070_6570, hasher.initial[4]);
+        assert_eq!(SHA512_IV[5], hasher.initial[5]);
+        assert_eq!(SHA512_IV[6] ^ 0x6f76_612d_6575_6c62, hasher.initial[6]);
+        assert_eq!(SHA512_IV[7] ^ 0x6f64_6163, hasher.initial[7]);
+
+        let hasher = Blake2b::new(1);
+        assert_eq!(SHA512_IV[0] ^ 0x0101_0001, hasher.initial[0]);
+        assert_eq!(1, hasher.finalize().len());
+    }
+
+    #[test]
+    fn blake2bp() {
+        // Official keyed test vectors (blake2bp-kat.txt).
+        for (n, digest) in [
+            (0, "9d9461073e4eb640a255357b839f394b838c6ff57c9b686a3f76107c1066728f3c9956bd785cbc3bf79dc2ab578c5a0c063b9d9c405848de1dbe821cd05c940a"),
+            (1, "ff8e90a37b94623932c59f7559f26035029c376732cb14d41602001cbb73adb79293a2dbda5f60703025144d158e2735529596251c73c0345ca6fccb1fb1e97e"),
+            (64, "22b8249eaf722964ce424f71a74d038ff9b615fba5c7c22cb62797f5398224c3f072ebc1dacba32fc6f66360b3e1658d0fa0da1ed1c1da662a2037da823a3383"),
+            (128, "9280f4d1157032ab315c100d636283fbf4fba2fbad0f8bc020721d76bc1c8973ced28871cc907dab60e59756987b0e0f867fa2fe9d9041f2c9618074e44fe5e9"),
+            (255, "96fbcbb60bd313b8845033e5bc058a38027438572d7e7957f3684f6268aadd3ad08d21767ed6878685331ba98571487e12470aad669326716e46667f69f8d7e8"),
+        ]
+        .iter()
+        {
+            let mut hasher = Blake2bp::with_key(64, &sequence(64));
+            hasher.update(&sequence(*n));
+            assert_eq!(hex(digest), hasher.finalize());
+        }
+
+        // Long enough for each leaf to process several blocks, fed in
+        // pieces that straddle the block boundaries.
+        let message: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+        let mut hasher = Blake2bp::default();
+        for chunk in message.chunks(100) {
+            hasher.update(chunk);
+        }
+        assert_eq!(Blake2bp::digest(&message), hasher.finalize());
+    }
+
+    #[test]
+    fn reset() {
+        let mut hasher = Blake2b::with_params(32, b"secret", &[], &[]);
+        let reference = hasher.clone();
+        hasher.update(b"garbage");
+        hasher.reset();
+        assert_eq!(reference.finalize(), hasher.finalize());
+
+        let mut hasher = Blake2bp::default();
+        hasher.update(&[0x5a; 1000]);
+        hasher.reset();
+        assert_eq!(Blake2bp::digest(b""), hasher.finalize());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_output_size() {
+        Blake2b::new(65);
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_salt() {
+        Blake2b::with_params(64, &[], &[0u8; 17], &[]);
+    }
+}
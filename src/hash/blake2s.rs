@@ -0,0 +1,492 @@
+//! # BLAKE2s
+//!
+//! `blake2s` implements the BLAKE2s hash function (RFC 7693) and its
+//! parallel variant BLAKE2sp.
+//! BLAKE2s is the 32-bit version of BLAKE2b (see `hash::blake2b`), for
+//! 8-bit to 32-bit platforms: it has smaller blocks and digests, fewer
+//! rounds, and the rotation distances of ChaCha.
+//!
+//! BLAKE2sp deals 64-byte blocks to 8 BLAKE2s leaves, whose digests are
+//! then hashed by a root.
+
+use super::blake2b::SIGMA;
+use super::sha2::SHA256_IV;
+use super::{BlockHasher, Hasher};
+
+/// The size of a block (in bytes).
+pub const BLOCK_SIZE_BYTES: usize = 64;
+/// The maximum size of a digest (in bytes).
+pub const MAX_OUTPUT_SIZE_BYTES: usize = 32;
+/// The maximum size of a key (in bytes).
+pub const MAX_KEY_SIZE_BYTES: usize = 32;
+/// The maximum size of a salt (in bytes).
+pub const SALT_SIZE_BYTES: usize = 8;
+/// The maximum size of a personalization string (in bytes).
+pub const PERSONALIZATION_SIZE_BYTES: usize = 8;
+/// The number of leaves of BLAKE2sp.
+pub const PARALLELISM_DEGREE: usize = 8;
+
+/// The number of rounds of the compression function.
+const ROUNDS: usize = 10;
+
+/// The tree parameters of a node.
+#[derive(Debug, Clone, Copy)]
+struct Tree {
+    fanout: u8,
+    depth: u8,
+    leaf_length: u32,
+    node_offset: u64,
+    node_depth: u8,
+    inner_length: u8,
+    last_node: bool,
+}
+
+/// The tree parameters of sequential hashing.
+const SEQUENTIAL: Tree = Tree {
+    fanout: 1,
+    depth: 1,
+    leaf_length: 0,
+    node_offset: 0,
+    node_depth: 0,
+    inner_length: 0,
+    last_node: false,
+};
+
+/// A BLAKE2s hasher.
+#[derive(Debug, Clone)]
+pub struct Blake2s {
+    h: [u32; 8],
+    initial: [u32; 8],
+    key_block: Option<[u8; BLOCK_SIZE_BYTES]>,
+    buffer: [u8; BLOCK_SIZE_BYTES],
+    buffer_len: usize,
+    counter: u64,
+    output_size: usize,
+    last_node: bool,
+}
+
+// Public methods.
+impl Blake2s {
+    /// new creates a BLAKE2s hasher with the given digest size (in bytes).
+    /// It panics if the size is not between 1 and 32.
+    pub fn new(output_size: usize) -> Blake2s {
+        Blake2s::with_params(output_size, &[], &[], &[])
+    }
+
+    /// with_params creates a BLAKE2s hasher with the given digest size, key,
+    /// salt and personalization string, all of which may be empty.
+    /// The salt and personalization string are padded with zeros.
+    /// It panics if the digest size is not between 1 and 32, if the key is
+    /// longer than 32 bytes, or if the salt or personalization string is
+    /// longer than 8 bytes.
+    pub fn with_params(
+        output_size: usize,
+        key: &[u8],
+        salt: &[u8],
+        personalization: &[u8],
+    ) -> Blake2s {
+        Blake2s::with_tree(output_size, key, salt, personalization, &SEQUENTIAL, true)
+    }
+}
+
+// Private methods.
+impl Blake2s {
+    /// with_tree creates a hasher for a node of a tree.
+    /// The root of BLAKE2sp has the key length in its parameters, but does
+    /// not absorb the key.
+    fn with_tree(
+        output_size: usize,
+        key: &[u8],
+        salt: &[u8],
+        personalization: &[u8],
+        tree: &Tree,
+        absorb_key: bool,
+    ) -> Blake2s {
+        if output_size == 0 || output_size > MAX_OUTPUT_SIZE_BYTES {
+            panic!("Output size must be between 1 and 32.");
+        }
+        if key.len() > MAX_KEY_SIZE_BYTES {
+            panic!("Key length must be at most 32.");
+        }
+        if salt.len() > SALT_SIZE_BYTES {
+            panic!("Salt length must be at most 8.");
+        }
+        if personalization.len() > PERSONALIZATION_SIZE_BYTES {
+            panic!("Personalization length must be at most 8.");
+        }
+
+        // The node offset only has 48 bits.
+        let mut params = [0u8; 32];
+        params[0] = output_size as u8;
+        params[1] = key.len() as u8;
+        params[2] = tree.fanout;
+        params[3] = tree.depth;
+        params[4..8].copy_from_slice(&tree.leaf_length.to_le_bytes());
+        params[8..14].copy_from_slice(&tree.node_offset.to_le_bytes()[..6]);
+        params[14] = tree.node_depth;
+        params[15] = tree.inner_length;
+        params[16..16 + salt.len()].copy_from_slice(salt);
+        params[24..24 + personalization.len()].copy_from_slice(personalization);
+
+        let mut h = SHA256_IV;
+        for i in 0..8 {
+            let mut word = [0u8; 4];
+            word.copy_from_slice(&params[4 * i..4 * (i + 1)]);
+            h[i] ^= u32::from_le_bytes(word);
+        }
+
+        // A key is absorbed as a first block padded with zeros.
+        let key_block = if key.is_empty() || !absorb_key {
+            None
+        } else {
+            let mut block = [0u8; BLOCK_SIZE_BYTES];
+            block[..key.len()].copy_from_slice(key);
+            Some(block)
+        };
+
+        let mut hasher = Blake2s {
+            h,
+            initial: h,
+            key_block,
+            buffer: [0u8; BLOCK_SIZE_BYTES],
+            buffer_len: 0,
+            counter: 0,
+            output_size,
+            last_node: tree.last_node,
+        };
+        hasher.reset();
+        hasher
+    }
+
+    /// compress processes the buffer, which is the last block if `last` is
+    /// set.
+    fn compress(&mut self, last: bool) {
+        let mut m = [0u32; 16];
+        for (mi, chunk) in m.iter_mut().zip(self.buffer.chunks(4)) {
+            let mut word = [0u8; 4];
+            word.copy_from_slice(chunk);
+            *mi = u32::from_le_bytes(word);
+        }
+
+        let mut v = [0u32; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&SHA256_IV);
+        v[12] ^= self.counter as u32;
+        v[13] ^= (self.counter >> 32) as u32;
+        if last {
+            v[14] = !v[14];
+            if self.last_node {
+                v[15] = !v[15];
+            }
+        }
+
+        for s in SIGMA.iter().take(ROUNDS) {
+            // Mix the columns, then the diagonals.
+            g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+
+    /// finalize_full pads and compresses the last block, and returns the
+    /// whole state, whatever the digest size.
+    fn finalize_full(mut self) -> [u8; MAX_OUTPUT_SIZE_BYTES] {
+        self.counter += self.buffer_len as u64;
+        for b in self.buffer[self.buffer_len..].iter_mut() {
+            *b = 0;
+        }
+        self.compress(true);
+
+        let mut digest = [0u8; MAX_OUTPUT_SIZE_BYTES];
+        for i in 0..8 {
+            digest[4 * i..4 * (i + 1)].copy_from_slice(&self.h[i].to_le_bytes());
+        }
+
+        digest
+    }
+}
+
+impl Default for Blake2s {
+    fn default() -> Blake2s {
+        Blake2s::new(MAX_OUTPUT_SIZE_BYTES)
+    }
+}
+
+impl Hasher for Blake2s {
+    const OUTPUT_SIZE_BYTES: usize = MAX_OUTPUT_SIZE_BYTES;
+
+    fn update(&mut self, data: &[u8]) {
+        for b in data {
+            // The last block is treated differently, so a full buffer is
+            // only processed once we know more data follows.
+            if self.buffer_len == BLOCK_SIZE_BYTES {
+                self.counter += BLOCK_SIZE_BYTES as u64;
+                self.compress(false);
+                self.buffer_len = 0;
+            }
+
+            self.buffer[self.buffer_len] = *b;
+            self.buffer_len += 1;
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        let output_size = self.output_size;
+        self.finalize_full()[..output_size].to_vec()
+    }
+
+    fn reset(&mut self) {
+        self.h = self.initial;
+        self.counter = 0;
+        match self.key_block {
+            Some(block) => {
+                self.buffer = block;
+                self.buffer_len = BLOCK_SIZE_BYTES;
+            }
+            None => {
+                self.buffer = [0u8; BLOCK_SIZE_BYTES];
+                self.buffer_len = 0;
+            }
+        }
+    }
+}
+
+impl BlockHasher for Blake2s {
+    const BLOCK_SIZE_BYTES: usize = BLOCK_SIZE_BYTES;
+}
+
+/// A BLAKE2sp hasher.
+#[derive(Debug, Clone)]
+pub struct Blake2sp {
+    leaves: Vec<Blake2s>,
+    root: Blake2s,
+    length: u64,
+}
+
+// Public methods.
+impl Blake2sp {
+    /// new creates a BLAKE2sp hasher with the given digest size (in bytes).
+    /// It panics if the size is not between 1 and 32.
+    pub fn new(output_size: usize) -> Blake2sp {
+        Blake2sp::with_key(output_size, &[])
+    }
+
+    /// with_key creates a keyed BLAKE2sp hasher with the given digest size.
+    /// It panics if the size is not between 1 and 32, or if the key is
+    /// longer than 32 bytes.
+    pub fn with_key(output_size: usize, key: &[u8]) -> Blake2sp {
+        let mut tree = Tree {
+            fanout: PARALLELISM_DEGREE as u8,
+            depth: 2,
+            leaf_length: 0,
+            node_offset: 0,
+            node_depth: 0,
+            inner_length: MAX_OUTPUT_SIZE_BYTES as u8,
+            last_node: false,
+        };
+
+        let mut leaves = Vec::new();
+        for i in 0..PARALLELISM_DEGREE {
+            tree.node_offset = i as u64;
+            tree.last_node = i == PARALLELISM_DEGREE - 1;
+            leaves.push(Blake2s::with_tree(output_size, key, &[], &[], &tree, true));
+        }
+
+        tree.node_offset = 0;
+        tree.node_depth = 1;
+        tree.last_node = true;
+        let root = Blake2s::with_tree(output_size, key, &[], &[], &tree, false);
+
+        Blake2sp {
+            leaves,
+            root,
+            length: 0,
+        }
+    }
+}
+
+impl Default for Blake2sp {
+    fn default() -> Blake2sp {
+        Blake2sp::new(MAX_OUTPUT_SIZE_BYTES)
+    }
+}
+
+impl Hasher for Blake2sp {
+    const OUTPUT_SIZE_BYTES: usize = MAX_OUTPUT_SIZE_BYTES;
+
+    fn update(&mut self, mut data: &[u8]) {
+        // Blocks are dealt to the leaves in turn.
+        while !data.is_empty() {
+            let block = (self.length / BLOCK_SIZE_BYTES as u64) as usize;
+            let offset = (self.length % BLOCK_SIZE_BYTES as u64) as usize;
+            let n = (BLOCK_SIZE_BYTES - offset).min(data.len());
+            self.leaves[block % PARALLELISM_DEGREE].update(&data[..n]);
+            self.length += n as u64;
+            data = &data[n..];
+        }
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        // The leaves always output their whole state, whatever the digest
+        // size.
+        for leaf in self.leaves {
+            self.root.update(&leaf.finalize_full());
+        }
+
+        self.root.finalize()
+    }
+
+    fn reset(&mut self) {
+        for leaf in self.leaves.iter_mut() {
+            leaf.reset();
+        }
+        self.root.reset();
+        self.length = 0;
+    }
+}
+
+/// g mixes two message words into four words of the state.
+/// BLAKE3 uses the same function.
+pub(crate) fn g(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    /// sequence returns the bytes 0, 1, 2, ... used by the official test
+    /// vectors, as inputs and keys.
+    fn sequence(n: usize) -> Vec<u8> {
+        (0..n).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn rfc7693() {
+        // RFC 7693, appendix B.
+        assert_eq!(
+            hex("508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"),
+            Blake2s::digest(b"abc")
+        );
+    }
+
+    #[test]
+    fn keyed() {
+        // Official keyed test vectors (blake2s-kat.txt).
+        for (n, digest) in [
+            (
+                0,
+                "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49",
+            ),
+            (
+                1,
+                "40d15fee7c328830166ac3f918650f807e7e01e177258cdc0a39b11f598066f1",
+            ),
+            (
+                255,
+                "3fb735061abc519dfe979e54c1ee5bfad0a9d858b3315bad34bde999efd724dd",
+            ),
+        ]
+        .iter()
+        {
+            let mut hasher = Blake2s::with_params(32, &sequence(32), &[], &[]);
+            hasher.update(&sequence(*n));
+            assert_eq!(hex(digest), hasher.finalize());
+        }
+    }
+
+    #[test]
+    fn params() {
+        // The official test vectors only cover keyed hashing: check that the
+        // digest size, key length, salt and personalization string are
+        // loaded in the parameter block (RFC 7693, section 2.5).
+        let hasher = Blake2s::with_params(20, b"secret", b"pepper", b"avocado");
+        assert_eq!(SHA256_IV[0] ^ 0x0101_0614, hasher.initial[0]);
+        assert_eq!(&SHA256_IV[1..4], &hasher.initial[1..4]);
+        assert_eq!(SHA256_IV[4] ^ 0x7070_6570, hasher.initial[4]);
+        assert_eq!(SHA256_IV[5] ^ 0x7265, hasher.initial[5]);
+        assert_eq!(SHA256_IV[6] ^ 0x636f_7661, hasher.initial[6]);
+        assert_eq!(SHA256_IV[7] ^ 0x006f_6461, hasher.initial[7]);
+
+        let hasher = Blake2s::new(1);
+        assert_eq!(SHA256_IV[0] ^ 0x0101_0001, hasher.initial[0]);
+        assert_eq!(1, hasher.finalize().len());
+    }
+
+    #[test]
+    fn blake2sp() {
+        // Official keyed test vectors (blake2sp-kat.txt).
+        for (n, digest) in [
+            (
+                0,
+                "715cb13895aeb678f6124160bff21465b30f4f6874193fc851b4621043f09cc6",
+            ),
+            (
+                1,
+                "40578ffa52bf51ae1866f4284d3a157fc1bcd36ac13cbdcb0377e4d0cd0b6603",
+            ),
+            (
+                64,
+                "1d3701a5661bd31ab20562bd07b74dd19ac8f3524b73ce7bc996b788afd2f317",
+            ),
+            (
+                128,
+                "0c6ce32a3ea05612c5f8090f6a7e87f5ab30e41b707dcbe54155620ad770a340",
+            ),
+            (
+                255,
+                "0c8a36597d7461c63a94732821c941856c668376606c86a52de0ee4104c615db",
+            ),
+        ]
+        .iter()
+        {
+            let mut hasher = Blake2sp::with_key(32, &sequence(32));
+            hasher.update(&sequence(*n));
+            assert_eq!(hex(digest), hasher.finalize());
+        }
+
+        let message: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+        let mut hasher = Blake2sp::default();
+        for chunk in message.chunks(100) {
+            hasher.update(chunk);
+        }
+        assert_eq!(Blake2sp::digest(&message), hasher.finalize());
+    }
+
+    #[test]
+    fn reset() {
+        let mut hasher = Blake2s::with_params(16, b"secret", &[], &[]);
+        let reference = hasher.clone();
+        hasher.update(b"garbage");
+        hasher.reset();
+        assert_eq!(reference.finalize(), hasher.finalize());
+
+        let mut hasher = Blake2sp::default();
+        hasher.update(&[0x5a; 1000]);
+        hasher.reset();
+        assert_eq!(Blake2sp::digest(b""), hasher.finalize());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_key() {
+        Blake2s::with_params(32, &[0u8; 33], &[], &[]);
+    }
+}
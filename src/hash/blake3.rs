@@ -0,0 +1,709 @@
+//! # BLAKE3
+//!
+//! `blake3` implements the BLAKE3 hash function.
+//! BLAKE3 splits its input into 1024-byte chunks, hashes each chunk with a
+//! reduced-round BLAKE2s compression function, and combines the chunk
+//! chaining values in a binary Merkle tree: the chunks can be hashed
+//! independently, so long inputs can be hashed on several threads.
+//! The same function provides a keyed hash (a MAC) and a key derivation
+//! function, and its output can be extended to any length.
+
+use std::thread;
+
+use super::blake2s::g;
+use super::sha2::SHA256_IV;
+use super::Hasher;
+
+/// The size of a key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 32;
+/// The size of a block (in bytes).
+pub const BLOCK_SIZE_BYTES: usize = 64;
+/// The size of a chunk (in bytes).
+pub const CHUNK_SIZE_BYTES: usize = 1024;
+
+/// The size of a default digest (in bytes).
+const OUT_SIZE_BYTES: usize = 32;
+/// The minimum input size worth splitting between threads (in bytes).
+const MIN_PARALLEL_SIZE_BYTES: usize = 64 * CHUNK_SIZE_BYTES;
+
+/// The domain separation flags.
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+const KEYED_HASH: u32 = 1 << 4;
+const DERIVE_KEY_CONTEXT: u32 = 1 << 5;
+const DERIVE_KEY_MATERIAL: u32 = 1 << 6;
+
+/// The permutation of the message words between rounds.
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+/// A chaining value.
+type ChainingValue = [u32; 8];
+
+/// compress applies the compression function to a block, and returns the
+/// whole state: its first half is the new chaining value, and the whole
+/// state is extended output.
+fn compress(
+    cv: &ChainingValue,
+    block: &[u32; 16],
+    counter: u64,
+    block_len: usize,
+    flags: u32,
+) -> [u32; 16] {
+    let mut v = [0u32; 16];
+    v[..8].copy_from_slice(cv);
+    v[8..12].copy_from_slice(&SHA256_IV[..4]);
+    v[12] = counter as u32;
+    v[13] = (counter >> 32) as u32;
+    v[14] = block_len as u32;
+    v[15] = flags;
+
+    let mut m = *block;
+    for round in 0..7 {
+        // Mix the columns, then the diagonals.
+        g(&mut v, 0, 4, 8, 12, m[0], m[1]);
+        g(&mut v, 1, 5, 9, 13, m[2], m[3]);
+        g(&mut v, 2, 6, 10, 14, m[4], m[5]);
+        g(&mut v, 3, 7, 11, 15, m[6], m[7]);
+        g(&mut v, 0, 5, 10, 15, m[8], m[9]);
+        g(&mut v, 1, 6, 11, 12, m[10], m[11]);
+        g(&mut v, 2, 7, 8, 13, m[12], m[13]);
+        g(&mut v, 3, 4, 9, 14, m[14], m[15]);
+
+        if round < 6 {
+            let mut permuted = [0u32; 16];
+            for i in 0..16 {
+                permuted[i] = m[MSG_PERMUTATION[i]];
+            }
+            m = permuted;
+        }
+    }
+
+    for i in 0..8 {
+        v[i] ^= v[i + 8];
+        v[i + 8] ^= cv[i];
+    }
+
+    v
+}
+
+/// words reads a block as little-endian words.
+fn words(block: &[u8; BLOCK_SIZE_BYTES]) -> [u32; 16] {
+    let mut w = [0u32; 16];
+    for i in 0..16 {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&block[4 * i..4 * (i + 1)]);
+        w[i] = u32::from_le_bytes(word);
+    }
+
+    w
+}
+
+/// The last compression of a node, which is deferred until we know whether
+/// the node is the root.
+#[derive(Debug, Clone)]
+struct Output {
+    cv: ChainingValue,
+    block: [u32; 16],
+    counter: u64,
+    block_len: usize,
+    flags: u32,
+}
+
+impl Output {
+    /// chaining_value returns the chaining value of a node that is not the
+    /// root.
+    fn chaining_value(&self) -> ChainingValue {
+        let v = compress(
+            &self.cv,
+            &self.block,
+            self.counter,
+            self.block_len,
+            self.flags,
+        );
+        let mut cv = [0u32; 8];
+        cv.copy_from_slice(&v[..8]);
+        cv
+    }
+
+    /// root_block returns a block of output of the root node.
+    fn root_block(&self, counter: u64) -> [u8; BLOCK_SIZE_BYTES] {
+        let v = compress(
+            &self.cv,
+            &self.block,
+            counter,
+            self.block_len,
+            self.flags | ROOT,
+        );
+        let mut block = [0u8; BLOCK_SIZE_BYTES];
+        for i in 0..16 {
+            block[4 * i..4 * (i + 1)].copy_from_slice(&v[i].to_le_bytes());
+        }
+
+        block
+    }
+}
+
+/// parent_output returns the output of a parent node.
+fn parent_output(
+    left: &ChainingValue,
+    right: &ChainingValue,
+    key: &ChainingValue,
+    flags: u32,
+) -> Output {
+    let mut block = [0u32; 16];
+    block[..8].copy_from_slice(left);
+    block[8..].copy_from_slice(right);
+
+    Output {
+        cv: *key,
+        block,
+        counter: 0,
+        block_len: BLOCK_SIZE_BYTES,
+        flags: flags | PARENT,
+    }
+}
+
+/// The state of the chunk being hashed.
+#[derive(Debug, Clone)]
+struct ChunkState {
+    cv: ChainingValue,
+    counter: u64,
+    block: [u8; BLOCK_SIZE_BYTES],
+    block_len: usize,
+    blocks_compressed: usize,
+    flags: u32,
+}
+
+impl ChunkState {
+    /// new starts the chunk with the given index.
+    fn new(key: &ChainingValue, counter: u64, flags: u32) -> ChunkState {
+        ChunkState {
+            cv: *key,
+            counter,
+            block: [0u8; BLOCK_SIZE_BYTES],
+            block_len: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    /// len returns the number of bytes fed to the chunk.
+    fn len(&self) -> usize {
+        BLOCK_SIZE_BYTES * self.blocks_compressed + self.block_len
+    }
+
+    /// start_flag returns CHUNK_START for the first block of the chunk.
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    /// update feeds more data to the chunk, which must not overflow it.
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // The last block is treated differently, so a full block is
+            // only compressed once we know more data follows.
+            if self.block_len == BLOCK_SIZE_BYTES {
+                let flags = self.flags | self.start_flag();
+                let v = compress(
+                    &self.cv,
+                    &words(&self.block),
+                    self.counter,
+                    BLOCK_SIZE_BYTES,
+                    flags,
+                );
+                self.cv.copy_from_slice(&v[..8]);
+                self.blocks_compressed += 1;
+                self.block = [0u8; BLOCK_SIZE_BYTES];
+                self.block_len = 0;
+            }
+
+            let n = (BLOCK_SIZE_BYTES - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&data[..n]);
+            self.block_len += n;
+            data = &data[n..];
+        }
+    }
+
+    /// output returns the last compression of the chunk.
+    fn output(&self) -> Output {
+        Output {
+            cv: self.cv,
+            block: words(&self.block),
+            counter: self.counter,
+            block_len: self.block_len,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+/// subtree_cv returns the chaining value of a subtree that is not the
+/// root, using up to the given number of threads.
+fn subtree_cv(
+    data: &[u8],
+    key: &ChainingValue,
+    counter: u64,
+    flags: u32,
+    threads: usize,
+) -> ChainingValue {
+    if data.len() <= CHUNK_SIZE_BYTES {
+        let mut chunk = ChunkState::new(key, counter, flags);
+        chunk.update(data);
+        return chunk.output().chaining_value();
+    }
+
+    let (left, right) = subtree_children(data, key, counter, flags, threads);
+    parent_output(&left, &right, key, flags).chaining_value()
+}
+
+/// subtree_children returns the chaining values of the two children of a
+/// subtree of more than one chunk.
+/// The left child is the largest complete tree of chunks that leaves at
+/// least one byte for the right child.
+fn subtree_children(
+    data: &[u8],
+    key: &ChainingValue,
+    counter: u64,
+    flags: u32,
+    threads: usize,
+) -> (ChainingValue, ChainingValue) {
+    let full_chunks = (data.len() - 1) / CHUNK_SIZE_BYTES;
+    let left_len = CHUNK_SIZE_BYTES << (63 - (full_chunks as u64).leading_zeros());
+    let (left, right) = data.split_at(left_len);
+    let right_counter = counter + (left_len / CHUNK_SIZE_BYTES) as u64;
+
+    if threads < 2 || data.len() < MIN_PARALLEL_SIZE_BYTES {
+        return (
+            subtree_cv(left, key, counter, flags, 1),
+            subtree_cv(right, key, right_counter, flags, 1),
+        );
+    }
+
+    // The left half is hashed on a new thread, the right half on this one.
+    thread::scope(|s| {
+        let handle = s.spawn(|| subtree_cv(left, key, counter, flags, threads / 2));
+        let right_cv = subtree_cv(right, key, right_counter, flags, threads - threads / 2);
+        (handle.join().unwrap(), right_cv)
+    })
+}
+
+/// A BLAKE3 hasher.
+#[derive(Debug, Clone)]
+pub struct Blake3 {
+    key: ChainingValue,
+    chunk: ChunkState,
+    cv_stack: Vec<ChainingValue>,
+    flags: u32,
+}
+
+// Public methods.
+impl Blake3 {
+    /// new creates a BLAKE3 hasher.
+    pub fn new() -> Blake3 {
+        Blake3::with_key_words(SHA256_IV, 0)
+    }
+
+    /// new_keyed creates a keyed BLAKE3 hasher, which makes it a MAC.
+    pub fn new_keyed(key: &[u8; KEY_SIZE_BYTES]) -> Blake3 {
+        let mut key_words = [0u32; 8];
+        for i in 0..8 {
+            key_words[i] =
+                u32::from_le_bytes([key[4 * i], key[4 * i + 1], key[4 * i + 2], key[4 * i + 3]]);
+        }
+
+        Blake3::with_key_words(key_words, KEYED_HASH)
+    }
+
+    /// new_derive_key creates a BLAKE3 hasher that derives keys from the
+    /// key material fed to it.
+    /// The context string should be hardcoded, globally unique and
+    /// application specific, such as "example.com 2019-12-25 16:18:03
+    /// session tokens v1": keys derived with different contexts are
+    /// unrelated.
+    pub fn new_derive_key(context: &str) -> Blake3 {
+        let mut context_hasher = Blake3::with_key_words(SHA256_IV, DERIVE_KEY_CONTEXT);
+        context_hasher.update(context.as_bytes());
+        let key = context_hasher.final_output().root_block(0);
+
+        let mut key_words = [0u32; 8];
+        for i in 0..8 {
+            key_words[i] =
+                u32::from_le_bytes([key[4 * i], key[4 * i + 1], key[4 * i + 2], key[4 * i + 3]]);
+        }
+
+        Blake3::with_key_words(key_words, DERIVE_KEY_MATERIAL)
+    }
+
+    /// update_parallel feeds more data to the hasher, hashing large inputs
+    /// on as many threads as the platform can run in parallel.
+    /// The digest is the same as with `update`.
+    pub fn update_parallel(&mut self, data: &[u8]) {
+        let threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        self.update_with_threads(data, threads);
+    }
+
+    /// finalize_xof returns a reader for the extended output of all the
+    /// data fed so far.
+    /// Its first 32 bytes are the default digest.
+    pub fn finalize_xof(self) -> OutputReader {
+        OutputReader {
+            output: self.final_output(),
+            counter: 0,
+            block: [0u8; BLOCK_SIZE_BYTES],
+            position: BLOCK_SIZE_BYTES,
+        }
+    }
+}
+
+// Private methods.
+impl Blake3 {
+    /// with_key_words creates a hasher with the given key and flags.
+    fn with_key_words(key: ChainingValue, flags: u32) -> Blake3 {
+        Blake3 {
+            key,
+            chunk: ChunkState::new(&key, 0, flags),
+            cv_stack: Vec::new(),
+            flags,
+        }
+    }
+
+    /// update_with_threads feeds more data to the hasher, using up to the
+    /// given number of threads.
+    fn update_with_threads(&mut self, mut data: &[u8], threads: usize) {
+        // Complete the current chunk first.
+        if self.chunk.len() > 0 {
+            let n = (CHUNK_SIZE_BYTES - self.chunk.len()).min(data.len());
+            self.chunk.update(&data[..n]);
+            data = &data[n..];
+            if data.is_empty() {
+                return;
+            }
+
+            let cv = self.chunk.output().chaining_value();
+            let counter = self.chunk.counter;
+            self.push_cv(cv, counter);
+            self.chunk = ChunkState::new(&self.key, counter + 1, self.flags);
+        }
+
+        // Hash whole subtrees, as long as at least one byte follows: the
+        // last chunk could be the root.
+        while data.len() > CHUNK_SIZE_BYTES {
+            // A subtree must be aligned on its own size.
+            let mut subtree_len = 1 << (63 - (data.len() as u64).leading_zeros());
+            let hashed_len = self.chunk.counter * CHUNK_SIZE_BYTES as u64;
+            while hashed_len & (subtree_len as u64 - 1) != 0 {
+                subtree_len /= 2;
+            }
+
+            let counter = self.chunk.counter;
+            let subtree_chunks = (subtree_len / CHUNK_SIZE_BYTES) as u64;
+            if subtree_chunks == 1 {
+                let cv = subtree_cv(&data[..subtree_len], &self.key, counter, self.flags, 1);
+                self.push_cv(cv, counter);
+            } else {
+                // The children of the subtree are pushed separately, since
+                // the subtree could be the root.
+                let (left, right) = subtree_children(
+                    &data[..subtree_len],
+                    &self.key,
+                    counter,
+                    self.flags,
+                    threads,
+                );
+                self.push_cv(left, counter);
+                self.push_cv(right, counter + subtree_chunks / 2);
+            }
+
+            self.chunk.counter += subtree_chunks;
+            data = &data[subtree_len..];
+        }
+
+        if !data.is_empty() {
+            self.chunk.update(data);
+            let counter = self.chunk.counter;
+            self.merge_cv_stack(counter);
+        }
+    }
+
+    /// merge_cv_stack merges the complete subtrees on the stack before the
+    /// given chunk: afterwards, the stack has one chaining value per bit
+    /// set in the chunk index.
+    /// Merging is lazy, because the last subtree could be the root.
+    fn merge_cv_stack(&mut self, counter: u64) {
+        while self.cv_stack.len() > counter.count_ones() as usize {
+            let right = self.cv_stack.pop().unwrap();
+            let left = self.cv_stack.pop().unwrap();
+            let parent = parent_output(&left, &right, &self.key, self.flags).chaining_value();
+            self.cv_stack.push(parent);
+        }
+    }
+
+    /// push_cv pushes the chaining value of a subtree starting at the given
+    /// chunk.
+    fn push_cv(&mut self, cv: ChainingValue, counter: u64) {
+        self.merge_cv_stack(counter);
+        self.cv_stack.push(cv);
+    }
+
+    /// final_output returns the output of the root node.
+    fn final_output(&self) -> Output {
+        let mut remaining = self.cv_stack.len();
+        if remaining == 0 {
+            return self.chunk.output();
+        }
+
+        // If the current chunk is empty, the last two chaining values on the
+        // stack are the children of a subtree that has not been merged yet.
+        let mut output = if self.chunk.len() > 0 {
+            self.chunk.output()
+        } else {
+            remaining -= 2;
+            parent_output(
+                &self.cv_stack[remaining],
+                &self.cv_stack[remaining + 1],
+                &self.key,
+                self.flags,
+            )
+        };
+
+        while remaining > 0 {
+            remaining -= 1;
+            output = parent_output(
+                &self.cv_stack[remaining],
+                &output.chaining_value(),
+                &self.key,
+                self.flags,
+            );
+        }
+
+        output
+    }
+}
+
+impl Default for Blake3 {
+    fn default() -> Blake3 {
+        Blake3::new()
+    }
+}
+
+impl Hasher for Blake3 {
+    const OUTPUT_SIZE_BYTES: usize = OUT_SIZE_BYTES;
+
+    fn update(&mut self, data: &[u8]) {
+        self.update_with_threads(data, 1);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.final_output().root_block(0)[..OUT_SIZE_BYTES].to_vec()
+    }
+
+    fn reset(&mut self) {
+        self.chunk = ChunkState::new(&self.key, 0, self.flags);
+        self.cv_stack.clear();
+    }
+}
+
+/// A reader for the extended output of BLAKE3.
+#[derive(Debug, Clone)]
+pub struct OutputReader {
+    output: Output,
+    counter: u64,
+    block: [u8; BLOCK_SIZE_BYTES],
+    position: usize,
+}
+
+impl OutputReader {
+    /// read fills the destination with output.
+    /// Successive calls return successive parts of the same output stream.
+    pub fn read(&mut self, dest: &mut [u8]) {
+        for b in dest.iter_mut() {
+            if self.position == BLOCK_SIZE_BYTES {
+                self.block = self.output.root_block(self.counter);
+                self.counter += 1;
+                self.position = 0;
+            }
+
+            *b = self.block[self.position];
+            self.position += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    // From the official test_vectors.json: the input is the byte sequence
+    // 0, 1, ..., 250, 0, 1, ... of the given length, and each output is 131
+    // bytes of extended output (hash, keyed hash, derived key).
+    const KEY: &[u8; KEY_SIZE_BYTES] = b"whats the Elvish word for friend";
+    const CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+    const VECTORS: &[(usize, &str, &str, &str)] = &[
+        (
+            0,
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262e00f03e7b69af26b7faaf09fcd333050338ddfe085b8cc869ca98b206c08243a26f5487789e8f660afe6c99ef9e0c52b92e7393024a80459cf91f476f9ffdbda7001c22e159b402631f277ca96f2defdf1078282314e763699a31c5363165421cce14d",
+            "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26b18171a2f22a4b94822c701f107153dba24918c4bae4d2945c20ece13387627d3b73cbf97b797d5e59948c7ef788f54372df45e45e4293c7dc18c1d41144a9758be58960856be1eabbe22c2653190de560ca3b2ac4aa692a9210694254c371e851bc8f",
+            "2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d905630c8be290dfcf3e6842f13bddd573c098c3f17361f1f206b8cad9d088aa4a3f746752c6b0ce6a83b0da81d59649257cdf8eb3e9f7d4998e41021fac119deefb896224ac99f860011f73609e6e0e4540f93b273e56547dfd3aa1a035ba6689d89a0",
+        ),
+        (
+            1,
+            "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213c3a6cb8bf623e20cdb535f8d1a5ffb86342d9c0b64aca3bce1d31f60adfa137b358ad4d79f97b47c3d5e79f179df87a3b9776ef8325f8329886ba42f07fb138bb502f4081cbcec3195c5871e6c23e2cc97d3c69a613eba131e5f1351f3f1da786545e5",
+            "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b6568c0490609413006fbd428eb3fd14e7756d90f73a4725fad147f7bf70fd61c4e0cf7074885e92b0e3f125978b4154986d4fb202a3f331a3fb6cf349a3a70e49990f98fe4289761c8602c4e6ab1138d31d3b62218078b2f3ba9a88e1d08d0dd4cea11",
+            "b3e2e340a117a499c6cf2398a19ee0d29cca2bb7404c73063382693bf66cb06c5827b91bf889b6b97c5477f535361caefca0b5d8c4746441c57617111933158950670f9aa8a05d791daae10ac683cbef8faf897c84e6114a59d2173c3f417023a35d6983f2c7dfa57e7fc559ad751dbfb9ffab39c2ef8c4aafebc9ae973a64f0c76551",
+        ),
+        (
+            1024,
+            "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af71cf8107265ecdaf8505b95d8fcec83a98a6a96ea5109d2c179c47a387ffbb404756f6eeae7883b446b70ebb144527c2075ab8ab204c0086bb22b7c93d465efc57f8d917f0b385c6df265e77003b85102967486ed57db5c5ca170ba441427ed9afa684e",
+            "75c46f6f3d9eb4f55ecaaee480db732e6c2105546f1e675003687c31719c7ba4a78bc838c72852d4f49c864acb7adafe2478e824afe51c8919d06168414c265f298a8094b1ad813a9b8614acabac321f24ce61c5a5346eb519520d38ecc43e89b5000236df0597243e4d2493fd626730e2ba17ac4d8824d09d1a4a8f57b8227778e2de",
+            "7356cd7720d5b66b6d0697eb3177d9f8d73a4a5c5e968896eb6a6896843027066c23b601d3ddfb391e90d5c8eccdef4ae2a264bce9e612ba15e2bc9d654af1481b2e75dbabe615974f1070bba84d56853265a34330b4766f8e75edd1f4a1650476c10802f22b64bd3919d246ba20a17558bc51c199efdec67e80a227251808d8ce5bad",
+        ),
+        (
+            1025,
+            "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444f4c4a22b4b399155358a994e52bf255de60035742ec71bd08ac275a1b51cc6bfe332b0ef84b409108cda080e6269ed4b3e2c3f7d722aa4cdc98d16deb554e5627be8f955c98e1d5f9565a9194cad0c4285f93700062d9595adb992ae68ff12800ab67a",
+            "357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69362396b77fdc0d2634a552970843722066c3c15902ae5097e00ff53f1e116f1cd5352720113a837ab2452cafbde4d54085d9cf5d21ca613071551b25d52e69d6c81123872b6f19cd3bc1333edf0c52b94de23ba772cf82636cff4542540a7738d5b930",
+            "effaa245f065fbf82ac186839a249707c3bddf6d3fdda22d1b95a3c970379bcb5d31013a167509e9066273ab6e2123bc835b408b067d88f96addb550d96b6852dad38e320b9d940f86db74d398c770f462118b35d2724efa13da97194491d96dd37c3c09cbef665953f2ee85ec83d88b88d11547a6f911c8217cca46defa2751e7f3ad",
+        ),
+        (
+            2049,
+            "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b687952256303096de31d71d74103403822a2e0bc1eb193e7aecc9643a76b7bbc0c9f9c52e8783aae98764ca468962b5c2ec92f0c74eb5448d519713e09413719431c802f948dd5d90425a4ecdadece9eb178d80f26efccae630734dff63340285adec2aed3b51073ad3",
+            "9f29700902f7c86e514ddc4df1e3049f258b2472b6dd5267f61bf13983b78dd5f9a88abfefdfa1e00b418971f2b39c64ca621e8eb37fceac57fd0c8fc8e117d43b81447be22d5d8186f8f5919ba6bcc6846bd7d50726c06d245672c2ad4f61702c646499ee1173daa061ffe15bf45a631e2946d616a4c345822f1151284712f76b2b0e",
+            "2ea477c5515cc3dd606512ee72bb3e0e758cfae7232826f35fb98ca1bcbdf27316d8e9e79081a80b046b60f6a263616f33ca464bd78d79fa18200d06c7fc9bffd808cc4755277a7d5e09da0f29ed150f6537ea9bed946227ff184cc66a72a5f8c1e4bd8b04e81cf40fe6dc4427ad5678311a61f4ffc39d195589bdbc670f63ae70f4b6",
+        ),
+        (
+            3073,
+            "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd39a27ae3b79d68d89da9bf25bc27139ae65a324918a5f9b7828181e52cf373c84f35b639b7fccbb985b6f2fa56aea0c18f531203497b8bbd3a07ceb5926f1cab74d14bd66486d9a91eba99059a98bd1cd25876b2af5a76c3e9eed554ed72ea952b603bf",
+            "68dede9bef00ba89e43f31a6825f4cf433389fedae75c04ee9f0cf16a427c95a96d6da3fe985054d3478865be9a092250839a697bbda74e279e8a9e69f0025e4cfddd6cfb434b1cd9543aaf97c635d1b451a4386041e4bb100f5e45407cbbc24fa53ea2de3536ccb329e4eb9466ec37093a42cf62b82903c696a93a50b702c80f3c3c5",
+            "72613c9ec9ff7e40f8f5c173784c532ad852e827dba2bf85b2ab4b76f7079081576288e552647a9d86481c2cae75c2dd4e7c5195fb9ada1ef50e9c5098c249d743929191441301c69e1f48505a4305ec1778450ee48b8e69dc23a25960fe33070ea549119599760a8a2d28aeca06b8c5e9ba58bc19e11fe57b6ee98aa44b2a8e6b14a5",
+        ),
+        (
+            4097,
+            "9b4052b38f1c5fc8b1f9ff7ac7b27cd242487b3d890d15c96a1c25b8aa0fb99505f91b0b5600a11251652eacfa9497b31cd3c409ce2e45cfe6c0a016967316c426bd26f619eab5d70af9a418b845c608840390f361630bd497b1ab44019316357c61dbe091ce72fc16dc340ac3d6e009e050b3adac4b5b2c92e722cffdc46501531956",
+            "00df940cd36bb9fa7cbbc3556744e0dbc8191401afe70520ba292ee3ca80abbc606db4976cfdd266ae0abf667d9481831ff12e0caa268e7d3e57260c0824115a54ce595ccc897786d9dcbf495599cfd90157186a46ec800a6763f1c59e36197e9939e900809f7077c102f888caaf864b253bc41eea812656d46742e4ea42769f89b83f",
+            "aca51029626b55fda7117b42a7c211f8c6e9ba4fe5b7a8ca922f34299500ead8a897f66a400fed9198fd61dd2d58d382458e64e100128075fc54b860934e8de2e84170734b06e1d212a117100820dbc48292d148afa50567b8b84b1ec336ae10d40c8c975a624996e12de31abbe135d9d159375739c333798a80c64ae895e51e22f3ad",
+        ),
+        (
+            8193,
+            "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3bb2282aa69be089359ea1154b9a9286c4a56af4de975a9aa4a5c497654914d279bea60bb6d2cf7225a2fa0ff5ef56bbe4b149f3ed15860f78b4e2ad04e158e375c1e0c0b551cd7dfc82f1b155c11b6b3ed51ec9edb30d133653bb5709d1dbd55f4e1ff6",
+            "954a2a75420c8d6547e3ba5b98d963e6fa6491addc8c023189cc519821b4a1f5f03228648fd983aef045c2fa8290934b0866b615f585149587dda2299039965328835a2b18f1d63b7e300fc76ff260b571839fe44876a4eae66cbac8c67694411ed7e09df51068a22c6e67d6d3dd2cca8ff12e3275384006c80f4db68023f24eebba57",
+            "af1e0346e389b17c23200270a64aa4e1ead98c61695d917de7d5b00491c9b0f12f20a01d6d622edf3de026a4db4e4526225debb93c1237934d71c7340bb5916158cbdafe9ac3225476b6ab57a12357db3abbad7a26c6e66290e44034fb08a20a8d0ec264f309994d2810c49cfba6989d7abb095897459f5425adb48aba07c5fb3c83c0",
+        ),
+        (
+            31744,
+            "62b6960e1a44bcc1eb1a611a8d6235b6b4b78f32e7abc4fb4c6cdcce94895c47860cc51f2b0c28a7b77304bd55fe73af663c02d3f52ea053ba43431ca5bab7bfea2f5e9d7121770d88f70ae9649ea713087d1914f7f312147e247f87eb2d4ffef0ac978bf7b6579d57d533355aa20b8b77b13fd09748728a5cc327a8ec470f4013226f",
+            "efa53b389ab67c593dba624d898d0f7353ab99e4ac9d42302ee64cbf9939a4193a7258db2d9cd32a7a3ecfce46144114b15c2fcb68a618a976bd74515d47be08b628be420b5e830fade7c080e351a076fbc38641ad80c736c8a18fe3c66ce12f95c61c2462a9770d60d0f77115bbcd3782b593016a4e728d4c06cee4505cb0c08a42ec",
+            "39772aef80e0ebe60596361e45b061e8f417429d529171b6764468c22928e28e9759adeb797a3fbf771b1bcea30150a020e317982bf0d6e7d14dd9f064bc11025c25f31e81bd78a921db0174f03dd481d30e93fd8e90f8b2fee209f849f2d2a52f31719a490fb0ba7aea1e09814ee912eba111a9fde9d5c274185f7bae8ba85d300a2b",
+        ),
+        (
+            102400,
+            "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085e01c59dab908c04c3342b816941a26d69c2605ebee5ec5291cc55e15b76146e6745f0601156c3596cb75065a9c57f35585a52e1ac70f69131c23d611ce11ee4ab1ec2c009012d236648e77be9295dd0426f29b764d65de58eb7d01dd42248204f45f8e",
+            "1c35d1a5811083fd7119f5d5d1ba027b4d01c0c6c49fb6ff2cf75393ea5db4a7f9dbdd3e1d81dcbca3ba241bb18760f207710b751846faaeb9dff8262710999a59b2aa1aca298a032d94eacfadf1aa192418eb54808db23b56e34213266aa08499a16b354f018fc4967d05f8b9d2ad87a7278337be9693fc638a3bfdbe314574ee6fc4",
+            "4652cff7a3f385a6103b5c260fc1593e13c778dbe608efb092fe7ee69df6e9c6d83a3e041bc3a48df2879f4a0a3ed40e7c961c73eff740f3117a0504c2dff4786d44fb17f1549eb0ba585e40ec29bf7732f0b7e286ff8acddc4cb1e23b87ff5d824a986458dcc6a04ac83969b80637562953df51ed1a7e90a7926924d2763778be8560",
+        ),
+    ];
+
+    fn input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn check(hasher: Blake3, data: &[u8], expected: &str) {
+        let expected = hex(expected);
+
+        // The default digest is a prefix of the extended output.
+        let mut clone = hasher.clone();
+        clone.update(data);
+        assert_eq!(&expected[..32], &clone.finalize()[..]);
+
+        let mut clone = hasher.clone();
+        clone.update(data);
+        let mut reader = clone.finalize_xof();
+        let mut output = vec![0u8; expected.len()];
+        reader.read(&mut output[..7]);
+        reader.read(&mut output[7..100]);
+        reader.read(&mut output[100..]);
+        assert_eq!(expected, output);
+
+        // Feed the same data in uneven pieces.
+        let mut hasher = hasher;
+        let mut data = data;
+        let mut size = 1;
+        while !data.is_empty() {
+            size = size * 7 % 2053 + 1;
+            let n = size.min(data.len());
+            hasher.update(&data[..n]);
+            data = &data[n..];
+        }
+        assert_eq!(&expected[..32], &hasher.finalize()[..]);
+    }
+
+    #[test]
+    fn hash() {
+        for &(len, expected, _, _) in VECTORS {
+            check(Blake3::new(), &input(len), expected);
+        }
+    }
+
+    #[test]
+    fn keyed_hash() {
+        for &(len, _, expected, _) in VECTORS {
+            check(Blake3::new_keyed(KEY), &input(len), expected);
+        }
+    }
+
+    #[test]
+    fn derive_key() {
+        for &(len, _, _, expected) in VECTORS {
+            check(Blake3::new_derive_key(CONTEXT), &input(len), expected);
+        }
+    }
+
+    #[test]
+    fn digest() {
+        assert_eq!(hex(&VECTORS[0].1[..64]), Blake3::digest(b""));
+    }
+
+    #[test]
+    fn parallel() {
+        let data = input(1 << 20 | 12345);
+        let mut sequential = Blake3::new();
+        sequential.update(&data);
+        let expected = sequential.finalize();
+
+        for &threads in &[2, 3, 8] {
+            let mut hasher = Blake3::new();
+            hasher.update_with_threads(&data, threads);
+            assert_eq!(expected, hasher.finalize());
+
+            // Start off a chunk boundary.
+            let mut hasher = Blake3::new();
+            hasher.update(&data[..100]);
+            hasher.update_with_threads(&data[100..], threads);
+            assert_eq!(expected, hasher.finalize());
+        }
+
+        let mut hasher = Blake3::new();
+        hasher.update_parallel(&data);
+        assert_eq!(expected, hasher.finalize());
+
+        let (len, expected, _, _) = VECTORS[VECTORS.len() - 1];
+        let mut hasher = Blake3::new();
+        hasher.update_parallel(&input(len));
+        assert_eq!(hex(&expected[..64]), hasher.finalize());
+    }
+
+    #[test]
+    fn reset() {
+        let mut hasher = Blake3::new_keyed(KEY);
+        hasher.update(&input(5000));
+        hasher.reset();
+        hasher.update(&input(1025));
+        assert_eq!(hex(&VECTORS[3].2[..64]), hasher.finalize());
+    }
+}
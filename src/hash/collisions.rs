@@ -0,0 +1,179 @@
+//! # Collisions
+//!
+//! `collisions` demonstrates published collisions of MD5 and SHA-1.
+//! Both are Merkle-Damgård hashes: once two messages of whole blocks
+//! collide, the internal states are equal, so the collision survives any
+//! common suffix.
+//! The MD5 collision of Wang et al. is two 128-byte messages that differ in
+//! six bytes.
+//! The SHAttered collision (Stevens et al., 2017) is two PDF files that
+//! share a prefix and a suffix, with two different blocks in between: the
+//! first 320 bytes collide, and the rest of each file (whose layout selects
+//! which image is displayed) is common.
+
+use super::Hasher;
+
+/// The first message of the MD5 collision of Wang et al. (2004).
+pub const MD5_COLLISION_1: [u8; 128] = [
+    0xd1, 0x31, 0xdd, 0x02, 0xc5, 0xe6, 0xee, 0xc4, 0x69, 0x3d, 0x9a, 0x06, 0x98, 0xaf, 0xf9, 0x5c,
+    0x2f, 0xca, 0xb5, 0x87, 0x12, 0x46, 0x7e, 0xab, 0x40, 0x04, 0x58, 0x3e, 0xb8, 0xfb, 0x7f, 0x89,
+    0x55, 0xad, 0x34, 0x06, 0x09, 0xf4, 0xb3, 0x02, 0x83, 0xe4, 0x88, 0x83, 0x25, 0x71, 0x41, 0x5a,
+    0x08, 0x51, 0x25, 0xe8, 0xf7, 0xcd, 0xc9, 0x9f, 0xd9, 0x1d, 0xbd, 0xf2, 0x80, 0x37, 0x3c, 0x5b,
+    0xd8, 0x82, 0x3e, 0x31, 0x56, 0x34, 0x8f, 0x5b, 0xae, 0x6d, 0xac, 0xd4, 0x36, 0xc9, 0x19, 0xc6,
+    0xdd, 0x53, 0xe2, 0xb4, 0x87, 0xda, 0x03, 0xfd, 0x02, 0x39, 0x63, 0x06, 0xd2, 0x48, 0xcd, 0xa0,
+    0xe9, 0x9f, 0x33, 0x42, 0x0f, 0x57, 0x7e, 0xe8, 0xce, 0x54, 0xb6, 0x70, 0x80, 0xa8, 0x0d, 0x1e,
+    0xc6, 0x98, 0x21, 0xbc, 0xb6, 0xa8, 0x83, 0x93, 0x96, 0xf9, 0x65, 0x2b, 0x6f, 0xf7, 0x2a, 0x70,
+];
+
+/// The second message of the MD5 collision of Wang et al. (2004).
+pub const MD5_COLLISION_2: [u8; 128] = [
+    0xd1, 0x31, 0xdd, 0x02, 0xc5, 0xe6, 0xee, 0xc4, 0x69, 0x3d, 0x9a, 0x06, 0x98, 0xaf, 0xf9, 0x5c,
+    0x2f, 0xca, 0xb5, 0x07, 0x12, 0x46, 0x7e, 0xab, 0x40, 0x04, 0x58, 0x3e, 0xb8, 0xfb, 0x7f, 0x89,
+    0x55, 0xad, 0x34, 0x06, 0x09, 0xf4, 0xb3, 0x02, 0x83, 0xe4, 0x88, 0x83, 0x25, 0xf1, 0x41, 0x5a,
+    0x08, 0x51, 0x25, 0xe8, 0xf7, 0xcd, 0xc9, 0x9f, 0xd9, 0x1d, 0xbd, 0x72, 0x80, 0x37, 0x3c, 0x5b,
+    0xd8, 0x82, 0x3e, 0x31, 0x56, 0x34, 0x8f, 0x5b, 0xae, 0x6d, 0xac, 0xd4, 0x36, 0xc9, 0x19, 0xc6,
+    0xdd, 0x53, 0xe2, 0x34, 0x87, 0xda, 0x03, 0xfd, 0x02, 0x39, 0x63, 0x06, 0xd2, 0x48, 0xcd, 0xa0,
+    0xe9, 0x9f, 0x33, 0x42, 0x0f, 0x57, 0x7e, 0xe8, 0xce, 0x54, 0xb6, 0x70, 0x80, 0x28, 0x0d, 0x1e,
+    0xc6, 0x98, 0x21, 0xbc, 0xb6, 0xa8, 0x83, 0x93, 0x96, 0xf9, 0x65, 0xab, 0x6f, 0xf7, 0x2a, 0x70,
+];
+
+/// The first 192 bytes of both SHAttered PDFs: a PDF header and the
+/// start of a JPEG comment.
+pub const SHATTERED_PREFIX: [u8; 192] = [
+    0x25, 0x50, 0x44, 0x46, 0x2d, 0x31, 0x2e, 0x33, 0x0a, 0x25, 0xe2, 0xe3, 0xcf, 0xd3, 0x0a, 0x0a,
+    0x0a, 0x31, 0x20, 0x30, 0x20, 0x6f, 0x62, 0x6a, 0x0a, 0x3c, 0x3c, 0x2f, 0x57, 0x69, 0x64, 0x74,
+    0x68, 0x20, 0x32, 0x20, 0x30, 0x20, 0x52, 0x2f, 0x48, 0x65, 0x69, 0x67, 0x68, 0x74, 0x20, 0x33,
+    0x20, 0x30, 0x20, 0x52, 0x2f, 0x54, 0x79, 0x70, 0x65, 0x20, 0x34, 0x20, 0x30, 0x20, 0x52, 0x2f,
+    0x53, 0x75, 0x62, 0x74, 0x79, 0x70, 0x65, 0x20, 0x35, 0x20, 0x30, 0x20, 0x52, 0x2f, 0x46, 0x69,
+    0x6c, 0x74, 0x65, 0x72, 0x20, 0x36, 0x20, 0x30, 0x20, 0x52, 0x2f, 0x43, 0x6f, 0x6c, 0x6f, 0x72,
+    0x53, 0x70, 0x61, 0x63, 0x65, 0x20, 0x37, 0x20, 0x30, 0x20, 0x52, 0x2f, 0x4c, 0x65, 0x6e, 0x67,
+    0x74, 0x68, 0x20, 0x38, 0x20, 0x30, 0x20, 0x52, 0x2f, 0x42, 0x69, 0x74, 0x73, 0x50, 0x65, 0x72,
+    0x43, 0x6f, 0x6d, 0x70, 0x6f, 0x6e, 0x65, 0x6e, 0x74, 0x20, 0x38, 0x3e, 0x3e, 0x0a, 0x73, 0x74,
+    0x72, 0x65, 0x61, 0x6d, 0x0a, 0xff, 0xd8, 0xff, 0xfe, 0x00, 0x24, 0x53, 0x48, 0x41, 0x2d, 0x31,
+    0x20, 0x69, 0x73, 0x20, 0x64, 0x65, 0x61, 0x64, 0x21, 0x21, 0x21, 0x21, 0x21, 0x85, 0x2f, 0xec,
+    0x09, 0x23, 0x39, 0x75, 0x9c, 0x39, 0xb1, 0xa1, 0xc6, 0x3c, 0x4c, 0x97, 0xe1, 0xff, 0xfe, 0x01,
+];
+
+/// The two near-collision blocks of the first SHAttered PDF.
+pub const SHATTERED_BLOCKS_1: [u8; 128] = [
+    0x73, 0x46, 0xdc, 0x91, 0x66, 0xb6, 0x7e, 0x11, 0x8f, 0x02, 0x9a, 0xb6, 0x21, 0xb2, 0x56, 0x0f,
+    0xf9, 0xca, 0x67, 0xcc, 0xa8, 0xc7, 0xf8, 0x5b, 0xa8, 0x4c, 0x79, 0x03, 0x0c, 0x2b, 0x3d, 0xe2,
+    0x18, 0xf8, 0x6d, 0xb3, 0xa9, 0x09, 0x01, 0xd5, 0xdf, 0x45, 0xc1, 0x4f, 0x26, 0xfe, 0xdf, 0xb3,
+    0xdc, 0x38, 0xe9, 0x6a, 0xc2, 0x2f, 0xe7, 0xbd, 0x72, 0x8f, 0x0e, 0x45, 0xbc, 0xe0, 0x46, 0xd2,
+    0x3c, 0x57, 0x0f, 0xeb, 0x14, 0x13, 0x98, 0xbb, 0x55, 0x2e, 0xf5, 0xa0, 0xa8, 0x2b, 0xe3, 0x31,
+    0xfe, 0xa4, 0x80, 0x37, 0xb8, 0xb5, 0xd7, 0x1f, 0x0e, 0x33, 0x2e, 0xdf, 0x93, 0xac, 0x35, 0x00,
+    0xeb, 0x4d, 0xdc, 0x0d, 0xec, 0xc1, 0xa8, 0x64, 0x79, 0x0c, 0x78, 0x2c, 0x76, 0x21, 0x56, 0x60,
+    0xdd, 0x30, 0x97, 0x91, 0xd0, 0x6b, 0xd0, 0xaf, 0x3f, 0x98, 0xcd, 0xa4, 0xbc, 0x46, 0x29, 0xb1,
+];
+
+/// The two near-collision blocks of the second SHAttered PDF.
+pub const SHATTERED_BLOCKS_2: [u8; 128] = [
+    0x7f, 0x46, 0xdc, 0x93, 0xa6, 0xb6, 0x7e, 0x01, 0x3b, 0x02, 0x9a, 0xaa, 0x1d, 0xb2, 0x56, 0x0b,
+    0x45, 0xca, 0x67, 0xd6, 0x88, 0xc7, 0xf8, 0x4b, 0x8c, 0x4c, 0x79, 0x1f, 0xe0, 0x2b, 0x3d, 0xf6,
+    0x14, 0xf8, 0x6d, 0xb1, 0x69, 0x09, 0x01, 0xc5, 0x6b, 0x45, 0xc1, 0x53, 0x0a, 0xfe, 0xdf, 0xb7,
+    0x60, 0x38, 0xe9, 0x72, 0x72, 0x2f, 0xe7, 0xad, 0x72, 0x8f, 0x0e, 0x49, 0x04, 0xe0, 0x46, 0xc2,
+    0x30, 0x57, 0x0f, 0xe9, 0xd4, 0x13, 0x98, 0xab, 0xe1, 0x2e, 0xf5, 0xbc, 0x94, 0x2b, 0xe3, 0x35,
+    0x42, 0xa4, 0x80, 0x2d, 0x98, 0xb5, 0xd7, 0x0f, 0x2a, 0x33, 0x2e, 0xc3, 0x7f, 0xac, 0x35, 0x14,
+    0xe7, 0x4d, 0xdc, 0x0f, 0x2c, 0xc1, 0xa8, 0x74, 0xcd, 0x0c, 0x78, 0x30, 0x5a, 0x21, 0x56, 0x64,
+    0x61, 0x30, 0x97, 0x89, 0x60, 0x6b, 0xd0, 0xbf, 0x3f, 0x98, 0xcd, 0xa8, 0x04, 0x46, 0x29, 0xa1,
+];
+
+/// shattered_prefixes returns the first 320 bytes of the two SHAttered PDFs,
+/// which have the same SHA-1 digest.
+pub fn shattered_prefixes() -> (Vec<u8>, Vec<u8>) {
+    let mut first = SHATTERED_PREFIX.to_vec();
+    first.extend_from_slice(&SHATTERED_BLOCKS_1);
+    let mut second = SHATTERED_PREFIX.to_vec();
+    second.extend_from_slice(&SHATTERED_BLOCKS_2);
+
+    (first, second)
+}
+
+/// is_shattered checks whether the data starts with one of the SHAttered
+/// prefixes: such data has the same SHA-1 digest as the data with the other
+/// prefix, so its SHA-1 digest does not identify it.
+pub fn is_shattered(data: &[u8]) -> bool {
+    let (first, second) = shattered_prefixes();
+    data.starts_with(&first) || data.starts_with(&second)
+}
+
+/// collides checks whether two different messages followed by the same
+/// suffix have the same digest.
+pub fn collides<H: Hasher + Default>(a: &[u8], b: &[u8], suffix: &[u8]) -> bool {
+    let mut ha = H::default();
+    ha.update(a);
+    ha.update(suffix);
+    let mut hb = H::default();
+    hb.update(b);
+    hb.update(suffix);
+
+    a != b && ha.finalize() == hb.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    use hash::md5::Md5;
+    use hash::ripemd160::Ripemd160;
+    use hash::sha1::Sha1;
+    use hash::sha2::Sha256;
+
+    #[test]
+    fn md5_collision() {
+        assert_eq!(
+            hex("79054025255fb1a26e4bc422aef54eb4"),
+            Md5::digest(&MD5_COLLISION_1)
+        );
+        assert_eq!(
+            hex("79054025255fb1a26e4bc422aef54eb4"),
+            Md5::digest(&MD5_COLLISION_2)
+        );
+
+        assert!(collides::<Md5>(&MD5_COLLISION_1, &MD5_COLLISION_2, b""));
+        assert!(collides::<Md5>(
+            &MD5_COLLISION_1,
+            &MD5_COLLISION_2,
+            b"there is no spoon"
+        ));
+
+        // The collision is specific to MD5.
+        assert!(!collides::<Sha1>(&MD5_COLLISION_1, &MD5_COLLISION_2, b""));
+        assert!(!collides::<Ripemd160>(
+            &MD5_COLLISION_1,
+            &MD5_COLLISION_2,
+            b""
+        ));
+    }
+
+    #[test]
+    fn shattered() {
+        let (first, second) = shattered_prefixes();
+        assert_eq!(
+            hex("f92d74e3874587aaf443d1db961d4e26dde13e9c"),
+            Sha1::digest(&first)
+        );
+        assert_eq!(
+            hex("f92d74e3874587aaf443d1db961d4e26dde13e9c"),
+            Sha1::digest(&second)
+        );
+
+        assert!(collides::<Sha1>(&first, &second, b"there is no spoon"));
+        assert!(!collides::<Sha256>(&first, &second, b""));
+        assert!(!collides::<Md5>(&first, &second, b""));
+    }
+
+    #[test]
+    fn shattered_detection() {
+        let (first, second) = shattered_prefixes();
+        let mut file = second.clone();
+        file.extend_from_slice(b"there is no spoon");
+        assert!(is_shattered(&first));
+        assert!(is_shattered(&file));
+
+        // The common prefix alone does not collide.
+        assert!(!is_shattered(&SHATTERED_PREFIX));
+        assert!(!is_shattered(&MD5_COLLISION_1));
+    }
+}
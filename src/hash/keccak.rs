@@ -0,0 +1,279 @@
+//! # Keccak
+//!
+//! `keccak` implements the Keccak-f[1600] permutation and the sponge
+//! construction built on it (FIPS 202).
+//! A sponge absorbs data into the first `rate` bytes of a 200-byte state,
+//! permuting the whole state each time this part is full, and then
+//! squeezes output from the same bytes.
+//! The remaining bytes (the capacity) are never directly exposed, and
+//! determine the security of the construction.
+//! SHA-3, SHAKE, cSHAKE and KMAC are all sponges with different rates and
+//! padding domains.
+
+/// The size of the Keccak-f[1600] state (in bytes).
+pub const STATE_SIZE_BYTES: usize = 200;
+/// The number of rounds of Keccak-f[1600].
+pub const ROUNDS: usize = 24;
+
+/// A Keccak-f[1600] state: 25 lanes of 64 bits, where lane (x, y) is at
+/// index x + 5 * y.
+pub type State = [u64; 25];
+
+/// The round constants of the iota step.
+const ROUND_CONSTANTS: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// keccak_f1600 applies the Keccak-f[1600] permutation to a state.
+pub fn keccak_f1600(a: &mut State) {
+    for rc in ROUND_CONSTANTS.iter() {
+        // Theta: add the parities of two neighbouring columns to each lane.
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+
+        // Rho and pi: lane (x, y) is rotated and moved to (y, 2x + 3y).
+        // Starting from (1, 0), this walks through the 24 lanes other than
+        // (0, 0), the t-th of which is rotated by (t + 1)(t + 2) / 2.
+        let (mut x, mut y) = (1, 0);
+        let mut current = a[x + 5 * y];
+        for t in 0..24 {
+            let next = (y, (2 * x + 3 * y) % 5);
+            x = next.0;
+            y = next.1;
+            let rotation = ((t + 1) * (t + 2) / 2 % 64) as u32;
+            let moved = current.rotate_left(rotation);
+            current = a[x + 5 * y];
+            a[x + 5 * y] = moved;
+        }
+
+        // Chi: the only non-linear step, applied to each row.
+        for y in 0..5 {
+            let row = [
+                a[5 * y],
+                a[5 * y + 1],
+                a[5 * y + 2],
+                a[5 * y + 3],
+                a[5 * y + 4],
+            ];
+            for x in 0..5 {
+                a[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota: break the symmetry between rounds.
+        a[0] ^= rc;
+    }
+}
+
+/// A sponge built on Keccak-f[1600].
+/// A sponge first absorbs data, then is padded once, and then squeezes
+/// any amount of output.
+#[derive(Debug, Clone)]
+pub struct Sponge {
+    state: State,
+    rate: usize,
+    position: usize,
+    squeezing: bool,
+}
+
+// Public methods.
+impl Sponge {
+    /// new creates an empty sponge with the given rate (in bytes).
+    /// The capacity is what remains of the state, so a sponge with a
+    /// capacity of c bytes offers at most c / 2 bytes of security.
+    /// It panics if the rate is not a multiple of 8 between 8 and 192.
+    pub fn new(rate: usize) -> Sponge {
+        if rate == 0 || rate >= STATE_SIZE_BYTES || !rate.is_multiple_of(8) {
+            panic!("Rate must be a multiple of 8 between 8 and 192.");
+        }
+
+        Sponge {
+            state: [0u64; 25],
+            rate,
+            position: 0,
+            squeezing: false,
+        }
+    }
+
+    /// rate returns the rate of the sponge (in bytes).
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// is_squeezing returns whether the sponge has been padded.
+    pub fn is_squeezing(&self) -> bool {
+        self.squeezing
+    }
+
+    /// absorb feeds more data to the sponge.
+    /// It panics if the sponge has already been padded.
+    pub fn absorb(&mut self, data: &[u8]) {
+        if self.squeezing {
+            panic!("Cannot absorb after squeezing.");
+        }
+
+        for b in data {
+            self.xor_byte(self.position, *b);
+            self.position += 1;
+            if self.position == self.rate {
+                keccak_f1600(&mut self.state);
+                self.position = 0;
+            }
+        }
+    }
+
+    /// pad ends the absorbing phase with the pad10*1 rule, preceded by the
+    /// domain separation bits.
+    /// The domain byte contains these bits followed by the first bit of the
+    /// padding, least significant bit first: 0x06 for SHA-3, 0x1f for
+    /// SHAKE, 0x04 for cSHAKE and 0x01 for the original Keccak.
+    /// It panics if the sponge has already been padded.
+    pub fn pad(&mut self, domain: u8) {
+        if self.squeezing {
+            panic!("Cannot pad twice.");
+        }
+
+        self.xor_byte(self.position, domain);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccak_f1600(&mut self.state);
+        self.position = 0;
+        self.squeezing = true;
+    }
+
+    /// squeeze fills the destination with output.
+    /// Successive calls return successive parts of the same output stream.
+    /// It panics if the sponge has not been padded.
+    pub fn squeeze(&mut self, dest: &mut [u8]) {
+        if !self.squeezing {
+            panic!("Cannot squeeze before padding.");
+        }
+
+        for b in dest.iter_mut() {
+            if self.position == self.rate {
+                keccak_f1600(&mut self.state);
+                self.position = 0;
+            }
+
+            *b = (self.state[self.position / 8] >> (8 * (self.position % 8))) as u8;
+            self.position += 1;
+        }
+    }
+
+    /// reset empties the sponge, keeping its rate.
+    pub fn reset(&mut self) {
+        *self = Sponge::new(self.rate);
+    }
+}
+
+// Private methods.
+impl Sponge {
+    /// xor_byte adds a byte to the state.
+    /// Lanes are little-endian, so byte i is in lane i / 8.
+    fn xor_byte(&mut self, i: usize, b: u8) {
+        self.state[i / 8] ^= (b as u64) << (8 * (i % 8));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutation_of_zero_state() {
+        // Keccak-f[1600] applied to the all-zero state, from the Keccak
+        // team's KeccakF-1600-IntermediateValues.txt.
+        let mut state = [0u64; 25];
+        keccak_f1600(&mut state);
+        assert_eq!(0xf1258f7940e1dde7, state[0]);
+        assert_eq!(0x84d5ccf933c0478a, state[1]);
+
+        keccak_f1600(&mut state);
+        assert_eq!(0x2d5c954df96ecb3c, state[0]);
+    }
+
+    #[test]
+    fn original_keccak_padding() {
+        // Keccak-256 as used before standardization (e.g. by Ethereum).
+        let mut sponge = Sponge::new(136);
+        sponge.pad(0x01);
+        let mut out = [0u8; 4];
+        sponge.squeeze(&mut out);
+        assert_eq!([0xc5, 0xd2, 0x46, 0x01], out);
+    }
+
+    #[test]
+    fn squeeze_in_pieces() {
+        let mut sponge = Sponge::new(72);
+        sponge.absorb(b"there is no spoon");
+        sponge.pad(0x1f);
+        let mut reference = sponge.clone();
+
+        let mut all = [0u8; 300];
+        reference.squeeze(&mut all);
+
+        let mut pieces = Vec::new();
+        for size in [1, 71, 72, 100, 56].iter() {
+            let mut piece = vec![0u8; *size];
+            sponge.squeeze(&mut piece);
+            pieces.extend(piece);
+        }
+
+        assert_eq!(all.to_vec(), pieces);
+    }
+
+    #[test]
+    fn reset() {
+        let mut sponge = Sponge::new(136);
+        sponge.absorb(b"garbage");
+        sponge.pad(0x06);
+        sponge.reset();
+        assert!(!sponge.is_squeezing());
+        assert_eq!(136, sponge.rate());
+        assert_eq!([0u64; 25], sponge.state);
+    }
+
+    #[test]
+    #[should_panic]
+    fn absorb_after_squeezing() {
+        let mut sponge = Sponge::new(136);
+        sponge.pad(0x06);
+        sponge.absorb(b"late");
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_rate() {
+        Sponge::new(200);
+    }
+}
@@ -0,0 +1,173 @@
+//! # MD5
+//!
+//! `md5` implements the MD5 hash function (RFC 1321).
+//! MD5 is broken: collisions can be found in seconds on a laptop, and
+//! chosen-prefix collisions have been used to forge certificates (see
+//! `hash::collisions`).
+//! It is only provided to verify checksums of legacy data, where no
+//! attacker chose the data.
+
+use super::{BlockHasher, Hasher, MdBuffer, MD_BLOCK_SIZE_BYTES};
+
+/// The size of a block (in bytes).
+const BLOCK_SIZE_BYTES: usize = MD_BLOCK_SIZE_BYTES;
+
+/// The initial value.
+const IV: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// The round constants: the first 32 bits of the absolute values of the
+/// sines of 1 to 64.
+const K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// The rotation amounts of each round, for each step modulo 4.
+const R: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+/// A MD5 hasher.
+#[derive(Debug, Clone)]
+pub struct Md5 {
+    h: [u32; 4],
+    buffer: MdBuffer,
+}
+
+impl Md5 {
+    /// new creates a MD5 hasher.
+    pub fn new() -> Md5 {
+        Md5 {
+            h: IV,
+            buffer: MdBuffer::new(),
+        }
+    }
+}
+
+impl Default for Md5 {
+    fn default() -> Md5 {
+        Md5::new()
+    }
+}
+
+impl Hasher for Md5 {
+    const OUTPUT_SIZE_BYTES: usize = 16;
+
+    fn update(&mut self, data: &[u8]) {
+        let h = &mut self.h;
+        self.buffer.update(data, |block| compress(h, block));
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        let h = &mut self.h;
+        self.buffer
+            .finalize(u64::to_le_bytes, |block| compress(h, block));
+
+        self.h
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        *self = Md5::new();
+    }
+}
+
+impl BlockHasher for Md5 {
+    const BLOCK_SIZE_BYTES: usize = BLOCK_SIZE_BYTES;
+}
+
+/// compress processes a 64-byte block with the MD5 compression function.
+fn compress(h: &mut [u32; 4], block: &[u8; BLOCK_SIZE_BYTES]) {
+    let mut m = [0u32; 16];
+    for i in 0..16 {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&block[4 * i..4 * (i + 1)]);
+        m[i] = u32::from_le_bytes(word);
+    }
+
+    let [mut a, mut b, mut c, mut d] = *h;
+    for i in 0..64 {
+        // Each round has its own boolean function and message word order.
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+
+        let t = a.wrapping_add(f).wrapping_add(K[i]).wrapping_add(m[g]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(t.rotate_left(R[i / 16][i % 4]));
+    }
+
+    for (x, y) in h.iter_mut().zip([a, b, c, d].iter()) {
+        *x = x.wrapping_add(*y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    #[test]
+    fn rfc1321_vectors() {
+        let vectors: [(&str, &str); 7] = [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("a", "0cc175b9c0f1b6a831c399e269772661"),
+            ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+            ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+
+        for &(message, digest) in vectors.iter() {
+            assert_eq!(hex(digest), Md5::digest(message.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn streaming() {
+        // The longest RFC 1321 message fed in pieces that straddle the
+        // block boundary and the padding limit.
+        let message =
+            b"12345678901234567890123456789012345678901234567890123456789012345678901234567890";
+        let mut hasher = Md5::new();
+        for chunk in message.chunks(7) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hex("57edf4a22be3c955ac49da2e2107b67a"), hasher.finalize());
+    }
+
+    #[test]
+    fn reset() {
+        let mut hasher = Md5::new();
+        hasher.update(b"garbage");
+        hasher.reset();
+        hasher.update(b"abc");
+        assert_eq!(hex("900150983cd24fb0d6963f7d28e17f72"), hasher.finalize());
+    }
+}
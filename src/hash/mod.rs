@@ -0,0 +1,141 @@
+//! # Hash
+//!
+//! `hash` contains a collection of cryptographic hash functions.
+//! A hash function maps data of any length to a short digest, such that it
+//! is infeasible to find two inputs with the same digest, or an input
+//! with a given digest.
+
+pub mod blake2b;
+pub mod blake2s;
+pub mod blake3;
+pub mod collisions;
+pub mod keccak;
+pub mod md5;
+pub mod ripemd160;
+pub mod rumba20;
+pub mod sha1;
+pub mod sha2;
+pub mod sha3;
+
+/// A streaming hash function.
+/// Data can be fed in any number of pieces: the digest only depends on the
+/// concatenation of all the pieces.
+pub trait Hasher {
+    /// The size of a digest (in bytes).
+    /// Hashers with a variable output size use their default size here,
+    /// and their digests have the size they were created with.
+    const OUTPUT_SIZE_BYTES: usize;
+
+    /// update feeds more data to the hasher.
+    fn update(&mut self, data: &[u8]);
+
+    /// finalize computes the digest of all the data fed so far.
+    fn finalize(self) -> Vec<u8>;
+
+    /// reset discards the data fed so far.
+    fn reset(&mut self);
+
+    /// digest computes the digest of a message in one call.
+    fn digest(message: &[u8]) -> Vec<u8>
+    where
+        Self: Default + Sized,
+    {
+        let mut hasher = Self::default();
+        hasher.update(message);
+        hasher.finalize()
+    }
+}
+
+/// A hash function that processes its input in blocks of a fixed size, such
+/// as the hash functions HMAC is defined for.
+pub trait BlockHasher: Hasher + Default + Clone {
+    /// The size of a block (in bytes).
+    const BLOCK_SIZE_BYTES: usize;
+}
+
+/// The size of the blocks of the Merkle–Damgård hashers sharing `MdBuffer`
+/// (in bytes).
+pub(crate) const MD_BLOCK_SIZE_BYTES: usize = 64;
+
+/// MdBuffer splits the input of MD5, SHA-1 and RIPEMD-160 into 64-byte
+/// blocks, and pads the last block with a 1 bit, zeros and the 64-bit
+/// message length (in bits).
+#[derive(Debug, Clone)]
+pub(crate) struct MdBuffer {
+    buffer: [u8; MD_BLOCK_SIZE_BYTES],
+    buffer_len: usize,
+    length: u64,
+}
+
+impl MdBuffer {
+    /// new creates an empty buffer.
+    pub(crate) fn new() -> MdBuffer {
+        MdBuffer {
+            buffer: [0u8; MD_BLOCK_SIZE_BYTES],
+            buffer_len: 0,
+            length: 0,
+        }
+    }
+
+    /// update buffers the data, and compresses every complete block.
+    pub(crate) fn update<F>(&mut self, data: &[u8], mut compress: F)
+    where
+        F: FnMut(&[u8; MD_BLOCK_SIZE_BYTES]),
+    {
+        self.length = self.length.wrapping_add(data.len() as u64);
+        for b in data {
+            self.buffer[self.buffer_len] = *b;
+            self.buffer_len += 1;
+            if self.buffer_len == MD_BLOCK_SIZE_BYTES {
+                compress(&self.buffer);
+                self.buffer_len = 0;
+            }
+        }
+    }
+
+    /// finalize pads the message and compresses the last blocks; the
+    /// length is encoded with `length_bytes` (little-endian for MD5 and
+    /// RIPEMD-160, big-endian for SHA-1).
+    pub(crate) fn finalize<F>(mut self, length_bytes: fn(u64) -> [u8; 8], mut compress: F)
+    where
+        F: FnMut(&[u8; MD_BLOCK_SIZE_BYTES]),
+    {
+        let bit_length = self.length.wrapping_mul(8);
+        let zeros = (MD_BLOCK_SIZE_BYTES + 55 - self.buffer_len) % MD_BLOCK_SIZE_BYTES;
+        let mut padding = vec![0x80];
+        padding.extend(vec![0u8; zeros]);
+        padding.extend(&length_bytes(bit_length));
+        self.update(&padding, &mut compress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn md_padding() {
+        // The padded message is the message, a 1 bit, the fewest zeros that
+        // leave 8 bytes in the last block, and the length.
+        let message: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        for n in 0..message.len() {
+            let mut blocks = Vec::new();
+            let mut buffer = MdBuffer::new();
+            buffer.update(&message[..n], |b| blocks.extend_from_slice(b));
+            buffer.finalize(u64::to_be_bytes, |b| blocks.extend_from_slice(b));
+
+            let padded_len = (n + 8) / MD_BLOCK_SIZE_BYTES * MD_BLOCK_SIZE_BYTES + 64;
+            assert_eq!(padded_len, blocks.len());
+            assert_eq!(message[..n], blocks[..n]);
+            assert_eq!(0x80, blocks[n]);
+            assert!(blocks[n + 1..padded_len - 8].iter().all(|b| *b == 0));
+            assert_eq!((8 * n as u64).to_be_bytes(), blocks[padded_len - 8..]);
+        }
+
+        let mut blocks = Vec::new();
+        let mut buffer = MdBuffer::new();
+        buffer.update(b"abc", |_| {});
+        buffer.finalize(u64::to_le_bytes, |b| blocks.extend_from_slice(b));
+        assert_eq!([24, 0, 0, 0, 0, 0, 0, 0], blocks[56..]);
+    }
+}
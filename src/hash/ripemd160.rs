@@ -0,0 +1,235 @@
+//! # RIPEMD-160
+//!
+//! `ripemd160` implements the RIPEMD-160 hash function.
+//! RIPEMD-160 runs two MD5-like lines of 80 steps in parallel, with
+//! different constants, boolean functions and message word orders, and
+//! combines them at the end of each block.
+//! No collision has been found, but its 160-bit digest only gives 80 bits
+//! of collision resistance, and it is considered broken for new designs.
+//! It is only provided to verify legacy data, such as Bitcoin addresses.
+
+use super::{BlockHasher, Hasher, MdBuffer, MD_BLOCK_SIZE_BYTES};
+
+/// The size of a block (in bytes).
+const BLOCK_SIZE_BYTES: usize = MD_BLOCK_SIZE_BYTES;
+
+/// The initial value.
+const IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// The round constants of the left line.
+const KL: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+/// The round constants of the right line.
+const KR: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// The message word order of the left line.
+const RL: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5,
+    2, 14, 11, 8, 3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, 1, 9, 11, 10, 0, 8, 12, 4,
+    13, 3, 7, 15, 14, 5, 6, 2, 4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+/// The message word order of the right line.
+const RR: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, 6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12,
+    4, 9, 1, 2, 15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, 8, 6, 4, 1, 3, 11, 15, 0, 5,
+    12, 2, 13, 9, 7, 10, 14, 12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// The rotation amounts of the left line.
+const SL: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, 7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15,
+    9, 11, 7, 13, 12, 11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, 11, 12, 14, 15, 14,
+    15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, 9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+/// The rotation amounts of the right line.
+const SR: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, 9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12,
+    7, 6, 15, 13, 11, 9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, 15, 5, 8, 11, 14, 14,
+    6, 14, 6, 9, 12, 9, 12, 5, 15, 8, 8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// A RIPEMD-160 hasher.
+#[derive(Debug, Clone)]
+pub struct Ripemd160 {
+    h: [u32; 5],
+    buffer: MdBuffer,
+}
+
+impl Ripemd160 {
+    /// new creates a RIPEMD-160 hasher.
+    pub fn new() -> Ripemd160 {
+        Ripemd160 {
+            h: IV,
+            buffer: MdBuffer::new(),
+        }
+    }
+}
+
+impl Default for Ripemd160 {
+    fn default() -> Ripemd160 {
+        Ripemd160::new()
+    }
+}
+
+impl Hasher for Ripemd160 {
+    const OUTPUT_SIZE_BYTES: usize = 20;
+
+    fn update(&mut self, data: &[u8]) {
+        let h = &mut self.h;
+        self.buffer.update(data, |block| compress(h, block));
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        let h = &mut self.h;
+        self.buffer
+            .finalize(u64::to_le_bytes, |block| compress(h, block));
+
+        self.h
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        *self = Ripemd160::new();
+    }
+}
+
+impl BlockHasher for Ripemd160 {
+    const BLOCK_SIZE_BYTES: usize = BLOCK_SIZE_BYTES;
+}
+
+/// f is the boolean function of the given round.
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// compress processes a 64-byte block with the RIPEMD-160 compression
+/// function.
+fn compress(h: &mut [u32; 5], block: &[u8; BLOCK_SIZE_BYTES]) {
+    let mut m = [0u32; 16];
+    for i in 0..16 {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&block[4 * i..4 * (i + 1)]);
+        m[i] = u32::from_le_bytes(word);
+    }
+
+    let [mut al, mut bl, mut cl, mut dl, mut el] = *h;
+    let [mut ar, mut br, mut cr, mut dr, mut er] = *h;
+    for j in 0..80 {
+        let round = j / 16;
+
+        // The right line uses the boolean functions in reverse order.
+        let t = al
+            .wrapping_add(f(round, bl, cl, dl))
+            .wrapping_add(m[RL[j]])
+            .wrapping_add(KL[round])
+            .rotate_left(SL[j])
+            .wrapping_add(el);
+        al = el;
+        el = dl;
+        dl = cl.rotate_left(10);
+        cl = bl;
+        bl = t;
+
+        let t = ar
+            .wrapping_add(f(4 - round, br, cr, dr))
+            .wrapping_add(m[RR[j]])
+            .wrapping_add(KR[round])
+            .rotate_left(SR[j])
+            .wrapping_add(er);
+        ar = er;
+        er = dr;
+        dr = cr.rotate_left(10);
+        cr = br;
+        br = t;
+    }
+
+    let t = h[1].wrapping_add(cl).wrapping_add(dr);
+    h[1] = h[2].wrapping_add(dl).wrapping_add(er);
+    h[2] = h[3].wrapping_add(el).wrapping_add(ar);
+    h[3] = h[4].wrapping_add(al).wrapping_add(br);
+    h[4] = h[0].wrapping_add(bl).wrapping_add(cr);
+    h[0] = t;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    #[test]
+    fn reference_vectors() {
+        // From the RIPEMD-160 page of Bosselaers.
+        let vectors: [(&str, &str); 8] = [
+            ("", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+            ("a", "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe"),
+            ("abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            ("message digest", "5d0689ef49d2fae572b881b123a85ffa21595f36"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "f71c27109c692c1b56bbdceb5b9d2865b3708dbc",
+            ),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "b0e20b6e3116640286ed3a87a5713079b21f5189",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+            ),
+        ];
+
+        for &(message, digest) in vectors.iter() {
+            assert_eq!(hex(digest), Ripemd160::digest(message.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn million_a() {
+        let chunk = [b'a'; 1000];
+        let mut hasher = Ripemd160::new();
+        for _ in 0..1000 {
+            hasher.update(&chunk);
+        }
+        assert_eq!(
+            hex("52783243c1697bdbe16d37f97f68f08325dc1528"),
+            hasher.finalize()
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        // The 56-byte reference message leaves no room for the length in
+        // its first block; feed it one byte at a time.
+        let mut hasher = Ripemd160::new();
+        for b in b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".iter() {
+            hasher.update(&[*b]);
+        }
+        assert_eq!(
+            hex("12a053384a9c0c88e405a06c27dcf49ada62eb2b"),
+            hasher.finalize()
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let mut hasher = Ripemd160::new();
+        hasher.update(b"garbage");
+        hasher.reset();
+        hasher.update(b"abc");
+        assert_eq!(
+            hex("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            hasher.finalize()
+        );
+    }
+}
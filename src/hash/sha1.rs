@@ -0,0 +1,171 @@
+//! # SHA-1
+//!
+//! `sha1` implements the SHA-1 hash function (FIPS 180-4).
+//! SHA-1 is broken: a collision was published in 2017 (SHAttered, see
+//! `hash::collisions`), and chosen-prefix collisions are within reach of
+//! academic budgets.
+//! It is only provided to verify checksums of legacy data, where no
+//! attacker chose the data.
+
+use super::{BlockHasher, Hasher, MdBuffer, MD_BLOCK_SIZE_BYTES};
+
+/// The size of a block (in bytes).
+const BLOCK_SIZE_BYTES: usize = MD_BLOCK_SIZE_BYTES;
+
+/// The initial value.
+const IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// The round constants, one for each 20 steps.
+const K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+/// A SHA-1 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha1 {
+    h: [u32; 5],
+    buffer: MdBuffer,
+}
+
+impl Sha1 {
+    /// new creates a SHA-1 hasher.
+    pub fn new() -> Sha1 {
+        Sha1 {
+            h: IV,
+            buffer: MdBuffer::new(),
+        }
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Sha1 {
+        Sha1::new()
+    }
+}
+
+impl Hasher for Sha1 {
+    const OUTPUT_SIZE_BYTES: usize = 20;
+
+    fn update(&mut self, data: &[u8]) {
+        let h = &mut self.h;
+        self.buffer.update(data, |block| compress(h, block));
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        let h = &mut self.h;
+        self.buffer
+            .finalize(u64::to_be_bytes, |block| compress(h, block));
+
+        self.h
+            .iter()
+            .flat_map(|x| x.to_be_bytes().to_vec())
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        *self = Sha1::new();
+    }
+}
+
+impl BlockHasher for Sha1 {
+    const BLOCK_SIZE_BYTES: usize = BLOCK_SIZE_BYTES;
+}
+
+/// compress processes a 64-byte block with the SHA-1 compression function.
+fn compress(h: &mut [u32; 5], block: &[u8; BLOCK_SIZE_BYTES]) {
+    let mut w = [0u32; 80];
+    for t in 0..16 {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&block[4 * t..4 * (t + 1)]);
+        w[t] = u32::from_be_bytes(word);
+    }
+    for t in 16..80 {
+        // This rotation is the only difference with SHA-0.
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *h;
+    for t in 0..80 {
+        let f = match t / 20 {
+            0 => (b & c) | (!b & d),
+            2 => (b & c) | (b & d) | (c & d),
+            _ => b ^ c ^ d,
+        };
+
+        let tmp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(K[t / 20])
+            .wrapping_add(w[t]);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = tmp;
+    }
+
+    for (x, y) in h.iter_mut().zip([a, b, c, d, e].iter()) {
+        *x = x.wrapping_add(*y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    #[test]
+    fn fips180_vectors() {
+        assert_eq!(
+            hex("da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            Sha1::digest(b"")
+        );
+        assert_eq!(
+            hex("a9993e364706816aba3e25717850c26c9cd0d89d"),
+            Sha1::digest(b"abc")
+        );
+        assert_eq!(
+            hex("84983e441c3bd26ebaae4aa1f95129e5e54670f1"),
+            Sha1::digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")
+        );
+    }
+
+    #[test]
+    fn million_a() {
+        let chunk = [b'a'; 1000];
+        let mut hasher = Sha1::new();
+        for _ in 0..1000 {
+            hasher.update(&chunk);
+        }
+        assert_eq!(
+            hex("34aa973cd4c4daa4f61eeb2bdbad27316534016f"),
+            hasher.finalize()
+        );
+    }
+
+    #[test]
+    fn cavp_short_messages() {
+        // Entries of the NIST CAVP SHA1ShortMsg.rsp file.
+        let vectors: [(&str, &str); 4] = [
+            ("36", "c1dfd96eea8cc2b62785275bca38ac261256e278"),
+            ("195a", "0a1c2d555bbe431ad6288af5a54f93e0449c9232"),
+            ("df4bd2", "bf36ed5d74727dfd5d7854ec6b1d49468d8ee8aa"),
+            ("549e959e", "b78bae6d14338ffccfd5d5b5674a275f6ef9c717"),
+        ];
+
+        for &(message, digest) in vectors.iter() {
+            assert_eq!(hex(digest), Sha1::digest(&hex(message)));
+        }
+    }
+
+    #[test]
+    fn reset() {
+        let mut hasher = Sha1::new();
+        hasher.update(b"garbage");
+        hasher.reset();
+        hasher.update(b"abc");
+        assert_eq!(
+            hex("a9993e364706816aba3e25717850c26c9cd0d89d"),
+            hasher.finalize()
+        );
+    }
+}
@@ -0,0 +1,855 @@
+//! # SHA-2
+//!
+//! `sha2` implements the SHA-2 family of hash functions, as specified in
+//! FIPS 180-4: SHA-224, SHA-256, SHA-384, SHA-512 and SHA-512/256.
+//! SHA-224 and SHA-256 share a compression function on 32-bit words, and
+//! the other variants share one on 64-bit words: the variants of each
+//! family only differ in their initial values and in how much of the final
+//! state is output.
+
+use super::{BlockHasher, Hasher};
+
+/// The round constants of the 32-bit compression function: the first 32
+/// bits of the fractional parts of the cube roots of the first 64 primes.
+const K32: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The round constants of the 64-bit compression function: the first 64
+/// bits of the fractional parts of the cube roots of the first 80 primes.
+const K64: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// The initial value of SHA-224.
+const SHA224_IV: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
+/// The initial value of SHA-256, also used by BLAKE2s.
+pub(crate) const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The initial value of SHA-384.
+const SHA384_IV: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+/// The initial value of SHA-512, also used by BLAKE2b.
+pub(crate) const SHA512_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// The initial value of SHA-512/256, generated from the SHA-512 initial
+/// value as described in section 5.3.6 of FIPS 180-4.
+const SHA512_256_IV: [u64; 8] = [
+    0x22312194fc2bf72c,
+    0x9f555fa3c84c64c2,
+    0x2393b86b6f53b151,
+    0x963877195940eabd,
+    0x96283ee2a88effe3,
+    0xbe5e1e2553863992,
+    0x2b0199fc2c85b8aa,
+    0x0eb72ddc81c52ca2,
+];
+
+/// The size of a block of the 32-bit compression function (in bytes).
+const BLOCK32_SIZE_BYTES: usize = 64;
+/// The size of a block of the 64-bit compression function (in bytes).
+const BLOCK64_SIZE_BYTES: usize = 128;
+
+/// The state of a hash based on the 32-bit compression function.
+#[derive(Debug, Clone)]
+struct Engine32 {
+    iv: [u32; 8],
+    h: [u32; 8],
+    buffer: [u8; BLOCK32_SIZE_BYTES],
+    buffer_len: usize,
+    length: u64,
+}
+
+impl Engine32 {
+    /// new creates an engine starting from the given initial value.
+    fn new(iv: [u32; 8]) -> Engine32 {
+        Engine32 {
+            iv,
+            h: iv,
+            buffer: [0u8; BLOCK32_SIZE_BYTES],
+            buffer_len: 0,
+            length: 0,
+        }
+    }
+
+    /// update feeds more data to the engine.
+    fn update(&mut self, data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+        for b in data {
+            self.buffer[self.buffer_len] = *b;
+            self.buffer_len += 1;
+            if self.buffer_len == BLOCK32_SIZE_BYTES {
+                compress32(&mut self.h, &self.buffer);
+                self.buffer_len = 0;
+            }
+        }
+    }
+
+    /// finalize pads the data and returns the whole final state.
+    fn finalize(mut self) -> [u8; 32] {
+        // The padding is a 1 bit, zeros, and the length in bits on 64 bits,
+        // so that the padded data is a whole number of blocks.
+        let bit_length = self.length.wrapping_mul(8);
+        let zeros = (BLOCK32_SIZE_BYTES + 55 - self.buffer_len) % BLOCK32_SIZE_BYTES;
+        let mut padding = vec![0x80];
+        padding.extend(vec![0u8; zeros]);
+        padding.extend(&bit_length.to_be_bytes());
+        self.update(&padding);
+
+        let mut digest = [0u8; 32];
+        for i in 0..8 {
+            digest[4 * i..4 * (i + 1)].copy_from_slice(&self.h[i].to_be_bytes());
+        }
+
+        digest
+    }
+
+    /// reset discards the data fed so far.
+    fn reset(&mut self) {
+        *self = Engine32::new(self.iv);
+    }
+}
+
+/// The state of a hash based on the 64-bit compression function.
+#[derive(Debug, Clone)]
+struct Engine64 {
+    iv: [u64; 8],
+    h: [u64; 8],
+    buffer: [u8; BLOCK64_SIZE_BYTES],
+    buffer_len: usize,
+    length: u128,
+}
+
+impl Engine64 {
+    /// new creates an engine starting from the given initial value.
+    fn new(iv: [u64; 8]) -> Engine64 {
+        Engine64 {
+            iv,
+            h: iv,
+            buffer: [0u8; BLOCK64_SIZE_BYTES],
+            buffer_len: 0,
+            length: 0,
+        }
+    }
+
+    /// update feeds more data to the engine.
+    fn update(&mut self, data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u128);
+        for b in data {
+            self.buffer[self.buffer_len] = *b;
+            self.buffer_len += 1;
+            if self.buffer_len == BLOCK64_SIZE_BYTES {
+                compress64(&mut self.h, &self.buffer);
+                self.buffer_len = 0;
+            }
+        }
+    }
+
+    /// finalize pads the data and returns the whole final state.
+    fn finalize(mut self) -> [u8; 64] {
+        // Same padding as the 32-bit engine, with a 128-bit length.
+        let bit_length = self.length.wrapping_mul(8);
+        let zeros = (BLOCK64_SIZE_BYTES + 111 - self.buffer_len) % BLOCK64_SIZE_BYTES;
+        let mut padding = vec![0x80];
+        padding.extend(vec![0u8; zeros]);
+        padding.extend(&bit_length.to_be_bytes());
+        self.update(&padding);
+
+        let mut digest = [0u8; 64];
+        for i in 0..8 {
+            digest[8 * i..8 * (i + 1)].copy_from_slice(&self.h[i].to_be_bytes());
+        }
+
+        digest
+    }
+
+    /// reset discards the data fed so far.
+    fn reset(&mut self) {
+        *self = Engine64::new(self.iv);
+    }
+}
+
+/// compress32 processes a 64-byte block with the SHA-256 compression
+/// function.
+fn compress32(h: &mut [u32; 8], block: &[u8; BLOCK32_SIZE_BYTES]) {
+    let mut w = [0u32; 64];
+    for t in 0..16 {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&block[4 * t..4 * (t + 1)]);
+        w[t] = u32::from_be_bytes(word);
+    }
+    for t in 16..64 {
+        let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+        let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+        w[t] = w[t - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[t - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = *h;
+    for t in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = hh
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K32[t])
+            .wrapping_add(w[t]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        hh = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (x, y) in h.iter_mut().zip([a, b, c, d, e, f, g, hh].iter()) {
+        *x = x.wrapping_add(*y);
+    }
+}
+
+/// compress64 processes a 128-byte block with the SHA-512 compression
+/// function.
+fn compress64(h: &mut [u64; 8], block: &[u8; BLOCK64_SIZE_BYTES]) {
+    let mut w = [0u64; 80];
+    for t in 0..16 {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&block[8 * t..8 * (t + 1)]);
+        w[t] = u64::from_be_bytes(word);
+    }
+    for t in 16..80 {
+        let s0 = w[t - 15].rotate_right(1) ^ w[t - 15].rotate_right(8) ^ (w[t - 15] >> 7);
+        let s1 = w[t - 2].rotate_right(19) ^ w[t - 2].rotate_right(61) ^ (w[t - 2] >> 6);
+        w[t] = w[t - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[t - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = *h;
+    for t in 0..80 {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = hh
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K64[t])
+            .wrapping_add(w[t]);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        hh = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (x, y) in h.iter_mut().zip([a, b, c, d, e, f, g, hh].iter()) {
+        *x = x.wrapping_add(*y);
+    }
+}
+
+/// A SHA-224 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha224 {
+    engine: Engine32,
+}
+
+impl Sha224 {
+    /// new creates a SHA-224 hasher.
+    pub fn new() -> Sha224 {
+        Sha224 {
+            engine: Engine32::new(SHA224_IV),
+        }
+    }
+}
+
+impl Default for Sha224 {
+    fn default() -> Sha224 {
+        Sha224::new()
+    }
+}
+
+impl Hasher for Sha224 {
+    const OUTPUT_SIZE_BYTES: usize = 28;
+
+    fn update(&mut self, data: &[u8]) {
+        self.engine.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.engine.finalize()[..Self::OUTPUT_SIZE_BYTES].to_vec()
+    }
+
+    fn reset(&mut self) {
+        self.engine.reset();
+    }
+}
+
+impl BlockHasher for Sha224 {
+    const BLOCK_SIZE_BYTES: usize = BLOCK32_SIZE_BYTES;
+}
+
+/// A SHA-256 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    engine: Engine32,
+}
+
+impl Sha256 {
+    /// new creates a SHA-256 hasher.
+    pub fn new() -> Sha256 {
+        Sha256 {
+            engine: Engine32::new(SHA256_IV),
+        }
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Sha256 {
+        Sha256::new()
+    }
+}
+
+impl Hasher for Sha256 {
+    const OUTPUT_SIZE_BYTES: usize = 32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.engine.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.engine.finalize().to_vec()
+    }
+
+    fn reset(&mut self) {
+        self.engine.reset();
+    }
+}
+
+impl BlockHasher for Sha256 {
+    const BLOCK_SIZE_BYTES: usize = BLOCK32_SIZE_BYTES;
+}
+
+/// A SHA-384 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha384 {
+    engine: Engine64,
+}
+
+impl Sha384 {
+    /// new creates a SHA-384 hasher.
+    pub fn new() -> Sha384 {
+        Sha384 {
+            engine: Engine64::new(SHA384_IV),
+        }
+    }
+}
+
+impl Default for Sha384 {
+    fn default() -> Sha384 {
+        Sha384::new()
+    }
+}
+
+impl Hasher for Sha384 {
+    const OUTPUT_SIZE_BYTES: usize = 48;
+
+    fn update(&mut self, data: &[u8]) {
+        self.engine.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.engine.finalize()[..Self::OUTPUT_SIZE_BYTES].to_vec()
+    }
+
+    fn reset(&mut self) {
+        self.engine.reset();
+    }
+}
+
+impl BlockHasher for Sha384 {
+    const BLOCK_SIZE_BYTES: usize = BLOCK64_SIZE_BYTES;
+}
+
+/// A SHA-512 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha512 {
+    engine: Engine64,
+}
+
+impl Sha512 {
+    /// new creates a SHA-512 hasher.
+    pub fn new() -> Sha512 {
+        Sha512 {
+            engine: Engine64::new(SHA512_IV),
+        }
+    }
+}
+
+impl Default for Sha512 {
+    fn default() -> Sha512 {
+        Sha512::new()
+    }
+}
+
+impl Hasher for Sha512 {
+    const OUTPUT_SIZE_BYTES: usize = 64;
+
+    fn update(&mut self, data: &[u8]) {
+        self.engine.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.engine.finalize().to_vec()
+    }
+
+    fn reset(&mut self) {
+        self.engine.reset();
+    }
+}
+
+impl BlockHasher for Sha512 {
+    const BLOCK_SIZE_BYTES: usize = BLOCK64_SIZE_BYTES;
+}
+
+/// A SHA-512/256 hasher.
+/// It is faster than SHA-256 on 64-bit platforms, and since its output is
+/// truncated it is not subject to length extension attacks.
+#[derive(Debug, Clone)]
+pub struct Sha512_256 {
+    engine: Engine64,
+}
+
+impl Sha512_256 {
+    /// new creates a SHA-512/256 hasher.
+    pub fn new() -> Sha512_256 {
+        Sha512_256 {
+            engine: Engine64::new(SHA512_256_IV),
+        }
+    }
+}
+
+impl Default for Sha512_256 {
+    fn default() -> Sha512_256 {
+        Sha512_256::new()
+    }
+}
+
+impl Hasher for Sha512_256 {
+    const OUTPUT_SIZE_BYTES: usize = 32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.engine.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.engine.finalize()[..Self::OUTPUT_SIZE_BYTES].to_vec()
+    }
+
+    fn reset(&mut self) {
+        self.engine.reset();
+    }
+}
+
+impl BlockHasher for Sha512_256 {
+    const BLOCK_SIZE_BYTES: usize = BLOCK64_SIZE_BYTES;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    const ABC: &[u8] = b"abc";
+    const MSG_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const MSG_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    /// check_examples tests the FIPS 180-4 examples: the empty message,
+    /// "abc", and the 448-bit and 896-bit messages.
+    fn check_examples<H: Hasher + Default>(expected: [&str; 4]) {
+        for (message, digest) in [b"" as &[u8], ABC, MSG_448, MSG_896]
+            .iter()
+            .zip(expected.iter())
+        {
+            assert_eq!(hex(digest), H::digest(message));
+        }
+    }
+
+    /// check_million_a tests the one million "a" message, fed in uneven
+    /// pieces.
+    fn check_million_a<H: Hasher + Default>(expected: &str) {
+        let chunk = [b'a'; 1000];
+        let mut hasher = H::default();
+        let mut remaining = 1_000_000;
+        let mut size = 1;
+        while remaining > 0 {
+            let n = size.min(remaining);
+            hasher.update(&chunk[..n]);
+            remaining -= n;
+            size = size % 977 + 13;
+        }
+
+        assert_eq!(hex(expected), hasher.finalize());
+    }
+
+    /// check_messages tests (message, digest) pairs taken from the CAVP
+    /// SHA*ShortMsg.rsp and SHA*LongMsg.rsp files.
+    fn check_messages<H: Hasher + Default>(vectors: &[(&str, &str)]) {
+        for (message, digest) in vectors.iter() {
+            assert_eq!(hex(digest), H::digest(&hex(message)));
+        }
+    }
+
+    /// check_monte_carlo runs the CAVP Monte Carlo test procedure from the
+    /// seed of a SHA*Monte.rsp file, and checks its first checkpoints
+    /// (COUNT = 0, 1, ...): each checkpoint chains 1000 hashes of the
+    /// previous three digests.
+    fn check_monte_carlo<H: Hasher + Default>(seed: &str, expected: &[&str]) {
+        let mut seed = hex(seed);
+        for checkpoint in expected {
+            let mut md = [seed.clone(), seed.clone(), seed];
+            for _ in 0..1000 {
+                let mut hasher = H::default();
+                for d in md.iter() {
+                    hasher.update(d);
+                }
+                md = [md[1].clone(), md[2].clone(), hasher.finalize()];
+            }
+
+            assert_eq!(hex(checkpoint), md[2]);
+            seed = md[2].clone();
+        }
+    }
+
+    #[test]
+    fn sha224() {
+        check_examples::<Sha224>([
+            "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525",
+            "c97ca9a559850ce97a04a96def6d99a9e0e0e2ab14e6b8df265fc0b3",
+        ]);
+        check_million_a::<Sha224>("20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67");
+        check_messages::<Sha224>(&[
+            (
+                "84",
+                "3cd36921df5d6963e73739cf4d20211e2d8877c19cff087ade9d0e3a",
+            ),
+            (
+                "5c7b",
+                "daff9bce685eb831f97fc1225b03c275a6c112e2d6e76f5faf7a36e6",
+            ),
+        ]);
+        check_monte_carlo::<Sha224>(
+            "ed2b70d575d9d0b4196ae84a03eed940057ea89cdd729b95b7d4e6a5",
+            &[
+                "cd94d7da13c030208b2d0d78fcfe9ea22fa8906df66aa9a1f42afa70",
+                "555846e884633639565d5e0c01dd93ba58edb01ee18e68ccca28f7b8",
+                "44d5f4a179b33231f24cc209ed2542ddb931391f2a2d604f80ed460b",
+            ],
+        );
+    }
+
+    #[test]
+    fn sha256() {
+        check_examples::<Sha256>([
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+        ]);
+        check_million_a::<Sha256>(
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+        );
+        check_messages::<Sha256>(&[
+            ("d3", "28969cdfa74a12c82f3bad960b0b000aca2ac329deea5c2328ebc6f2ba9802c1"),
+            ("11af", "5ca7133fa735326081558ac312c620eeca9970d1e70a4b95533d956f072d1f98"),
+            ("b4190e", "dff2e73091f6c05e528896c4c831b9448653dc2ff043528f6769437bc7b975c2"),
+            ("74ba2521", "b16aa56be3880d18cd41e68384cf1ec8c17680c45a02b1575dc1518923ae8b0e"),
+            (
+                "451101250ec6f26652249d59dc974b7361d571a8101cdfd36aba3b5854d3ae086b5fdd4597721b66e3c0dc5d8c606d9657d0e323283a5217d1f53f2f284f57b85c8a61ac8924711f895c5ed90ef17745ed2d728abd22a5f7a13479a462d71b56c19a74a40b655c58edfe0a188ad2cf46cbf30524f65d423c837dd1ff2bf462ac4198007345bb44dbb7b1c861298cdf61982a833afc728fae1eda2f87aa2c9480858bec",
+                "3c593aa539fdcdae516cdf2f15000f6634185c88f505b39775fb9ab137a10aa2",
+            ),
+        ]);
+        check_monte_carlo::<Sha256>(
+            "6d1e72ad03ddeb5de891e572e2396f8da015d899ef0e79503152d6010a3fe691",
+            &[
+                "e93c330ae5447738c8aa85d71a6c80f2a58381d05872d26bdd39f1fcd4f2b788",
+                "2e78f8c8772ea7c9331d41ed3f9cdf27d8f514a99342ee766ee3b8b0d0b121c0",
+                "d6a23dff1b7f2eddc1a212f8a218397523a799b07386a30692fd6fe9d2bf0944",
+            ],
+        );
+    }
+
+    #[test]
+    fn sha384() {
+        check_examples::<Sha384>([
+            "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+            "3391fdddfc8dc7393707a65b1b4709397cf8b1d162af05abfe8f450de5f36bc6b0455a8520bc4e6f5fe95b1fe3c8452b",
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039",
+        ]);
+        check_million_a::<Sha384>("9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985");
+        check_messages::<Sha384>(&[
+            ("c5", "b52b72da75d0666379e20f9b4a79c33a329a01f06a2fb7865c9062a28c1de860ba432edfd86b4cb1cb8a75b46076e3b1"),
+            ("6ece", "53d4773da50d8be4145d8f3a7098ff3691a554a29ae6f652cc7121eb8bc96fd2210e06ae2fa2a36c4b3b3497341e70f0"),
+            (
+                "62c6a169b9be02b3d7b471a964fc0bcc72b480d26aecb2ed460b7f50016ddaf04c51218783f3aadfdff5a04ded030d7b3fb7376b61ba30b90e2da921a4470740d63fb99fa16cc8ed81abaf8ce4016e50df81da832070372c24a80890aa3a26fa675710b8fb718266249d496f313c55d0bada101f8f56eeccee4345a8f98f60a36662cfda794900d12f9414fcbdfdeb85388a814996b47e24d5c8086e7a8edcc53d299d0d033e6bb60c58b83d6e8b57f6c258d6081dd10eb942fdf8ec157ec3e75371235a8196eb9d22b1de3a2d30c2abbe0db7650cf6c7159bacbe29b3a93c92100508",
+                "0730e184e7795575569f87030260bb8e54498e0e5d096b18285e988d245b6f3486d1f2447d5f85bcbe59d5689fc49425",
+            ),
+        ]);
+        check_monte_carlo::<Sha384>(
+            "edff07255c71b54a9beae52cdfa083569a08be89949cbba73ddc8acf429359ca5e5be7a673633ca0d9709848f522a9df",
+            &[
+                "e81b86c49a38feddfd185f71ca7da6732a053ed4a2640d52d27f53f9f76422650b0e93645301ac99f8295d6f820f1035",
+                "1d6bd21713bffd50946a10c39a7742d740e8f271f0c8f643d4c95375094fd9bf29d89ee61a76053f22e44a4b058a64ed",
+                "425167b66ae965bd7d68515b54ebfa16f33d2bdb2147a4eac515a75224cd19cea564d692017d2a1c41c1a3f68bb5a209",
+            ],
+        );
+    }
+
+    #[test]
+    fn sha512() {
+        check_examples::<Sha512>([
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c33596fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445",
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+        ]);
+        check_million_a::<Sha512>("e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b");
+        check_messages::<Sha512>(&[
+            ("21", "3831a6a6155e509dee59a7f451eb35324d8f8f2df6e3708894740f98fdee23889f4de5adb0c5010dfb555cda77c8ab5dc902094c52de3278f35a75ebc25f093a"),
+            ("9083", "55586ebba48768aeb323655ab6f4298fc9f670964fc2e5f2731e34dfa4b0c09e6e1e12e3d7286b3145c61c2047fb1a2a1297f36da64160b31fa4c8c2cddd2fb4"),
+            (
+                "4f05600950664d5190a2ebc29c9edb89c20079a4d3e6bc3b27d75e34e2fa3d02768502bd69790078598d5fcf3d6779bfed1284bbe5ad72fb456015181d9587d6e864c940564eaafb4f2fead4346ea09b6877d9340f6b82eb1515880872213da3ad88feba9f4f13817a71d6f90a1a17c43a15c038d988b5b29edffe2d6a062813cedbe852cde302b3e33b696846d2a8e36bd680efcc6cd3f9e9a4c1ae8cac10cc5244d131677140399176ed46700019a004a163806f7fa467fc4e17b4617bbd7641aaff7ff56396ba8c08a8be100b33a20b5daf134a2aefa5e1c3496770dcf6baa4f7bb",
+                "a9db490c708cc72548d78635aa7da79bb253f945d710e5cb677a474efc7c65a2aab45bc7ca1113c8ce0f3c32e1399de9c459535e8816521ab714b2a6cd200525",
+            ),
+        ]);
+        check_monte_carlo::<Sha512>(
+            "5c337de5caf35d18ed90b5cddfce001ca1b8ee8602f367e7c24ccca6f893802fb1aca7a3dae32dcd60800a59959bc540d63237876b799229ae71a2526fbc52cd",
+            &[
+                "ada69add0071b794463c8806a177326735fa624b68ab7bcab2388b9276c036e4eaaff87333e83c81c0bca0359d4aeebcbcfd314c0630e0c2af68c1fb19cc470e",
+                "ef219b37c24ae507a2b2b26d1add51b31fb5327eb8c3b19b882fe38049433dbeccd63b3d5b99ba2398920bcefb8aca98cd28a1ee5d2aaf139ce58a15d71b06b4",
+                "c3d5087a62db0e5c6f5755c417f69037308cbce0e54519ea5be8171496cc6d18023ba15768153cfd74c7e7dc103227e9eed4b0f82233362b2a7b1a2cbcda9daf",
+            ],
+        );
+    }
+
+    #[test]
+    fn sha512_256() {
+        check_examples::<Sha512_256>([
+            "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a",
+            "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+            "bde8e1f9f19bb9fd3406c90ec6bc47bd36d8ada9f11880dbc8a22a7078b6a461",
+            "3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a",
+        ]);
+        check_million_a::<Sha512_256>(
+            "9a59a052930187a97038cae692f30708aa6491923ef5194394dc68d56c74fb21",
+        );
+        check_messages::<Sha512_256>(&[(
+            "fa",
+            "c4ef36923c64e51e875720e550298a5ab8a3f2f875b1e1a4c9b95babf7344fef",
+        )]);
+        check_monte_carlo::<Sha512_256>(
+            "f41ece2613e4573915696b5adcd51ca328be3bf566a9ca99c9ceb0279c1cb0a7",
+            &[
+                "b1d97a6536896aa01098fb2b9e15d8692621c84077051fc1f70a8a48baa6dfaf",
+                "a008d2c5adce31a95b30397ac691d8606c6769a47b801441ba3afb7f727c8a9c",
+                "8eb896cb2b309db019121eb72564b89c1a59f74d4e2f2f6773c87b98c1997d77",
+            ],
+        );
+    }
+
+    #[test]
+    fn padding_boundaries() {
+        // Messages of 0x5a bytes around the block size, where the length
+        // may not fit in the last block; the digests come from Python's
+        // hashlib.
+        let sha256 = [
+            (
+                55,
+                "5f25f149aa92e3e13093aed8216072fae623f35e26ca605b6cce17e04b7ccf44",
+            ),
+            (
+                56,
+                "301c69927f1603720c9f847b7e5e3bef77a7b9f75344490fe9039f13c36b842a",
+            ),
+            (
+                63,
+                "939765b120205cbedae2ed31256b1967c38b6bdd9b0220535224cbc0b906d333",
+            ),
+            (
+                64,
+                "cc7321cce5e4409bd8077d58422e1214969059bbd40b4eeb0de0a642f40f7282",
+            ),
+            (
+                65,
+                "b8de0db62b6c87db61345504a8038bf973d987e8d2111abd8beb407c0bf3d9db",
+            ),
+        ];
+        let sha512 = [
+            (111, "421318daeb8461d426c4e5a8be95e8d3594116cafb9c28db68e22591c5af0b68962b99dcf2accc1ce4b2f4421287282924c0867d47b542a8923751a0e8cba847"),
+            (112, "efa85a2ad32eee7cd93fe9ef92a7f260e5e703f98cd0c02bfe9a0d4d12dfd0c411f46ef550e6dc55833cbf65f1129765c8073acc6c6255e5c74d703604bb1d0e"),
+            (127, "84d778b759460c828546471b242a4d4ec9ab273684c46c9e3d0513b35e0105e17a344b3ec559dab4c2e6fdc57c70e8fc10d4f688e44be16959a5128be52fabb1"),
+            (128, "ed24df3079846053b9f164968155d8c75c09048e7369477a8ed289aabc79abb00ebbc550b108a2d116743862c0f334cc067ac8baa9b7fde6bfb393e2de92057e"),
+            (129, "76fe07b95acb0e5327f9b4b4f9e0031aa55735b29d4138a797b6209705d0678c247e598f5ec1f82c7659a1e0d3d5d26687f9c2e3def69eb871d0a99d4c769e14"),
+        ];
+
+        let message = [0x5au8; 300];
+        for (n, digest) in sha256.iter() {
+            assert_eq!(hex(digest), Sha256::digest(&message[..*n]));
+        }
+        for (n, digest) in sha512.iter() {
+            assert_eq!(hex(digest), Sha512::digest(&message[..*n]));
+        }
+
+        // Messages split in two pieces give the same digests.
+        for n in 50..140 {
+            let mut hasher = Sha256::new();
+            hasher.update(&message[..n / 2]);
+            hasher.update(&message[n / 2..n]);
+            assert_eq!(Sha256::digest(&message[..n]), hasher.finalize());
+
+            let mut hasher = Sha512::new();
+            hasher.update(&message[..2 * n / 3]);
+            hasher.update(&message[2 * n / 3..2 * n]);
+            assert_eq!(Sha512::digest(&message[..2 * n]), hasher.finalize());
+        }
+    }
+
+    #[test]
+    fn reset() {
+        let mut hasher = Sha384::new();
+        hasher.update(b"garbage");
+        hasher.reset();
+        hasher.update(ABC);
+        assert_eq!(Sha384::digest(ABC), hasher.finalize());
+
+        let mut hasher = Sha224::new();
+        hasher.update(b"garbage");
+        hasher.reset();
+        assert_eq!(Sha224::digest(b""), hasher.finalize());
+    }
+}
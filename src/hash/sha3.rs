@@ -0,0 +1,534 @@
+//! # SHA-3
+//!
+//! `sha3` implements the SHA-3 hash functions and the SHAKE extendable
+//! output functions (FIPS 202), as well as cSHAKE (NIST SP 800-185).
+//! They are all Keccak sponges: the hash functions pad their input with
+//! a different domain than the extendable output functions, whose output
+//! can be read in any length.
+//! Unlike SHA-2, SHA-3 is not subject to length extension attacks.
+
+use super::keccak::{Sponge, STATE_SIZE_BYTES};
+use super::{BlockHasher, Hasher};
+
+/// The domain byte of the SHA-3 hash functions.
+const SHA3_DOMAIN: u8 = 0x06;
+/// The domain byte of SHAKE.
+const SHAKE_DOMAIN: u8 = 0x1f;
+/// The domain byte of cSHAKE.
+const CSHAKE_DOMAIN: u8 = 0x04;
+
+/// rate returns the rate of the sponge for a given capacity.
+const fn rate(capacity_bytes: usize) -> usize {
+    STATE_SIZE_BYTES - capacity_bytes
+}
+
+/// A SHA3-224 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha3_224 {
+    sponge: Sponge,
+}
+
+impl Sha3_224 {
+    /// new creates a SHA3-224 hasher.
+    pub fn new() -> Sha3_224 {
+        Sha3_224 {
+            sponge: Sponge::new(rate(2 * Self::OUTPUT_SIZE_BYTES)),
+        }
+    }
+}
+
+impl Default for Sha3_224 {
+    fn default() -> Sha3_224 {
+        Sha3_224::new()
+    }
+}
+
+impl Hasher for Sha3_224 {
+    const OUTPUT_SIZE_BYTES: usize = 28;
+
+    fn update(&mut self, data: &[u8]) {
+        self.sponge.absorb(data);
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        self.sponge.pad(SHA3_DOMAIN);
+        let mut digest = vec![0u8; Self::OUTPUT_SIZE_BYTES];
+        self.sponge.squeeze(&mut digest);
+        digest
+    }
+
+    fn reset(&mut self) {
+        self.sponge.reset();
+    }
+}
+
+impl BlockHasher for Sha3_224 {
+    const BLOCK_SIZE_BYTES: usize = rate(2 * Self::OUTPUT_SIZE_BYTES);
+}
+
+/// A SHA3-256 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha3_256 {
+    sponge: Sponge,
+}
+
+impl Sha3_256 {
+    /// new creates a SHA3-256 hasher.
+    pub fn new() -> Sha3_256 {
+        Sha3_256 {
+            sponge: Sponge::new(rate(2 * Self::OUTPUT_SIZE_BYTES)),
+        }
+    }
+}
+
+impl Default for Sha3_256 {
+    fn default() -> Sha3_256 {
+        Sha3_256::new()
+    }
+}
+
+impl Hasher for Sha3_256 {
+    const OUTPUT_SIZE_BYTES: usize = 32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.sponge.absorb(data);
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        self.sponge.pad(SHA3_DOMAIN);
+        let mut digest = vec![0u8; Self::OUTPUT_SIZE_BYTES];
+        self.sponge.squeeze(&mut digest);
+        digest
+    }
+
+    fn reset(&mut self) {
+        self.sponge.reset();
+    }
+}
+
+impl BlockHasher for Sha3_256 {
+    const BLOCK_SIZE_BYTES: usize = rate(2 * Self::OUTPUT_SIZE_BYTES);
+}
+
+/// A SHA3-384 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha3_384 {
+    sponge: Sponge,
+}
+
+impl Sha3_384 {
+    /// new creates a SHA3-384 hasher.
+    pub fn new() -> Sha3_384 {
+        Sha3_384 {
+            sponge: Sponge::new(rate(2 * Self::OUTPUT_SIZE_BYTES)),
+        }
+    }
+}
+
+impl Default for Sha3_384 {
+    fn default() -> Sha3_384 {
+        Sha3_384::new()
+    }
+}
+
+impl Hasher for Sha3_384 {
+    const OUTPUT_SIZE_BYTES: usize = 48;
+
+    fn update(&mut self, data: &[u8]) {
+        self.sponge.absorb(data);
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        self.sponge.pad(SHA3_DOMAIN);
+        let mut digest = vec![0u8; Self::OUTPUT_SIZE_BYTES];
+        self.sponge.squeeze(&mut digest);
+        digest
+    }
+
+    fn reset(&mut self) {
+        self.sponge.reset();
+    }
+}
+
+impl BlockHasher for Sha3_384 {
+    const BLOCK_SIZE_BYTES: usize = rate(2 * Self::OUTPUT_SIZE_BYTES);
+}
+
+/// A SHA3-512 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha3_512 {
+    sponge: Sponge,
+}
+
+impl Sha3_512 {
+    /// new creates a SHA3-512 hasher.
+    pub fn new() -> Sha3_512 {
+        Sha3_512 {
+            sponge: Sponge::new(rate(2 * Self::OUTPUT_SIZE_BYTES)),
+        }
+    }
+}
+
+impl Default for Sha3_512 {
+    fn default() -> Sha3_512 {
+        Sha3_512::new()
+    }
+}
+
+impl Hasher for Sha3_512 {
+    const OUTPUT_SIZE_BYTES: usize = 64;
+
+    fn update(&mut self, data: &[u8]) {
+        self.sponge.absorb(data);
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        self.sponge.pad(SHA3_DOMAIN);
+        let mut digest = vec![0u8; Self::OUTPUT_SIZE_BYTES];
+        self.sponge.squeeze(&mut digest);
+        digest
+    }
+
+    fn reset(&mut self) {
+        self.sponge.reset();
+    }
+}
+
+impl BlockHasher for Sha3_512 {
+    const BLOCK_SIZE_BYTES: usize = rate(2 * Self::OUTPUT_SIZE_BYTES);
+}
+
+/// The security strengths of the extendable output functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityStrength {
+    /// 128-bit security (SHAKE128, cSHAKE128, KMAC128).
+    Bits128,
+    /// 256-bit security (SHAKE256, cSHAKE256, KMAC256).
+    Bits256,
+}
+
+// Methods used by KMAC.
+impl SecurityStrength {
+    /// rate returns the rate of the sponge (in bytes).
+    pub(crate) fn rate(self) -> usize {
+        match self {
+            SecurityStrength::Bits128 => rate(32),
+            SecurityStrength::Bits256 => rate(64),
+        }
+    }
+}
+
+/// A SHAKE or cSHAKE extendable output function.
+/// Data is fed with `update`, and output is then read with `read`, in as
+/// many pieces as needed.
+#[derive(Debug, Clone)]
+pub struct Shake {
+    sponge: Sponge,
+    initial: Sponge,
+    domain: u8,
+}
+
+// Public methods.
+impl Shake {
+    /// new creates a SHAKE128 or SHAKE256 instance.
+    pub fn new(strength: SecurityStrength) -> Shake {
+        let sponge = Sponge::new(strength.rate());
+        Shake {
+            initial: sponge.clone(),
+            sponge,
+            domain: SHAKE_DOMAIN,
+        }
+    }
+
+    /// cshake creates a cSHAKE128 or cSHAKE256 instance.
+    /// The function name is reserved for functions defined by NIST (such as
+    /// KMAC), and applications pick a customization string so that their
+    /// outputs are unrelated to the outputs of other applications.
+    /// When both are empty, cSHAKE is SHAKE.
+    pub fn cshake(strength: SecurityStrength, function_name: &[u8], customization: &[u8]) -> Shake {
+        if function_name.is_empty() && customization.is_empty() {
+            return Shake::new(strength);
+        }
+
+        let mut prefix = encode_string(function_name);
+        prefix.extend(encode_string(customization));
+
+        let mut sponge = Sponge::new(strength.rate());
+        sponge.absorb(&bytepad(&prefix, strength.rate()));
+        Shake {
+            initial: sponge.clone(),
+            sponge,
+            domain: CSHAKE_DOMAIN,
+        }
+    }
+
+    /// update feeds more data to the function.
+    /// It panics if output has already been read.
+    pub fn update(&mut self, data: &[u8]) {
+        if self.sponge.is_squeezing() {
+            panic!("Cannot update after reading.");
+        }
+
+        self.sponge.absorb(data);
+    }
+
+    /// read fills the destination with output.
+    /// Successive calls return successive parts of the same output stream.
+    pub fn read(&mut self, dest: &mut [u8]) {
+        if !self.sponge.is_squeezing() {
+            self.sponge.pad(self.domain);
+        }
+
+        self.sponge.squeeze(dest);
+    }
+
+    /// reset discards the data fed so far, keeping the function name and
+    /// customization string.
+    pub fn reset(&mut self) {
+        self.sponge = self.initial.clone();
+    }
+}
+
+/// left_encode encodes an integer as its minimal big-endian bytes,
+/// preceded by their number (SP 800-185, section 2.3.1).
+pub(crate) fn left_encode(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = (bytes.iter().take_while(|b| **b == 0).count()).min(7);
+    let mut encoded = vec![(8 - skip) as u8];
+    encoded.extend(&bytes[skip..]);
+    encoded
+}
+
+/// right_encode encodes an integer as its minimal big-endian bytes,
+/// followed by their number (SP 800-185, section 2.3.1).
+pub(crate) fn right_encode(x: u64) -> Vec<u8> {
+    let mut encoded = left_encode(x);
+    let n = encoded.remove(0);
+    encoded.push(n);
+    encoded
+}
+
+/// encode_string prefixes a byte string with its length in bits, so that
+/// concatenated strings can be parsed unambiguously.
+pub(crate) fn encode_string(s: &[u8]) -> Vec<u8> {
+    let mut encoded = left_encode(8 * s.len() as u64);
+    encoded.extend(s);
+    encoded
+}
+
+/// bytepad prefixes a byte string with the encoded rate, and pads it with
+/// zeros to a multiple of the rate.
+pub(crate) fn bytepad(x: &[u8], rate: usize) -> Vec<u8> {
+    let mut padded = left_encode(rate as u64);
+    padded.extend(x);
+    while !padded.len().is_multiple_of(rate) {
+        padded.push(0);
+    }
+
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    const ABC: &[u8] = b"abc";
+    const MSG_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const MSG_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    /// check tests the NIST examples: the empty message, "abc", the 448-bit
+    /// and 896-bit messages, and one million "a" fed in pieces.
+    fn check<H: Hasher + Default>(expected: [&str; 5]) {
+        for (message, digest) in [b"" as &[u8], ABC, MSG_448, MSG_896]
+            .iter()
+            .zip(expected.iter())
+        {
+            assert_eq!(hex(digest), H::digest(message));
+        }
+
+        let mut hasher = H::default();
+        for _ in 0..1000 {
+            hasher.update(&[b'a'; 1000]);
+        }
+        assert_eq!(hex(expected[4]), hasher.finalize());
+    }
+
+    #[test]
+    fn sha3_224() {
+        check::<Sha3_224>([
+            "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7",
+            "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+            "8a24108b154ada21c9fd5574494479ba5c7e7ab76ef264ead0fcce33",
+            "543e6868e1666c1a643630df77367ae5a62a85070a51c14cbf665cbc",
+            "d69335b93325192e516a912e6d19a15cb51c6ed5c15243e7a7fd653c",
+        ]);
+    }
+
+    #[test]
+    fn sha3_256() {
+        check::<Sha3_256>([
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376",
+            "916f6061fe879741ca6469b43971dfdb28b1a32dc36cb3254e812be27aad1d18",
+            "5c8875ae474a3634ba4fd55ec85bffd661f32aca75c6d699d0cdcb6c115891c1",
+        ]);
+    }
+
+    #[test]
+    fn sha3_384() {
+        check::<Sha3_384>([
+            "0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61995e71bbee983a2ac3713831264adb47fb6bd1e058d5f004",
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25",
+            "991c665755eb3a4b6bbdfb75c78a492e8c56a22c5c4d7e429bfdbc32b9d4ad5aa04a1f076e62fea19eef51acd0657c22",
+            "79407d3b5916b59c3e30b09822974791c313fb9ecc849e406f23592d04f625dc8c709b98b43b3852b337216179aa7fc7",
+            "eee9e24d78c1855337983451df97c8ad9eedf256c6334f8e948d252d5e0e76847aa0774ddb90a842190d2c558b4b8340",
+        ]);
+    }
+
+    #[test]
+    fn sha3_512() {
+        check::<Sha3_512>([
+            "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a615b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26",
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+            "04a371e84ecfb5b8b77cb48610fca8182dd457ce6f326a0fd3d7ec2f1e91636dee691fbe0c985302ba1b0d8dc78c086346b533b49c030d99a27daf1139d6e75e",
+            "afebb2ef542e6579c50cad06d2e578f9f8dd6881d7dc824d26360feebf18a4fa73e3261122948efcfd492e74e82e2189ed0fb440d187f382270cb455f21dd185",
+            "3c3a876da14034ab60627c077bb98f7e120a2a5370212dffb3385a18d4f38859ed311d0a9d5141ce9cc5c66ee689b266a8aa18ace8282a0e0db596c90b0a7b87",
+        ]);
+    }
+
+    /// check_shake reads 512 bytes of output in uneven pieces, and compares
+    /// the first and last 32 bytes.
+    fn check_shake(mut xof: Shake, first: &str, last: &str) {
+        let mut output = Vec::new();
+        for size in [1, 31, 200, 280].iter() {
+            let mut piece = vec![0u8; *size];
+            xof.read(&mut piece);
+            output.extend(piece);
+        }
+
+        assert_eq!(hex(first), &output[..32]);
+        assert_eq!(hex(last), &output[480..]);
+    }
+
+    #[test]
+    fn shake128() {
+        check_shake(
+            Shake::new(SecurityStrength::Bits128),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26",
+            "43e41b45a653f2a5c4492c1add544512dda2529833462b71a41a45be97290b6f",
+        );
+
+        // The 1600-bit message of the NIST examples.
+        let mut xof = Shake::new(SecurityStrength::Bits128);
+        xof.update(&[0xa3; 200]);
+        check_shake(
+            xof,
+            "131ab8d2b594946b9c81333f9bb6e0ce75c3b93104fa3469d3917457385da037",
+            "44c9fb359fd56ac0a9a75a743cff6862f17d7259ab075216c0699511643b6439",
+        );
+    }
+
+    #[test]
+    fn shake256() {
+        check_shake(
+            Shake::new(SecurityStrength::Bits256),
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f",
+            "ab0bae316339894304e35877b0c28a9b1fd166c796b9cc258a064a8f57e27f2a",
+        );
+
+        let mut xof = Shake::new(SecurityStrength::Bits256);
+        xof.update(&[0xa3; 200]);
+        check_shake(
+            xof,
+            "cd8a920ed141aa0407a22d59288652e9d9f1a7ee0c1e7c1ca699424da84a904d",
+            "6a1a9d7846436e4dca5728b6f760eef0ca92bf0be5615e96959d767197a0beeb",
+        );
+    }
+
+    #[test]
+    fn cshake() {
+        // SP 800-185 cSHAKE samples #1 and #2.
+        let mut xof = Shake::cshake(SecurityStrength::Bits128, b"", b"Email Signature");
+        xof.update(&[0x00, 0x01, 0x02, 0x03]);
+        let mut output = [0u8; 32];
+        xof.read(&mut output);
+        assert_eq!(
+            hex("c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5"),
+            output
+        );
+
+        let message: Vec<u8> = (0..200).collect();
+        let mut xof = Shake::cshake(SecurityStrength::Bits128, b"", b"Email Signature");
+        xof.update(&message);
+        xof.read(&mut output);
+        assert_eq!(
+            hex("c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b"),
+            output
+        );
+    }
+
+    #[test]
+    fn cshake_without_names_is_shake() {
+        let mut cshake = Shake::cshake(SecurityStrength::Bits256, b"", b"");
+        let mut shake = Shake::new(SecurityStrength::Bits256);
+        cshake.update(ABC);
+        shake.update(ABC);
+
+        let mut a = [0u8; 64];
+        let mut b = [0u8; 64];
+        cshake.read(&mut a);
+        shake.read(&mut b);
+        assert_eq!(a.to_vec(), b.to_vec());
+    }
+
+    #[test]
+    fn encodings() {
+        assert_eq!(vec![1, 0], left_encode(0));
+        assert_eq!(vec![0, 1], right_encode(0));
+        assert_eq!(vec![2, 1, 0], left_encode(256));
+        assert_eq!(vec![1, 0, 2], right_encode(256));
+        let padded = bytepad(b"KMAC", 168);
+        assert_eq!(168, padded.len());
+        assert_eq!(
+            vec![1, 168, b'K', b'M', b'A', b'C', 0, 0],
+            padded[..8].to_vec()
+        );
+        assert_eq!(
+            vec![1, 8, b'K', b'M', b'A', b'C', 0, 0],
+            bytepad(b"KMAC", 8)
+        );
+        assert_eq!(vec![1, 32, b'K', b'M', b'A', b'C'], encode_string(b"KMAC"));
+    }
+
+    #[test]
+    fn reset() {
+        let mut xof = Shake::cshake(SecurityStrength::Bits128, b"", b"Email Signature");
+        xof.update(b"garbage");
+        let mut output = [0u8; 32];
+        xof.read(&mut output);
+
+        xof.reset();
+        xof.update(&[0x00, 0x01, 0x02, 0x03]);
+        xof.read(&mut output);
+        assert_eq!(
+            hex("c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5"),
+            output
+        );
+
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"garbage");
+        hasher.reset();
+        assert_eq!(Sha3_256::digest(b""), hasher.finalize());
+    }
+
+    #[test]
+    #[should_panic]
+    fn update_after_read() {
+        let mut xof = Shake::new(SecurityStrength::Bits128);
+        let mut output = [0u8; 8];
+        xof.read(&mut output);
+        xof.update(b"late");
+    }
+}
@@ -0,0 +1,310 @@
+//! # bcrypt
+//!
+//! `bcrypt` implements the bcrypt password hashing function.
+//! bcrypt keys Blowfish with the expensive key schedule (EksBlowfish) and
+//! uses it to encrypt a constant string.
+//! Hashes are encoded in the modular crypt format:
+//! `$2b$<cost>$<salt><hash>`, where the salt and hash use bcrypt's own
+//! base64 alphabet.
+
+use std::error;
+use std::fmt;
+
+use cipher::block::blowfish::Blowfish;
+use mac::constant_time_eq;
+
+/// The minimum cost accepted by bcrypt.
+pub const MIN_COST: u32 = 4;
+
+/// The maximum cost accepted by bcrypt.
+pub const MAX_COST: u32 = 31;
+
+/// The size of a bcrypt salt (in bytes).
+pub const SALT_SIZE_BYTES: usize = 16;
+
+/// The number of password bytes that are used: longer passwords are
+/// truncated.
+pub const MAX_PASSWORD_SIZE_BYTES: usize = 72;
+
+/// The size of a raw bcrypt hash (in bytes).
+/// The last byte of the encrypted text is dropped.
+pub const HASH_SIZE_BYTES: usize = 23;
+
+/// A bcrypt salt.
+pub type Salt = [u8; SALT_SIZE_BYTES];
+
+/// The constant string encrypted by bcrypt.
+const MAGIC: &[u8; 24] = b"OrpheanBeholderScryDoubt";
+
+/// The base64 alphabet of bcrypt (which differs from RFC 4648).
+const ALPHABET: &[u8; 64] = b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Errors returned by bcrypt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The cost is not between `MIN_COST` and `MAX_COST`.
+    InvalidCost,
+    /// The hash string is not in the modular crypt format.
+    InvalidHash,
+    /// The password does not match the hash.
+    PasswordMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidCost => write!(f, "invalid cost"),
+            Error::InvalidHash => write!(f, "invalid hash format"),
+            Error::PasswordMismatch => write!(f, "password does not match"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// bcrypt computes the raw hash of a password.
+pub fn bcrypt(password: &[u8], cost: u32, salt: &Salt) -> Result<[u8; HASH_SIZE_BYTES], Error> {
+    if !(MIN_COST..=MAX_COST).contains(&cost) {
+        return Err(Error::InvalidCost);
+    }
+
+    // The password is used as a null-terminated string.
+    let mut key = Vec::from(&password[..password.len().min(MAX_PASSWORD_SIZE_BYTES)]);
+    if key.len() < MAX_PASSWORD_SIZE_BYTES {
+        key.push(0);
+    }
+
+    let state = Blowfish::new_expensive(cost, salt, &key);
+
+    let mut text = [0u32; 6];
+    for i in 0..6 {
+        text[i] = u32::from_be_bytes([
+            MAGIC[4 * i],
+            MAGIC[4 * i + 1],
+            MAGIC[4 * i + 2],
+            MAGIC[4 * i + 3],
+        ]);
+    }
+
+    for _ in 0..64 {
+        for i in 0..3 {
+            let (l, r) = state.encrypt(text[2 * i], text[2 * i + 1]);
+            text[2 * i] = l;
+            text[2 * i + 1] = r;
+        }
+    }
+
+    let mut res = [0u8; HASH_SIZE_BYTES];
+    for i in 0..HASH_SIZE_BYTES {
+        res[i] = text[i / 4].to_be_bytes()[i % 4];
+    }
+
+    Ok(res)
+}
+
+/// hash computes the hash of a password and encodes it in the modular
+/// crypt format, with the `$2b$` prefix.
+/// The salt must be chosen randomly for each password.
+pub fn hash(password: &[u8], cost: u32, salt: &Salt) -> Result<String, Error> {
+    let h = bcrypt(password, cost, salt)?;
+    Ok(format!("$2b${:02}${}{}", cost, encode(salt), encode(&h)))
+}
+
+/// verify checks a password against a hash in the modular crypt format.
+/// The `$2a$`, `$2b$` and `$2y$` prefixes are accepted: they only differ
+/// for passwords longer than 255 bytes or with non-ASCII characters in
+/// buggy implementations.
+pub fn verify(password: &[u8], hash: &str) -> Result<(), Error> {
+    let (cost, salt, expected) = parse(hash)?;
+    let h = bcrypt(password, cost, &salt)?;
+
+    if !constant_time_eq(&h, &expected) {
+        return Err(Error::PasswordMismatch);
+    }
+
+    Ok(())
+}
+
+/// parse splits a hash in the modular crypt format into its cost, salt
+/// and raw hash.
+fn parse(hash: &str) -> Result<(u32, Salt, [u8; HASH_SIZE_BYTES]), Error> {
+    let parts: Vec<&str> = hash.split('$').collect();
+    if parts.len() != 4 || !parts[0].is_empty() {
+        return Err(Error::InvalidHash);
+    }
+
+    if parts[1] != "2a" && parts[1] != "2b" && parts[1] != "2y" {
+        return Err(Error::InvalidHash);
+    }
+
+    if parts[2].len() != 2 || !parts[2].bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidHash);
+    }
+
+    let cost: u32 = parts[2].parse().map_err(|_| Error::InvalidHash)?;
+
+    // 22 characters of salt followed by 31 characters of hash.
+    if parts[3].len() != 53 {
+        return Err(Error::InvalidHash);
+    }
+
+    let mut salt = [0u8; SALT_SIZE_BYTES];
+    salt.copy_from_slice(&decode(&parts[3][..22], SALT_SIZE_BYTES)?);
+    let mut h = [0u8; HASH_SIZE_BYTES];
+    h.copy_from_slice(&decode(&parts[3][22..], HASH_SIZE_BYTES)?);
+
+    Ok((cost, salt, h))
+}
+
+/// encode converts bytes to bcrypt's base64, without padding.
+fn encode(data: &[u8]) -> String {
+    let mut res = String::new();
+    for chunk in data.chunks(3) {
+        let mut b = [0u8; 3];
+        b[..chunk.len()].copy_from_slice(chunk);
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..chunk.len() + 1 {
+            res.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+
+    res
+}
+
+/// decode converts bcrypt's base64 to the given number of bytes.
+fn decode(data: &str, len: usize) -> Result<Vec<u8>, Error> {
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    let mut res = Vec::with_capacity(len);
+
+    for c in data.bytes() {
+        let v = match ALPHABET.iter().position(|a| *a == c) {
+            Some(v) => v as u32,
+            None => return Err(Error::InvalidHash),
+        };
+
+        bits = (bits << 6) | v;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            if res.len() < len {
+                res.push((bits >> bit_count) as u8);
+            }
+        }
+    }
+
+    if res.len() != len {
+        return Err(Error::InvalidHash);
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_decode() {
+        let data: Vec<u8> = (0..23).map(|i| i * 11).collect();
+        let encoded = encode(&data);
+        assert_eq!(31, encoded.len());
+        assert_eq!(data, decode(&encoded, 23).unwrap());
+
+        assert_eq!(22, encode(&[0u8; 16]).len());
+        assert_eq!("......", encode(&[0u8; 4]));
+        assert_eq!(Err(Error::InvalidHash), decode("ab!d", 3));
+    }
+
+    #[test]
+    fn known_hashes() {
+        // Test vectors of jBCrypt.
+        let vectors = [
+            (
+                "",
+                "$2a$06$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s.",
+            ),
+            (
+                "a",
+                "$2a$06$m0CrhHm10qJ3lXRY.5zDGO3rS2KdeeWLuGmsfGlMfOxih58VYVfxe",
+            ),
+            (
+                "abc",
+                "$2a$06$If6bvum7DFjUnE9p2uDeDu0YHzrHM6tf.iqN8.yx.jNN1ILEf7h0i",
+            ),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "$2a$06$.rCVZVOThsIa97pEDOxvGuRRgzG64bvtJ0938xuqzv18d3ZpQhstC",
+            ),
+            (
+                "~!@#$%^&*()      ~!@#$%^&*()PNBFRD",
+                "$2a$06$fPIsBO8qRqkjj273rfaOI.HtSV9jLDpTbZn782DC6/t7qT67P6FfO",
+            ),
+        ];
+
+        for (password, h) in vectors.iter() {
+            assert_eq!(Ok(()), verify(password.as_bytes(), h));
+
+            // Hashing again with the same salt gives the same result, with
+            // the $2b$ prefix.
+            let (cost, salt, _) = parse(h).unwrap();
+            let rehashed = hash(password.as_bytes(), cost, &salt).unwrap();
+            assert_eq!(&h[4..], &rehashed[4..]);
+            assert!(rehashed.starts_with("$2b$"));
+        }
+    }
+
+    #[test]
+    fn verify_wrong_password() {
+        let h = hash("there is no spoon".as_bytes(), 4, &[42u8; 16]).unwrap();
+        assert_eq!(Ok(()), verify("there is no spoon".as_bytes(), &h));
+        assert_eq!(
+            Err(Error::PasswordMismatch),
+            verify("there is a spoon".as_bytes(), &h)
+        );
+    }
+
+    #[test]
+    fn long_passwords_are_truncated() {
+        let password = [b'a'; 80];
+        assert_eq!(
+            bcrypt(&password[..72], 4, &[1u8; 16]),
+            bcrypt(&password, 4, &[1u8; 16])
+        );
+        assert_ne!(
+            bcrypt(&password[..71], 4, &[1u8; 16]),
+            bcrypt(&password[..72], 4, &[1u8; 16])
+        );
+    }
+
+    #[test]
+    fn invalid_cost() {
+        assert_eq!(Err(Error::InvalidCost), bcrypt(&[], 3, &[0u8; 16]));
+        assert_eq!(Err(Error::InvalidCost), bcrypt(&[], 32, &[0u8; 16]));
+    }
+
+    #[test]
+    fn invalid_hash() {
+        let invalid = [
+            "",
+            "$2b$06$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s",
+            "$2c$06$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s.",
+            "$2b$6$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s.",
+            "$2b$06$DCq7YPn5Rq63x1Lad4cll!TV4S6ytwfsfvkgY8jIucDrjc8deX1s.",
+            "2b$06$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s.",
+        ];
+
+        for h in invalid.iter() {
+            assert_eq!(Err(Error::InvalidHash), verify(&[], h));
+        }
+
+        assert_eq!(
+            Err(Error::InvalidCost),
+            verify(
+                &[],
+                "$2b$03$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s."
+            )
+        );
+    }
+}
@@ -0,0 +1,544 @@
+//! # HKDF
+//!
+//! `hkdf` implements the HKDF key derivation function (RFC 5869).
+//! HKDF first extracts a pseudorandom key from input keying material that
+//! may not be uniformly random, such as a Diffie-Hellman shared secret,
+//! then expands it into as many keys as needed: keys derived with
+//! different info strings are independent.
+//! HKDF is not a password hashing function: it is fast, so the input
+//! keying material must already be hard to guess.
+
+use std::error;
+use std::fmt;
+
+use cipher::block::aes::{Aes, TableAes};
+use cipher::block::aria::Aria;
+use cipher::block::blowfish::Blowfish;
+use cipher::block::camellia::Camellia;
+use cipher::block::des::{Des, TripleDes};
+use cipher::block::mars::Mars;
+use cipher::block::present::Present;
+use cipher::block::rc6::Rc6;
+use cipher::block::serpent::Serpent;
+use cipher::block::simon::Simon128;
+use cipher::block::sm4::Sm4;
+use cipher::block::speck::Speck128;
+use cipher::block::tea::Tea;
+use cipher::block::twofish::Twofish;
+use cipher::block::xtea::Xtea;
+use cipher::stream::a5::{self, A51, A52};
+use cipher::stream::grain::{self, Grain128a};
+use cipher::stream::hc128::{self, Hc128};
+use cipher::stream::kreyvium::{self, Kreyvium};
+use cipher::stream::mickey::{self, Mickey};
+use cipher::stream::rabbit::{self, Rabbit};
+use cipher::stream::rc4::Rc4;
+use cipher::stream::salsa20::Salsa20;
+use cipher::stream::sosemanuk::{self, Sosemanuk};
+use cipher::stream::trivium::{self, Trivium};
+use hash::sha2::{Sha256, Sha512};
+use hash::BlockHasher;
+use mac::hmac::Hmac;
+
+/// The maximum number of HMAC blocks of output.
+const MAX_BLOCKS: usize = 255;
+
+/// Errors returned by HKDF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The requested output is longer than 255 digests.
+    OutputTooLong,
+    /// The pseudorandom key is shorter than a digest.
+    InvalidPrkLength,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::OutputTooLong => write!(f, "output too long"),
+            Error::InvalidPrkLength => write!(f, "invalid pseudorandom key length"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A key type that can be built from HKDF output.
+pub trait DerivedKey: Sized {
+    /// The size of the key (in bytes).
+    const SIZE_BYTES: usize;
+
+    /// from_bytes builds a key from `SIZE_BYTES` bytes of output.
+    fn from_bytes(bytes: &[u8]) -> Self;
+}
+
+/// Byte keys.
+impl<const N: usize> DerivedKey for [u8; N] {
+    const SIZE_BYTES: usize = N;
+
+    fn from_bytes(bytes: &[u8]) -> [u8; N] {
+        let mut key = [0u8; N];
+        key.copy_from_slice(bytes);
+        key
+    }
+}
+
+/// 32-bit word keys, such as Mars or TEA keys.
+/// Words are read in little-endian order, like the blocks of these ciphers.
+impl<const N: usize> DerivedKey for [u32; N] {
+    const SIZE_BYTES: usize = 4 * N;
+
+    fn from_bytes(bytes: &[u8]) -> [u32; N] {
+        let mut key = [0u32; N];
+        for i in 0..N {
+            key[i] = u32::from_le_bytes([
+                bytes[4 * i],
+                bytes[4 * i + 1],
+                bytes[4 * i + 2],
+                bytes[4 * i + 3],
+            ]);
+        }
+
+        key
+    }
+}
+
+/// 64-bit word keys, such as Speck128 or Triple-DES keys.
+/// Words are read in big-endian order, like DES keys: the first bytes go to
+/// the first word, which Speck128 and Simon128 take as the most significant
+/// one.
+impl<const N: usize> DerivedKey for [u64; N] {
+    const SIZE_BYTES: usize = 8 * N;
+
+    fn from_bytes(bytes: &[u8]) -> [u64; N] {
+        let mut key = [0u64; N];
+        for i in 0..N {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&bytes[8 * i..8 * (i + 1)]);
+            key[i] = u64::from_be_bytes(word);
+        }
+
+        key
+    }
+}
+
+/// DES keys, read in big-endian order like the DES test vectors.
+/// The parity bits are left as derived: DES ignores them.
+impl DerivedKey for u64 {
+    const SIZE_BYTES: usize = 8;
+
+    fn from_bytes(bytes: &[u8]) -> u64 {
+        let mut key = [0u8; 8];
+        key.copy_from_slice(bytes);
+        u64::from_be_bytes(key)
+    }
+}
+
+/// A cipher whose key can be derived with HKDF.
+/// Ciphers that accept several key sizes get 256-bit keys, or their
+/// largest key when it is shorter.
+/// The generic `Speck` and `Simon` ciphers are not included: their key
+/// size depends on the word size chosen at runtime, so they have no key
+/// type; `Speck128` and `Simon128` are.
+pub trait KeyedCipher {
+    /// The key given to the cipher constructor.
+    type Key: DerivedKey;
+}
+
+impl KeyedCipher for Aes {
+    type Key = [u8; 32];
+}
+
+impl KeyedCipher for TableAes {
+    type Key = [u8; 32];
+}
+
+impl KeyedCipher for Aria {
+    type Key = [u8; 32];
+}
+
+impl KeyedCipher for Blowfish {
+    type Key = [u8; 32];
+}
+
+impl KeyedCipher for Camellia {
+    type Key = [u8; 32];
+}
+
+impl KeyedCipher for Des {
+    type Key = u64;
+}
+
+impl KeyedCipher for Mars {
+    type Key = [u32; 8];
+}
+
+impl KeyedCipher for Present {
+    type Key = [u8; 16];
+}
+
+impl KeyedCipher for Rc6 {
+    type Key = [u32; 8];
+}
+
+impl KeyedCipher for Serpent {
+    type Key = [u32; 8];
+}
+
+impl KeyedCipher for Simon128 {
+    type Key = [u64; 4];
+}
+
+impl KeyedCipher for Sm4 {
+    type Key = [u8; 16];
+}
+
+impl KeyedCipher for Speck128 {
+    type Key = [u64; 4];
+}
+
+impl KeyedCipher for Tea {
+    type Key = [u32; 4];
+}
+
+impl KeyedCipher for Twofish {
+    type Key = [u32; 8];
+}
+
+impl KeyedCipher for TripleDes {
+    type Key = [u64; 3];
+}
+
+impl KeyedCipher for Xtea {
+    type Key = [u32; 4];
+}
+
+impl KeyedCipher for A51 {
+    type Key = a5::Key;
+}
+
+impl KeyedCipher for A52 {
+    type Key = a5::Key;
+}
+
+impl KeyedCipher for Grain128a {
+    type Key = grain::Key;
+}
+
+impl KeyedCipher for Hc128 {
+    type Key = hc128::Key;
+}
+
+impl KeyedCipher for Kreyvium {
+    type Key = kreyvium::Key;
+}
+
+impl KeyedCipher for Mickey {
+    type Key = mickey::Key;
+}
+
+impl KeyedCipher for Rabbit {
+    type Key = rabbit::Key;
+}
+
+impl KeyedCipher for Rc4 {
+    type Key = [u8; 32];
+}
+
+impl KeyedCipher for Salsa20 {
+    type Key = [u8; 32];
+}
+
+impl KeyedCipher for Sosemanuk {
+    type Key = [u8; sosemanuk::MAX_KEY_SIZE_BYTES];
+}
+
+impl KeyedCipher for Trivium {
+    type Key = trivium::Key;
+}
+
+/// A HKDF instance holding a pseudorandom key.
+#[derive(Clone)]
+pub struct Hkdf<H: BlockHasher> {
+    prk: Vec<u8>,
+    mac: Hmac<H>,
+}
+
+/// The pseudorandom key is secret, so it is not printed.
+impl<H: BlockHasher> fmt::Debug for Hkdf<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hkdf")
+            .field("prk", &"<redacted>")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A HKDF instantiated with SHA-256.
+pub type HkdfSha256 = Hkdf<Sha256>;
+/// A HKDF instantiated with SHA-512.
+pub type HkdfSha512 = Hkdf<Sha512>;
+
+// Public methods.
+impl<H: BlockHasher> Hkdf<H> {
+    /// extract derives a pseudorandom key from the input keying material
+    /// and an optional salt.
+    /// An empty salt is equivalent to the default salt of RFC 5869 (a
+    /// digest of zeros), since HMAC pads its key with zeros.
+    pub fn extract(salt: &[u8], ikm: &[u8]) -> Hkdf<H> {
+        let mut mac = Hmac::<H>::new(salt);
+        mac.update(ikm);
+        let prk = mac.finalize();
+
+        Hkdf {
+            mac: Hmac::new(&prk),
+            prk,
+        }
+    }
+
+    /// from_prk skips extraction, when the input keying material is
+    /// already a uniformly random key.
+    /// The key must be at least as long as a digest.
+    pub fn from_prk(prk: &[u8]) -> Result<Hkdf<H>, Error> {
+        if prk.len() < H::OUTPUT_SIZE_BYTES {
+            return Err(Error::InvalidPrkLength);
+        }
+
+        Ok(Hkdf {
+            prk: prk.to_vec(),
+            mac: Hmac::new(prk),
+        })
+    }
+
+    /// prk returns the pseudorandom key.
+    pub fn prk(&self) -> &[u8] {
+        &self.prk
+    }
+
+    /// expand fills the output with key material bound to the info string.
+    /// The output can be at most 255 digests long.
+    pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<(), Error> {
+        if okm.len() > MAX_BLOCKS * H::OUTPUT_SIZE_BYTES {
+            return Err(Error::OutputTooLong);
+        }
+
+        let mut t = Vec::new();
+        for (i, chunk) in okm.chunks_mut(H::OUTPUT_SIZE_BYTES).enumerate() {
+            let mut mac = self.mac.clone();
+            mac.update(&t);
+            mac.update(info);
+            mac.update(&[i as u8 + 1]);
+            t = mac.finalize();
+
+            chunk.copy_from_slice(&t[..chunk.len()]);
+        }
+
+        Ok(())
+    }
+
+    /// expand_key derives a key for the given cipher, bound to the info
+    /// string.
+    pub fn expand_key<C: KeyedCipher>(&self, info: &[u8]) -> Result<C::Key, Error> {
+        let mut okm = vec![0u8; C::Key::SIZE_BYTES];
+        self.expand(info, &mut okm)?;
+        Ok(C::Key::from_bytes(&okm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    use cipher::block::{BlockCipher, BLOCK_SIZE_BYTES};
+    use hash::sha1::Sha1;
+
+    /// A cipher with a key longer than the longest HKDF-SHA256 output.
+    struct HugeKeyCipher;
+
+    impl KeyedCipher for HugeKeyCipher {
+        type Key = [u8; 255 * 32 + 1];
+    }
+
+    fn hkdf<H: BlockHasher>(
+        salt: &[u8],
+        ikm: &[u8],
+        info: &[u8],
+        len: usize,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let hkdf = Hkdf::<H>::extract(salt, ikm);
+        let mut okm = vec![0u8; len];
+        hkdf.expand(info, &mut okm).unwrap();
+        (hkdf.prk().to_vec(), okm)
+    }
+
+    #[test]
+    fn rfc5869_sha256() {
+        // Test cases 1 to 3.
+        let salt: Vec<u8> = (0x00..0x0d).collect();
+        let info: Vec<u8> = (0xf0..0xfa).collect();
+        assert_eq!(
+            (
+                hex("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"),
+                hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"),
+            ),
+            hkdf::<Sha256>(&salt, &[0x0b; 22], &info, 42)
+        );
+
+        let ikm: Vec<u8> = (0x00..0x50).collect();
+        let salt: Vec<u8> = (0x60..0xb0).collect();
+        let info: Vec<u8> = (0xb0..=0xff).collect();
+        assert_eq!(
+            (
+                hex("06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244"),
+                hex("b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71cc30c58179ec3e87c14c01d5c1f3434f1d87"),
+            ),
+            hkdf::<Sha256>(&salt, &ikm, &info, 82)
+        );
+
+        assert_eq!(
+            (
+                hex("19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04"),
+                hex("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"),
+            ),
+            hkdf::<Sha256>(b"", &[0x0b; 22], b"", 42)
+        );
+    }
+
+    #[test]
+    fn rfc5869_sha1() {
+        // Test cases 4 and 7.
+        let salt: Vec<u8> = (0x00..0x0d).collect();
+        let info: Vec<u8> = (0xf0..0xfa).collect();
+        assert_eq!(
+            (
+                hex("9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243"),
+                hex("085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2c22e422478d305f3f896"),
+            ),
+            hkdf::<Sha1>(&salt, &[0x0b; 11], &info, 42)
+        );
+
+        // Test case 7 has no salt, which defaults to a digest of zeros.
+        assert_eq!(
+            (
+                hex("2adccada18779e7c2077ad2eb19d3f3e731385dd"),
+                hex("2c91117204d745f3500d636a62f64f0ab3bae548aa53d423b0d1f27ebba6f5e5673a081d70cce7acfc48"),
+            ),
+            hkdf::<Sha1>(b"", &[0x0c; 22], b"", 42)
+        );
+        assert_eq!(
+            hkdf::<Sha1>(b"", &[0x0c; 22], b"", 42),
+            hkdf::<Sha1>(&[0u8; 20], &[0x0c; 22], b"", 42)
+        );
+    }
+
+    #[test]
+    fn from_prk() {
+        let prk = hex("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5");
+        let info: Vec<u8> = (0xf0..0xfa).collect();
+        let hkdf = HkdfSha256::from_prk(&prk).unwrap();
+        let mut okm = [0u8; 42];
+        hkdf.expand(&info, &mut okm).unwrap();
+        assert_eq!(
+            hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"),
+            okm.to_vec()
+        );
+
+        assert_eq!(
+            Err(Error::InvalidPrkLength),
+            HkdfSha256::from_prk(&prk[..31]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn debug_hides_prk() {
+        let hkdf = HkdfSha256::extract(b"salt", b"there is no spoon");
+        assert_eq!("Hkdf { prk: \"<redacted>\", .. }", format!("{:?}", hkdf));
+    }
+
+    #[test]
+    fn output_length() {
+        let hkdf = HkdfSha256::extract(b"salt", b"there is no spoon");
+        let mut okm = vec![0u8; 255 * 32];
+        assert_eq!(Ok(()), hkdf.expand(b"", &mut okm));
+
+        // Shorter outputs are prefixes of longer ones.
+        let mut short = [0u8; 50];
+        hkdf.expand(b"", &mut short).unwrap();
+        assert_eq!(okm[..50], short[..]);
+
+        okm.push(0);
+        assert_eq!(Err(Error::OutputTooLong), hkdf.expand(b"", &mut okm));
+        assert_eq!(
+            Err(Error::OutputTooLong),
+            hkdf.expand_key::<HugeKeyCipher>(b"")
+        );
+    }
+
+    #[test]
+    fn typed_keys() {
+        let hkdf = HkdfSha512::extract(b"salt", b"there is no spoon");
+
+        let salsa20_key = hkdf.expand_key::<Salsa20>(b"salsa20").unwrap();
+        let mut okm = [0u8; 32];
+        hkdf.expand(b"salsa20", &mut okm).unwrap();
+        assert_eq!(okm, salsa20_key);
+
+        // Words are read in little-endian order.
+        let mars_key = hkdf.expand_key::<Mars>(b"mars").unwrap();
+        hkdf.expand(b"mars", &mut okm).unwrap();
+        assert_eq!(
+            u32::from_le_bytes([okm[0], okm[1], okm[2], okm[3]]),
+            mars_key[0]
+        );
+        assert_eq!(
+            u32::from_le_bytes([okm[28], okm[29], okm[30], okm[31]]),
+            mars_key[7]
+        );
+
+        // 64-bit words and DES keys are read in big-endian order.
+        let speck_key = hkdf.expand_key::<Speck128>(b"speck").unwrap();
+        hkdf.expand(b"speck", &mut okm).unwrap();
+        assert_eq!(okm[8], (speck_key[1] >> 56) as u8);
+        assert_eq!(okm[15], speck_key[1] as u8);
+
+        let des_key = hkdf.expand_key::<Des>(b"des").unwrap();
+        hkdf.expand(b"des", &mut okm[..8]).unwrap();
+        assert_eq!(okm[0], (des_key >> 56) as u8);
+
+        let triple_des_key = hkdf.expand_key::<TripleDes>(b"3des").unwrap();
+        let mut okm3 = [0u8; 24];
+        hkdf.expand(b"3des", &mut okm3).unwrap();
+        assert_eq!(okm3[16], (triple_des_key[2] >> 56) as u8);
+
+        // The info string separates the keys derived from the same PRK.
+        let mut other = [0u8; 32];
+        hkdf.expand(b"salsa20", &mut okm).unwrap();
+        hkdf.expand(b"salsa21", &mut other).unwrap();
+        assert_ne!(okm, other);
+
+        // The keys go straight into the cipher constructors.
+        let message = b"there is no spoon";
+        let salsa20 = Salsa20::new(salsa20_key);
+        let cipher = salsa20.encrypt(message, [0u8; 8]);
+        assert_eq!(message.to_vec(), salsa20.decrypt(&cipher, [0u8; 8]));
+
+        let mars = Mars::new(&mars_key);
+        let block = [7u8; BLOCK_SIZE_BYTES];
+        assert_eq!(block, mars.decrypt_block(&mars.encrypt_block(&block)));
+
+        let aes = Aes::new(&hkdf.expand_key::<Aes>(b"aes").unwrap());
+        assert_eq!(block, aes.decrypt_block(&aes.encrypt_block(&block)));
+
+        let [k1, k2, k3] = triple_des_key;
+        let triple_des = TripleDes::new(k1, k2, k3);
+        assert_eq!(42, triple_des.decrypt(triple_des.encrypt(42)));
+
+        let trivium_key = hkdf.expand_key::<Trivium>(b"trivium").unwrap();
+        let iv = [0u8; trivium::IV_SIZE_BYTES];
+        let cipher = Trivium::new(iv, trivium_key).encrypt(message);
+        assert_eq!(
+            message.to_vec(),
+            Trivium::new(iv, trivium_key).decrypt(&cipher)
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! # KDF
+//!
+//! `kdf` contains a collection of key derivation functions.
+//! Password hashing functions are key derivation functions that are
+//! deliberately slow, to make guessing passwords expensive.
+
+pub mod bcrypt;
+pub mod hkdf;
@@ -6,4 +6,15 @@
 //! Use at your own risk.
 
 #![deny(missing_docs, unsafe_code)]
+
+#[cfg(feature = "rand_core")]
+extern crate rand_core;
+
 pub mod cipher;
+pub mod hash;
+pub mod kdf;
+pub mod mac;
+pub mod rand;
+
+#[cfg(test)]
+mod test_utils;
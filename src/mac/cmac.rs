@@ -0,0 +1,304 @@
+//! # CMAC
+//!
+//! `cmac` implements the CMAC message authentication code
+//! (NIST SP 800-38B), also known as OMAC1.
+//! CMAC only needs a block cipher, which makes it a good MAC for
+//! constrained environments that do not implement a hash function.
+
+use std::error;
+use std::fmt;
+
+use cipher::block::mars::Mars;
+use cipher::block::{xor, Block, BlockCipher, BLOCK_SIZE_BYTES};
+use mac::constant_time_eq;
+
+/// The minimum size of a truncated tag (in bytes).
+/// SP 800-38B recommends against tags shorter than 64 bits.
+pub const MIN_TAG_SIZE_BYTES: usize = 8;
+
+/// Errors returned by CMAC verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The tag is shorter than `MIN_TAG_SIZE_BYTES` or longer than a block.
+    InvalidTagLength,
+    /// The tag does not match the message.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidTagLength => write!(f, "invalid tag length"),
+            Error::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A CMAC instance.
+/// Messages can be fed incrementally with `update`.
+pub struct Cmac<C: BlockCipher> {
+    cipher: C,
+    k1: Block,
+    k2: Block,
+    state: Block,
+    buffer: Block,
+    buffer_len: usize,
+}
+
+/// A CMAC instantiated with Mars.
+pub type MarsCmac = Cmac<Mars>;
+
+// Public methods.
+impl<C: BlockCipher> Cmac<C> {
+    /// new creates a CMAC instance keyed by the given block cipher.
+    pub fn new(cipher: C) -> Cmac<C> {
+        let (k1, k2) = subkeys(&cipher);
+
+        Cmac {
+            cipher,
+            k1,
+            k2,
+            state: [0u8; BLOCK_SIZE_BYTES],
+            buffer: [0u8; BLOCK_SIZE_BYTES],
+            buffer_len: 0,
+        }
+    }
+
+    /// update feeds more data to the MAC.
+    pub fn update(&mut self, data: &[u8]) {
+        for b in data {
+            // The last block is treated differently, so a full buffer is
+            // only processed once we know more data follows.
+            if self.buffer_len == BLOCK_SIZE_BYTES {
+                self.state = self.cipher.encrypt_block(&xor(&self.state, &self.buffer));
+                self.buffer_len = 0;
+            }
+
+            self.buffer[self.buffer_len] = *b;
+            self.buffer_len += 1;
+        }
+    }
+
+    /// finalize computes the tag of all the data fed so far.
+    pub fn finalize(self) -> Block {
+        let last = if self.buffer_len == BLOCK_SIZE_BYTES {
+            xor(&self.buffer, &self.k1)
+        } else {
+            let mut padded = [0u8; BLOCK_SIZE_BYTES];
+            padded[..self.buffer_len].copy_from_slice(&self.buffer[..self.buffer_len]);
+            padded[self.buffer_len] = 0x80;
+            xor(&padded, &self.k2)
+        };
+
+        self.cipher.encrypt_block(&xor(&self.state, &last))
+    }
+
+    /// verify checks a (possibly truncated) tag against all the data fed
+    /// so far.
+    /// Truncated tags are compared with the leftmost bytes of the full tag.
+    pub fn verify(self, tag: &[u8]) -> Result<(), Error> {
+        if tag.len() < MIN_TAG_SIZE_BYTES || tag.len() > BLOCK_SIZE_BYTES {
+            return Err(Error::InvalidTagLength);
+        }
+
+        let expected = self.finalize();
+        if !constant_time_eq(&expected[..tag.len()], tag) {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        Ok(())
+    }
+
+    /// reset discards the data fed so far, keeping the key.
+    pub fn reset(&mut self) {
+        self.state = [0u8; BLOCK_SIZE_BYTES];
+        self.buffer = [0u8; BLOCK_SIZE_BYTES];
+        self.buffer_len = 0;
+    }
+}
+
+/// cmac computes the tag of a message in one call.
+pub fn cmac<C: BlockCipher>(cipher: &C, message: &[u8]) -> Block {
+    let mut mac = Cmac::new(cipher);
+    mac.update(message);
+    mac.finalize()
+}
+
+/// dbl multiplies a block by x in GF(2^128), using the big-endian
+/// convention of SP 800-38B.
+pub fn dbl(b: &Block) -> Block {
+    let mut res = [0u8; BLOCK_SIZE_BYTES];
+    for i in 0..BLOCK_SIZE_BYTES - 1 {
+        res[i] = (b[i] << 1) | (b[i + 1] >> 7);
+    }
+
+    res[BLOCK_SIZE_BYTES - 1] = b[BLOCK_SIZE_BYTES - 1] << 1;
+
+    // Reduce modulo x^128 + x^7 + x^2 + x + 1.
+    if b[0] & 0x80 != 0 {
+        res[BLOCK_SIZE_BYTES - 1] ^= 0x87;
+    }
+
+    res
+}
+
+/// subkeys derives the K1 and K2 subkeys from the block cipher.
+fn subkeys<C: BlockCipher>(cipher: &C) -> (Block, Block) {
+    let l = cipher.encrypt_block(&[0u8; BLOCK_SIZE_BYTES]);
+    let k1 = dbl(&l);
+    let k2 = dbl(&k1);
+    (k1, k2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::block::aes::Aes;
+    use test_utils::hex;
+
+    const TEST_KEY: [u32; 4] = [0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c];
+
+    #[test]
+    fn dbl_spec() {
+        // Subkeys of the SP 800-38B AES-128 examples.
+        let l = [
+            0x7d, 0xf7, 0x6b, 0x0c, 0x1a, 0xb8, 0x99, 0xb3, 0x3e, 0x42, 0xf0, 0x47, 0xb9, 0x1b,
+            0x54, 0x6f,
+        ];
+        let k1 = [
+            0xfb, 0xee, 0xd6, 0x18, 0x35, 0x71, 0x33, 0x66, 0x7c, 0x85, 0xe0, 0x8f, 0x72, 0x36,
+            0xa8, 0xde,
+        ];
+        let k2 = [
+            0xf7, 0xdd, 0xac, 0x30, 0x6a, 0xe2, 0x66, 0xcc, 0xf9, 0x0b, 0xc1, 0x1e, 0xe4, 0x6d,
+            0x51, 0x3b,
+        ];
+
+        assert_eq!(k1, dbl(&l));
+        assert_eq!(k2, dbl(&k1));
+
+        // Doubling step of RFC 5297 appendix A.1.
+        assert_eq!(
+            [
+                0x1c, 0x09, 0xbf, 0x5f, 0x83, 0xdf, 0x7e, 0x08, 0x02, 0x80, 0xb0, 0x50, 0xb3, 0x7e,
+                0x0e, 0x74,
+            ],
+            dbl(&[
+                0x0e, 0x04, 0xdf, 0xaf, 0xc1, 0xef, 0xbf, 0x04, 0x01, 0x40, 0x58, 0x28, 0x59, 0xbf,
+                0x07, 0x3a,
+            ])
+        );
+    }
+
+    #[test]
+    fn subkeys_from_cipher() {
+        let cipher = Mars::new(&TEST_KEY);
+        let l = cipher.encrypt_block(&[0u8; 16]);
+        let (k1, k2) = subkeys(&cipher);
+        assert_eq!(dbl(&l), k1);
+        assert_eq!(dbl(&dbl(&l)), k2);
+    }
+
+    #[test]
+    fn empty_message() {
+        // The empty message is padded to a full block and masked with K2.
+        let cipher = Mars::new(&TEST_KEY);
+        let (_, k2) = subkeys(&cipher);
+        let mut padded = [0u8; 16];
+        padded[0] = 0x80;
+
+        assert_eq!(cipher.encrypt_block(&xor(&padded, &k2)), cmac(&cipher, &[]));
+    }
+
+    #[test]
+    fn full_block_message() {
+        // A complete last block is masked with K1 and not padded.
+        let cipher = Mars::new(&TEST_KEY);
+        let (k1, _) = subkeys(&cipher);
+        let message = [42u8; 16];
+
+        assert_eq!(
+            cipher.encrypt_block(&xor(&message, &k1)),
+            cmac(&cipher, &message)
+        );
+    }
+
+    #[test]
+    fn update_in_chunks() {
+        let message: Vec<u8> = (0..100).collect();
+        let expected = cmac(&Mars::new(&TEST_KEY), &message);
+
+        for chunk_size in [1, 7, 15, 16, 17, 33].iter() {
+            let mut mac = MarsCmac::new(Mars::new(&TEST_KEY));
+            for chunk in message.chunks(*chunk_size) {
+                mac.update(chunk);
+            }
+
+            assert_eq!(expected, mac.finalize());
+        }
+    }
+
+    #[test]
+    fn reset() {
+        let mut mac = MarsCmac::new(Mars::new(&TEST_KEY));
+        mac.update("there is no spoon".as_bytes());
+        mac.reset();
+        mac.update("hello".as_bytes());
+
+        assert_eq!(
+            cmac(&Mars::new(&TEST_KEY), "hello".as_bytes()),
+            mac.finalize()
+        );
+    }
+
+    #[test]
+    fn verify() {
+        let message = "there is no spoon".as_bytes();
+        let tag = cmac(&Mars::new(&TEST_KEY), message);
+
+        let mut mac = MarsCmac::new(Mars::new(&TEST_KEY));
+        mac.update(message);
+        assert_eq!(Ok(()), mac.verify(&tag));
+
+        let mut mac = MarsCmac::new(Mars::new(&TEST_KEY));
+        mac.update(message);
+        assert_eq!(Ok(()), mac.verify(&tag[..8]));
+    }
+
+    #[test]
+    fn verify_tampered() {
+        let message = "there is no spoon".as_bytes();
+        let mut tag = cmac(&Mars::new(&TEST_KEY), message);
+        tag[3] ^= 1;
+
+        let mut mac = MarsCmac::new(Mars::new(&TEST_KEY));
+        mac.update(message);
+        assert_eq!(Err(Error::AuthenticationFailed), mac.verify(&tag));
+    }
+
+    #[test]
+    fn verify_invalid_tag_length() {
+        let mac = MarsCmac::new(Mars::new(&TEST_KEY));
+        assert_eq!(Err(Error::InvalidTagLength), mac.verify(&[0u8; 7]));
+
+        let mac = MarsCmac::new(Mars::new(&TEST_KEY));
+        assert_eq!(Err(Error::InvalidTagLength), mac.verify(&[0u8; 17]));
+    }
+
+    #[test]
+    fn aes_cmac_spec() {
+        // AES-128 examples of SP 800-38B.
+        let cipher = Aes::new(&hex("2b7e151628aed2a6abf7158809cf4f3c"));
+
+        assert_eq!(
+            hex("bb1d6929e95937287fa37d129b756746"),
+            cmac(&cipher, &[]).to_vec()
+        );
+        assert_eq!(
+            hex("070a16b46b4d4144f79bdd9dd04a287c"),
+            cmac(&cipher, &hex("6bc1bee22e409f96e93d7e117393172a")).to_vec()
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! # HMAC
+//!
+//! `hmac` implements the HMAC message authentication code (RFC 2104) over
+//! any hash function that processes its input in blocks.
+//! HMAC hashes the message with a first key-derived block, then hashes the
+//! result with a second one: the outer hash hides the inner digest, so
+//! HMAC is not subject to length extension even when the hash function is.
+//! Keys longer than a block are hashed first.
+
+use std::error;
+use std::fmt;
+
+use hash::sha2::{Sha256, Sha512};
+use hash::BlockHasher;
+use mac::constant_time_eq;
+
+/// The minimum size of a truncated tag (in bytes).
+/// RFC 2104 recommends against tags shorter than 80 bits.
+pub const MIN_TAG_SIZE_BYTES: usize = 10;
+
+/// The byte xored with the key in the inner block.
+const IPAD: u8 = 0x36;
+/// The byte xored with the key in the outer block.
+const OPAD: u8 = 0x5c;
+
+/// Errors returned by HMAC verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The tag is shorter than `MIN_TAG_SIZE_BYTES` or longer than a digest.
+    InvalidTagLength,
+    /// The tag does not match the message.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidTagLength => write!(f, "invalid tag length"),
+            Error::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A HMAC instance.
+/// Messages can be fed incrementally with `update`.
+#[derive(Debug, Clone)]
+pub struct Hmac<H: BlockHasher> {
+    inner: H,
+    outer: H,
+    initial: H,
+}
+
+/// A HMAC instantiated with SHA-256.
+pub type HmacSha256 = Hmac<Sha256>;
+/// A HMAC instantiated with SHA-512.
+pub type HmacSha512 = Hmac<Sha512>;
+
+// Public methods.
+impl<H: BlockHasher> Hmac<H> {
+    /// new creates a HMAC instance with the given key.
+    /// Keys of any length are accepted, but keys shorter than a digest
+    /// weaken the MAC.
+    pub fn new(key: &[u8]) -> Hmac<H> {
+        let mut block = if key.len() > H::BLOCK_SIZE_BYTES {
+            H::digest(key)
+        } else {
+            key.to_vec()
+        };
+        block.resize(H::BLOCK_SIZE_BYTES, 0);
+
+        // Both key-derived blocks are hashed once, so that each tag only
+        // costs the message and the outer digest.
+        let mut inner = H::default();
+        inner.update(&block.iter().map(|b| b ^ IPAD).collect::<Vec<u8>>());
+        let mut outer = H::default();
+        outer.update(&block.iter().map(|b| b ^ OPAD).collect::<Vec<u8>>());
+
+        Hmac {
+            initial: inner.clone(),
+            inner,
+            outer,
+        }
+    }
+
+    /// update feeds more data to the MAC.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// finalize computes the tag of all the data fed so far.
+    pub fn finalize(self) -> Vec<u8> {
+        let mut outer = self.outer;
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+
+    /// verify checks a (possibly truncated) tag against all the data fed
+    /// so far.
+    /// Truncated tags are compared with the leftmost bytes of the full tag.
+    pub fn verify(self, tag: &[u8]) -> Result<(), Error> {
+        if tag.len() < MIN_TAG_SIZE_BYTES || tag.len() > H::OUTPUT_SIZE_BYTES {
+            return Err(Error::InvalidTagLength);
+        }
+
+        let expected = self.finalize();
+        if !constant_time_eq(&expected[..tag.len()], tag) {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        Ok(())
+    }
+
+    /// reset discards the data fed so far, keeping the key.
+    pub fn reset(&mut self) {
+        self.inner = self.initial.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    use hash::blake2b::Blake2b;
+    use hash::md5::Md5;
+    use hash::ripemd160::Ripemd160;
+    use hash::sha1::Sha1;
+    use hash::sha2::{Sha224, Sha384};
+    use hash::sha3::Sha3_256;
+
+    fn mac<H: BlockHasher>(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<H>::new(key);
+        mac.update(data);
+        mac.finalize()
+    }
+
+    /// The RFC 4231 test cases: key, data, and tags with SHA-224, SHA-256,
+    /// SHA-384 and SHA-512.
+    fn rfc4231_cases() -> Vec<(Vec<u8>, Vec<u8>, [&'static str; 4])> {
+        vec![
+            (
+                vec![0x0b; 20],
+                b"Hi There".to_vec(),
+                [
+                    "896fb1128abbdf196832107cd49df33f47b4b1169912ba4f53684b22",
+                    "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                    "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec682aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6",
+                    "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+                ],
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?".to_vec(),
+                [
+                    "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44",
+                    "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                    "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+                    "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+                ],
+            ),
+            (
+                vec![0xaa; 20],
+                vec![0xdd; 50],
+                [
+                    "7fb3cb3588c6c1f6ffa9694d7d6ad2649365b0c1f65d69d1ec8333ea",
+                    "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+                    "88062608d3e6ad8a0aa2ace014c8a86f0aa635d947ac9febe83ef4e55966144b2a5ab39dc13814b94e3ab6e101a34f27",
+                    "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb",
+                ],
+            ),
+            (
+                (1..26).collect(),
+                vec![0xcd; 50],
+                [
+                    "6c11506874013cac6a2abc1bb382627cec6a90d86efc012de7afec5a",
+                    "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+                    "3e8a69b7783c25851933ab6290af6ca77a9981480850009cc5577c6e1f573b4e6801dd23c4a7d679ccf8a386c674cffb",
+                    "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3dba91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+                ],
+            ),
+            (
+                vec![0x0c; 20],
+                b"Test With Truncation".to_vec(),
+                [
+                    "0e2aea68a90c8d37c988bcdb9fca6fa8099cd857c7ec4a1815cac54c",
+                    "a3b6167473100ee06e0c796c2955552bfa6f7c0a6a8aef8b93f860aab0cd20c5",
+                    "3abf34c3503b2a23a46efc619baef897f4c8e42c934ce55ccbae9740fcbc1af4ca62269e2a37cd88ba926341efe4aeea",
+                    "415fad6271580a531d4179bc891d87a650188707922a4fbb36663a1eb16da008711c5b50ddd0fc235084eb9d3364a1454fb2ef67cd1d29fe6773068ea266e96b",
+                ],
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                [
+                    "95e9a0db962095adaebe9b2d6f0dbce2d499f112f2d2b7273fa6870e",
+                    "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                    "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f3cd11f05033ac4c60c2ef6ab4030fe8296248df163f44952",
+                    "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+                ],
+            ),
+            (
+                vec![0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.".to_vec(),
+                [
+                    "3a854166ac5d9f023f54d517d0b39dbd946770db9c2b95c9f6f565d1",
+                    "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+                    "6617178e941f020d351e2f254e8fd32c602420feb0b8fb9adccebb82461e99c5a678cc31e799176d3860e6110c46523e",
+                    "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58",
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn rfc4231_vectors() {
+        for (i, (key, data, tags)) in rfc4231_cases().into_iter().enumerate() {
+            let tags: Vec<Vec<u8>> = tags.iter().map(|t| hex(t)).collect();
+            let macs = [
+                mac::<Sha224>(&key, &data),
+                mac::<Sha256>(&key, &data),
+                mac::<Sha384>(&key, &data),
+                mac::<Sha512>(&key, &data),
+            ];
+
+            for (tag, mac) in tags.iter().zip(macs.iter()) {
+                // Test case 5 only specifies tags truncated to 128 bits.
+                if i == 4 {
+                    assert_eq!(tag[..16], mac[..16]);
+                } else {
+                    assert_eq!(tag, mac);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn other_hashes() {
+        // RFC 2202 and RFC 2286 test case 1.
+        assert_eq!(
+            hex("9294727a3638bb1c13f48ef8158bfc9d"),
+            mac::<Md5>(&[0x0b; 16], b"Hi There")
+        );
+        assert_eq!(
+            hex("b617318655057264e28bc0b6fb378c8ef146be00"),
+            mac::<Sha1>(&[0x0b; 20], b"Hi There")
+        );
+        assert_eq!(
+            hex("24cb4bd67d20fc1a5d2ed7732dcc39377f0a5668"),
+            mac::<Ripemd160>(&[0x0b; 20], b"Hi There")
+        );
+
+        // Computed with Python's hmac module.
+        assert_eq!(
+            hex("b2aa44bfc757ce43647e34e16c907da95055e88fe75353c648260983064936dd"),
+            mac::<Sha3_256>(&b"key".repeat(50), b"there is no spoon")
+        );
+        assert_eq!(
+            hex("2b11009734ff988c4ad2347d3b3596ade9cd3fbdc61229fa24966842b40da407b31e80605038432f18b23288668c181274267c774ef79d45038687ddf510c090"),
+            mac::<Blake2b>(b"key", b"there is no spoon")
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let (key, data, tags) = rfc4231_cases().pop().unwrap();
+        let mut mac = HmacSha256::new(&key);
+        for chunk in data.chunks(7) {
+            mac.update(chunk);
+        }
+        assert_eq!(hex(tags[1]), mac.finalize());
+    }
+
+    #[test]
+    fn verify() {
+        let mut mac = HmacSha512::new(b"key");
+        mac.update(b"there is no spoon");
+        let tag = mac.clone().finalize();
+        assert_eq!(Ok(()), mac.clone().verify(&tag));
+        assert_eq!(Ok(()), mac.clone().verify(&tag[..MIN_TAG_SIZE_BYTES]));
+
+        let mut forged = tag.clone();
+        forged[0] ^= 1;
+        assert_eq!(
+            Err(Error::AuthenticationFailed),
+            mac.clone().verify(&forged[..16])
+        );
+        assert_eq!(Err(Error::InvalidTagLength), mac.clone().verify(&tag[..4]));
+
+        let mut long = tag.clone();
+        long.push(0);
+        assert_eq!(Err(Error::InvalidTagLength), mac.verify(&long));
+    }
+
+    #[test]
+    fn reset() {
+        let (key, data, tags) = rfc4231_cases().remove(1);
+        let mut mac = HmacSha256::new(&key);
+        mac.update(b"garbage");
+        mac.reset();
+        mac.update(&data);
+        assert_eq!(hex(tags[1]), mac.finalize());
+    }
+}
@@ -0,0 +1,206 @@
+//! # KMAC
+//!
+//! `kmac` implements the KMAC message authentication code (NIST SP
+//! 800-185).
+//! KMAC is cSHAKE with the function name "KMAC" and the key absorbed
+//! first: since SHA-3 sponges are not subject to length extension, this is
+//! enough to make a MAC, without the nested construction of HMAC.
+//! The tag length is part of the input, so tags of different lengths are
+//! unrelated; KMACXOF drops this to output a stream of any length.
+
+use std::error;
+use std::fmt;
+
+use hash::sha3::{bytepad, encode_string, right_encode, SecurityStrength, Shake};
+use mac::constant_time_eq;
+
+/// The minimum size of a tag (in bytes).
+/// Shorter tags can be forged by guessing.
+pub const MIN_TAG_SIZE_BYTES: usize = 8;
+
+/// Errors returned by KMAC verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The tag is shorter than `MIN_TAG_SIZE_BYTES`.
+    InvalidTagLength,
+    /// The tag does not match the message.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidTagLength => write!(f, "invalid tag length"),
+            Error::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A KMAC128 or KMAC256 instance.
+/// Messages can be fed incrementally with `update`.
+#[derive(Debug, Clone)]
+pub struct Kmac {
+    xof: Shake,
+    initial: Shake,
+}
+
+// Public methods.
+impl Kmac {
+    /// new creates a KMAC instance with the given key and customization
+    /// string.
+    pub fn new(strength: SecurityStrength, key: &[u8], customization: &[u8]) -> Kmac {
+        let mut xof = Shake::cshake(strength, b"KMAC", customization);
+        xof.update(&bytepad(&encode_string(key), strength.rate()));
+
+        Kmac {
+            initial: xof.clone(),
+            xof,
+        }
+    }
+
+    /// update feeds more data to the MAC.
+    pub fn update(&mut self, data: &[u8]) {
+        self.xof.update(data);
+    }
+
+    /// finalize computes a tag of the given size of all the data fed so
+    /// far.
+    pub fn finalize(mut self, tag_size_bytes: usize) -> Vec<u8> {
+        self.xof.update(&right_encode(8 * tag_size_bytes as u64));
+        let mut tag = vec![0u8; tag_size_bytes];
+        self.xof.read(&mut tag);
+        tag
+    }
+
+    /// finalize_xof turns the MAC into KMACXOF, whose output can be read in
+    /// any length.
+    pub fn finalize_xof(mut self) -> Shake {
+        self.xof.update(&right_encode(0));
+        self.xof
+    }
+
+    /// verify checks a tag against all the data fed so far.
+    pub fn verify(self, tag: &[u8]) -> Result<(), Error> {
+        if tag.len() < MIN_TAG_SIZE_BYTES {
+            return Err(Error::InvalidTagLength);
+        }
+
+        let expected = self.finalize(tag.len());
+        if !constant_time_eq(&expected, tag) {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        Ok(())
+    }
+
+    /// reset discards the data fed so far, keeping the key.
+    pub fn reset(&mut self) {
+        self.xof = self.initial.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::hex;
+
+    const KEY: &str = "404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f";
+
+    #[test]
+    fn kmac128() {
+        // SP 800-185 KMAC samples #1 and #2.
+        let mut mac = Kmac::new(SecurityStrength::Bits128, &hex(KEY), b"");
+        mac.update(&[0x00, 0x01, 0x02, 0x03]);
+        assert_eq!(
+            hex("e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e"),
+            mac.finalize(32)
+        );
+
+        let mut mac = Kmac::new(
+            SecurityStrength::Bits128,
+            &hex(KEY),
+            b"My Tagged Application",
+        );
+        mac.update(&[0x00, 0x01, 0x02, 0x03]);
+        assert_eq!(
+            hex("3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5"),
+            mac.finalize(32)
+        );
+    }
+
+    #[test]
+    fn kmac256() {
+        // SP 800-185 KMAC sample #4.
+        let message: Vec<u8> = (0..200).collect();
+        let mut mac = Kmac::new(
+            SecurityStrength::Bits256,
+            &hex(KEY),
+            b"My Tagged Application",
+        );
+        mac.update(&message);
+        assert_eq!(
+            hex("b58618f71f92e1d56c1b8c55ddd7cd188b97b4ca4d99831eb2699a837da2e4d970fbacfde50033aea585f1a2708510c32d07880801bd182898fe476876fc8965"),
+            mac.finalize(64)
+        );
+    }
+
+    #[test]
+    fn kmacxof() {
+        // SP 800-185 KMACXOF sample #4.
+        let message: Vec<u8> = (0..200).collect();
+        let mut mac = Kmac::new(
+            SecurityStrength::Bits256,
+            &hex(KEY),
+            b"My Tagged Application",
+        );
+        mac.update(&message);
+
+        let mut xof = mac.finalize_xof();
+        let mut output = [0u8; 64];
+        xof.read(&mut output[..10]);
+        xof.read(&mut output[10..]);
+        assert_eq!(
+            hex("d5be731c954ed7732846bb59dbe3a8e30f83e77a4bff4459f2f1c2b4ecebb8ce67ba01c62e8ab8578d2d499bd1bb276768781190020a306a97de281dcc30305d"),
+            output.to_vec()
+        );
+    }
+
+    #[test]
+    fn tag_length_is_authenticated() {
+        let mut mac = Kmac::new(SecurityStrength::Bits128, &hex(KEY), b"");
+        mac.update(b"there is no spoon");
+        let long = mac.clone().finalize(32);
+        let short = mac.finalize(16);
+        assert_ne!(&long[..16], &short[..]);
+    }
+
+    #[test]
+    fn verify() {
+        let mut mac = Kmac::new(SecurityStrength::Bits256, &hex(KEY), b"");
+        mac.update(b"there is no spoon");
+        let tag = mac.clone().finalize(16);
+        assert_eq!(Ok(()), mac.clone().verify(&tag));
+
+        let mut forged = tag.clone();
+        forged[0] ^= 1;
+        assert_eq!(
+            Err(Error::AuthenticationFailed),
+            mac.clone().verify(&forged)
+        );
+        assert_eq!(Err(Error::InvalidTagLength), mac.verify(&tag[..4]));
+    }
+
+    #[test]
+    fn reset() {
+        let mut mac = Kmac::new(SecurityStrength::Bits128, &hex(KEY), b"");
+        mac.update(b"garbage");
+        mac.reset();
+        mac.update(&[0x00, 0x01, 0x02, 0x03]);
+        assert_eq!(
+            hex("e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e"),
+            mac.finalize(32)
+        );
+    }
+}
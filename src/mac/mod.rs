@@ -0,0 +1,25 @@
+//! # MAC
+//!
+//! `mac` contains a collection of message authentication codes.
+//! A MAC lets participants who share a secret key detect messages that
+//! have been tampered with.
+
+pub mod cmac;
+pub mod hmac;
+pub mod kmac;
+
+/// constant_time_eq compares two byte strings without leaking the position
+/// of the first difference.
+/// The lengths are not considered secret.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut diff = 0u8;
+    for i in 0..a.len() {
+        diff |= a[i] ^ b[i];
+    }
+
+    diff == 0
+}
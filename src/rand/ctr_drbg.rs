@@ -0,0 +1,760 @@
+//! # CTR_DRBG
+//!
+//! `ctr_drbg` implements the CTR_DRBG deterministic random bit generator
+//! of NIST SP 800-90A, with a 256-bit block cipher key.
+//! The generator runs a block cipher in counter mode, and updates its key
+//! and counter after every request so that compromising the state does not
+//! reveal previous outputs (backtracking resistance).
+//! The derivation function lets the generator be seeded with inputs of
+//! any length (and any entropy density); without it, the entropy input
+//! must be full-entropy and exactly one seed long.
+//! With prediction resistance, fresh entropy is added before every
+//! request, so that compromising the state does not reveal future outputs
+//! either.
+
+use std::error;
+use std::fmt;
+
+use super::{EntropySource, Rng};
+use cipher::block::mars::Mars;
+use cipher::block::{Block, BlockCipher, BLOCK_SIZE_BYTES};
+
+/// The size of the block cipher key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 32;
+/// The size of a seed (in bytes): a key followed by a counter block.
+pub const SEED_SIZE_BYTES: usize = KEY_SIZE_BYTES + BLOCK_SIZE_BYTES;
+/// The security strength of the generator (in bytes).
+pub const SECURITY_STRENGTH_BYTES: usize = 32;
+/// The maximum number of bytes returned by a single request.
+pub const MAX_REQUEST_SIZE_BYTES: usize = 1 << 16;
+/// The maximum number of requests between reseeds.
+pub const MAX_RESEED_INTERVAL: u64 = 1 << 48;
+
+type Key = [u8; KEY_SIZE_BYTES];
+type Seed = [u8; SEED_SIZE_BYTES];
+
+/// Errors returned by CTR_DRBG.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The entropy source failed.
+    EntropyUnavailable,
+    /// The personalization string or additional input is longer than a
+    /// seed (only without derivation function).
+    InputTooLong,
+    /// More than `MAX_REQUEST_SIZE_BYTES` were requested.
+    RequestTooLarge,
+    /// Prediction resistance was requested, but the generator was not
+    /// instantiated with prediction resistance.
+    PredictionResistanceUnavailable,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::EntropyUnavailable => write!(f, "entropy source is unavailable"),
+            Error::InputTooLong => write!(f, "input is too long"),
+            Error::RequestTooLarge => write!(f, "too many bytes requested"),
+            Error::PredictionResistanceUnavailable => {
+                write!(f, "prediction resistance is unavailable")
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// The instantiation parameters of a CTR_DRBG.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Whether inputs go through the block cipher derivation function.
+    pub derivation_function: bool,
+    /// Whether requests are allowed to ask for prediction resistance.
+    pub prediction_resistance: bool,
+    /// The number of requests after which the generator automatically
+    /// reseeds (between 1 and `MAX_RESEED_INTERVAL`).
+    pub reseed_interval: u64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            derivation_function: true,
+            prediction_resistance: false,
+            reseed_interval: MAX_RESEED_INTERVAL,
+        }
+    }
+}
+
+/// A CTR_DRBG generator.
+/// The block cipher is re-keyed on every update, so the generator is built
+/// from a function creating a cipher from a 256-bit key.
+pub struct CtrDrbg<E: EntropySource, C: BlockCipher> {
+    entropy_source: E,
+    new_cipher: fn(&[u8]) -> C,
+    config: Config,
+    key: Key,
+    v: Block,
+    reseed_counter: u64,
+}
+
+/// A CTR_DRBG generator instantiated with Mars.
+pub type MarsCtrDrbg<E> = CtrDrbg<E, Mars>;
+
+impl<E: EntropySource> MarsCtrDrbg<E> {
+    /// new instantiates a CTR_DRBG with Mars.
+    pub fn new(
+        entropy_source: E,
+        config: Config,
+        nonce: &[u8],
+        personalization: &[u8],
+    ) -> Result<MarsCtrDrbg<E>, Error> {
+        CtrDrbg::with_cipher(new_mars, entropy_source, config, nonce, personalization)
+    }
+}
+
+// Public methods.
+impl<E: EntropySource, C: BlockCipher> CtrDrbg<E, C> {
+    /// with_cipher instantiates a CTR_DRBG with the given block cipher.
+    /// The nonce is only used with the derivation function.
+    /// It panics if the reseed interval is invalid.
+    pub fn with_cipher(
+        new_cipher: fn(&[u8]) -> C,
+        entropy_source: E,
+        config: Config,
+        nonce: &[u8],
+        personalization: &[u8],
+    ) -> Result<CtrDrbg<E, C>, Error> {
+        if config.reseed_interval == 0 || config.reseed_interval > MAX_RESEED_INTERVAL {
+            panic!("Reseed interval must be between 1 and 2^48.");
+        }
+
+        if !config.derivation_function && personalization.len() > SEED_SIZE_BYTES {
+            return Err(Error::InputTooLong);
+        }
+
+        let mut instance = CtrDrbg {
+            entropy_source,
+            new_cipher,
+            config,
+            key: [0u8; KEY_SIZE_BYTES],
+            v: [0u8; BLOCK_SIZE_BYTES],
+            reseed_counter: 0,
+        };
+
+        let entropy = instance.entropy()?;
+        let seed_material = if config.derivation_function {
+            let input = [&entropy[..], nonce, personalization].concat();
+            block_cipher_df(new_cipher, &input)
+        } else {
+            xor_padded(&entropy, personalization)
+        };
+
+        instance.update(&seed_material);
+        instance.reseed_counter = 1;
+
+        Ok(instance)
+    }
+
+    /// reseed adds fresh entropy (and optional additional input) to the
+    /// generator.
+    pub fn reseed(&mut self, additional_input: &[u8]) -> Result<(), Error> {
+        if !self.config.derivation_function && additional_input.len() > SEED_SIZE_BYTES {
+            return Err(Error::InputTooLong);
+        }
+
+        let entropy = self.entropy()?;
+        let seed_material = if self.config.derivation_function {
+            block_cipher_df(self.new_cipher, &[&entropy[..], additional_input].concat())
+        } else {
+            xor_padded(&entropy, additional_input)
+        };
+
+        self.update(&seed_material);
+        self.reseed_counter = 1;
+
+        Ok(())
+    }
+
+    /// generate fills the output with random bytes.
+    /// The generator reseeds first if prediction resistance is requested,
+    /// or if the reseed interval has been reached.
+    pub fn generate(
+        &mut self,
+        output: &mut [u8],
+        additional_input: &[u8],
+        prediction_resistance: bool,
+    ) -> Result<(), Error> {
+        if output.len() > MAX_REQUEST_SIZE_BYTES {
+            return Err(Error::RequestTooLarge);
+        }
+        if !self.config.derivation_function && additional_input.len() > SEED_SIZE_BYTES {
+            return Err(Error::InputTooLong);
+        }
+        if prediction_resistance && !self.config.prediction_resistance {
+            return Err(Error::PredictionResistanceUnavailable);
+        }
+
+        // The additional input is consumed by the reseed.
+        let mut additional_input = additional_input;
+        if prediction_resistance || self.reseed_counter > self.config.reseed_interval {
+            self.reseed(additional_input)?;
+            additional_input = &[];
+        }
+
+        let additional = if additional_input.is_empty() {
+            [0u8; SEED_SIZE_BYTES]
+        } else {
+            let additional = if self.config.derivation_function {
+                block_cipher_df(self.new_cipher, additional_input)
+            } else {
+                xor_padded(&[0u8; SEED_SIZE_BYTES], additional_input)
+            };
+            self.update(&additional);
+            additional
+        };
+
+        let cipher = (self.new_cipher)(&self.key);
+        for chunk in output.chunks_mut(BLOCK_SIZE_BYTES) {
+            increment(&mut self.v);
+            let block = cipher.encrypt_block(&self.v);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+
+        // The key and counter are updated after every request for
+        // backtracking resistance.
+        self.update(&additional);
+        self.reseed_counter += 1;
+
+        Ok(())
+    }
+}
+
+// Private methods.
+impl<E: EntropySource, C: BlockCipher> CtrDrbg<E, C> {
+    /// entropy reads an entropy input from the entropy source.
+    /// Without derivation function, the entropy input must fill a whole
+    /// seed.
+    fn entropy(&mut self) -> Result<Vec<u8>, Error> {
+        let len = if self.config.derivation_function {
+            SECURITY_STRENGTH_BYTES
+        } else {
+            SEED_SIZE_BYTES
+        };
+
+        let mut entropy = vec![0u8; len];
+        self.entropy_source
+            .fill_entropy(&mut entropy)
+            .map_err(|_| Error::EntropyUnavailable)?;
+
+        Ok(entropy)
+    }
+
+    /// update runs the cipher in counter mode to derive a new key and
+    /// counter, mixed with the provided data (CTR_DRBG_Update).
+    fn update(&mut self, provided_data: &Seed) {
+        let cipher = (self.new_cipher)(&self.key);
+
+        let mut temp = [0u8; SEED_SIZE_BYTES];
+        for chunk in temp.chunks_mut(BLOCK_SIZE_BYTES) {
+            increment(&mut self.v);
+            chunk.copy_from_slice(&cipher.encrypt_block(&self.v));
+        }
+
+        for i in 0..SEED_SIZE_BYTES {
+            temp[i] ^= provided_data[i];
+        }
+
+        self.key.copy_from_slice(&temp[..KEY_SIZE_BYTES]);
+        self.v.copy_from_slice(&temp[KEY_SIZE_BYTES..]);
+    }
+}
+
+impl<E: EntropySource, C: BlockCipher> Rng for CtrDrbg<E, C> {
+    /// fill_bytes panics if the generator needs to reseed and the entropy
+    /// source fails.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(MAX_REQUEST_SIZE_BYTES) {
+            if self.generate(chunk, &[], false).is_err() {
+                panic!("Reseeding failed.");
+            }
+        }
+    }
+}
+
+/// new_mars creates a Mars cipher from a key in little-endian words.
+pub(super) fn new_mars(key: &[u8]) -> Mars {
+    let words: Vec<u32> = key
+        .chunks(4)
+        .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+        .collect();
+
+    Mars::new(&words)
+}
+
+/// increment increments a counter block (big-endian, modulo 2^128).
+fn increment(v: &mut Block) {
+    let n = u128::from_be_bytes(*v).wrapping_add(1);
+    *v = n.to_be_bytes();
+}
+
+/// xor_padded XORs a seed with a zero-padded input.
+fn xor_padded(seed: &[u8], input: &[u8]) -> Seed {
+    let mut res = [0u8; SEED_SIZE_BYTES];
+    res.copy_from_slice(seed);
+    for i in 0..input.len() {
+        res[i] ^= input[i];
+    }
+
+    res
+}
+
+/// block_cipher_df compresses an input of any length into a seed
+/// (Block_Cipher_df).
+pub(super) fn block_cipher_df<C: BlockCipher>(new_cipher: fn(&[u8]) -> C, input: &[u8]) -> Seed {
+    // S = L || N || input || 0x80, padded with zeros to a whole block.
+    let mut s = Vec::with_capacity(input.len() + 2 * BLOCK_SIZE_BYTES);
+    s.extend_from_slice(&(input.len() as u32).to_be_bytes());
+    s.extend_from_slice(&(SEED_SIZE_BYTES as u32).to_be_bytes());
+    s.extend_from_slice(input);
+    s.push(0x80);
+    while !s.len().is_multiple_of(BLOCK_SIZE_BYTES) {
+        s.push(0);
+    }
+
+    // The first stage is a CBC-MAC of S keyed by a fixed key, prefixed
+    // with a block counter.
+    let mut k = [0u8; KEY_SIZE_BYTES];
+    for (i, b) in k.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let cipher = new_cipher(&k);
+
+    let mut temp = [0u8; SEED_SIZE_BYTES];
+    for (i, chunk) in temp.chunks_mut(BLOCK_SIZE_BYTES).enumerate() {
+        let mut iv = [0u8; BLOCK_SIZE_BYTES];
+        iv[..4].copy_from_slice(&(i as u32).to_be_bytes());
+
+        let mut chaining = cipher.encrypt_block(&iv);
+        for block in s.chunks(BLOCK_SIZE_BYTES) {
+            for j in 0..BLOCK_SIZE_BYTES {
+                chaining[j] ^= block[j];
+            }
+            chaining = cipher.encrypt_block(&chaining);
+        }
+
+        chunk.copy_from_slice(&chaining);
+    }
+
+    // The second stage encrypts X repeatedly with the derived key.
+    let cipher = new_cipher(&temp[..KEY_SIZE_BYTES]);
+    let mut x = [0u8; BLOCK_SIZE_BYTES];
+    x.copy_from_slice(&temp[KEY_SIZE_BYTES..]);
+
+    let mut res = [0u8; SEED_SIZE_BYTES];
+    for chunk in res.chunks_mut(BLOCK_SIZE_BYTES) {
+        x = cipher.encrypt_block(&x);
+        chunk.copy_from_slice(&x);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::block::aes::Aes;
+    use rand;
+    use test_utils::hex;
+
+    /// An entropy source returning predefined entropy inputs.
+    struct TestEntropy {
+        inputs: Vec<Vec<u8>>,
+    }
+
+    impl TestEntropy {
+        fn new(inputs: &[&[u8]]) -> TestEntropy {
+            TestEntropy {
+                inputs: inputs.iter().map(|i| i.to_vec()).collect(),
+            }
+        }
+    }
+
+    impl EntropySource for TestEntropy {
+        fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            if self.inputs.is_empty() {
+                return Err(rand::Error::OsRandomUnavailable);
+            }
+
+            let input = self.inputs.remove(0);
+            dest.copy_from_slice(&input[..dest.len()]);
+            Ok(())
+        }
+    }
+
+    fn no_df() -> Config {
+        Config {
+            derivation_function: false,
+            ..Config::default()
+        }
+    }
+
+    fn counter(n: u8) -> Block {
+        let mut block = [0u8; BLOCK_SIZE_BYTES];
+        block[BLOCK_SIZE_BYTES - 1] = n;
+        block
+    }
+
+    #[test]
+    fn increment_wraps() {
+        let mut v = [0xffu8; BLOCK_SIZE_BYTES];
+        v[0] = 0;
+        increment(&mut v);
+        assert_eq!(counter(0)[1..], v[1..]);
+        assert_eq!(1, v[0]);
+
+        let mut v = [0xffu8; BLOCK_SIZE_BYTES];
+        increment(&mut v);
+        assert_eq!([0u8; BLOCK_SIZE_BYTES], v);
+    }
+
+    #[test]
+    fn instantiate_without_df() {
+        let entropy = [7u8; SEED_SIZE_BYTES];
+        let drbg = MarsCtrDrbg::new(TestEntropy::new(&[&entropy]), no_df(), &[], b"hello").unwrap();
+
+        // Key = 0 and V = 0, updated with the entropy XORed with the
+        // padded personalization string.
+        let cipher = new_mars(&[0u8; KEY_SIZE_BYTES]);
+        let mut expected = Vec::new();
+        for i in 1..4 {
+            expected.extend_from_slice(&cipher.encrypt_block(&counter(i)));
+        }
+        let seed_material = xor_padded(&entropy, b"hello");
+        for i in 0..SEED_SIZE_BYTES {
+            expected[i] ^= seed_material[i];
+        }
+
+        assert_eq!(expected[..KEY_SIZE_BYTES], drbg.key);
+        assert_eq!(expected[KEY_SIZE_BYTES..], drbg.v);
+        assert_eq!(1, drbg.reseed_counter);
+    }
+
+    #[test]
+    fn generate_step_by_step() {
+        let entropy = [7u8; SEED_SIZE_BYTES];
+        let mut drbg = MarsCtrDrbg::new(TestEntropy::new(&[&entropy]), no_df(), &[], &[]).unwrap();
+        let (key, v) = (drbg.key, drbg.v);
+        let start = u128::from_be_bytes(v);
+        let block = |i: u128| new_mars(&key).encrypt_block(&start.wrapping_add(i).to_be_bytes());
+
+        let mut output = [0u8; 20];
+        drbg.generate(&mut output, &[], false).unwrap();
+
+        // The output is the counter mode key stream from V + 1.
+        assert_eq!(block(1)[..], output[..16]);
+        assert_eq!(block(2)[..4], output[16..]);
+
+        // The state is then updated with the next three blocks.
+        assert_eq!(block(3)[..], drbg.key[..16]);
+        assert_eq!(block(4)[..], drbg.key[16..]);
+        assert_eq!(block(5), drbg.v);
+        assert_eq!(2, drbg.reseed_counter);
+    }
+
+    #[test]
+    fn generate_with_additional_input() {
+        let entropy = [7u8; SEED_SIZE_BYTES];
+        let mut drbg1 = MarsCtrDrbg::new(TestEntropy::new(&[&entropy]), no_df(), &[], &[]).unwrap();
+        let mut drbg2 = MarsCtrDrbg::new(TestEntropy::new(&[&entropy]), no_df(), &[], &[]).unwrap();
+
+        // The additional input updates the state before generating.
+        drbg2.update(&xor_padded(&[0u8; SEED_SIZE_BYTES], b"extra"));
+        let mut expected = [0u8; 32];
+        drbg2.generate(&mut expected, &[], false).unwrap();
+
+        let mut output = [0u8; 32];
+        drbg1.generate(&mut output, b"extra", false).unwrap();
+        assert_eq!(expected, output);
+        assert_ne!(drbg1.key, drbg2.key);
+    }
+
+    #[test]
+    fn reseed_resets_counter() {
+        let mut drbg = MarsCtrDrbg::new(
+            TestEntropy::new(&[&[1u8; 32], &[2u8; 32]]),
+            Config::default(),
+            b"nonce",
+            &[],
+        )
+        .unwrap();
+
+        let mut output = [0u8; 16];
+        drbg.generate(&mut output, &[], false).unwrap();
+        drbg.generate(&mut output, &[], false).unwrap();
+        assert_eq!(3, drbg.reseed_counter);
+
+        let key = drbg.key;
+        drbg.reseed(b"additional").unwrap();
+        assert_eq!(1, drbg.reseed_counter);
+        assert_ne!(key, drbg.key);
+
+        // The entropy source is exhausted.
+        assert_eq!(Err(Error::EntropyUnavailable), drbg.reseed(&[]));
+    }
+
+    #[test]
+    fn reseed_interval() {
+        let config = Config {
+            reseed_interval: 2,
+            ..Config::default()
+        };
+        let mut drbg = MarsCtrDrbg::new(
+            TestEntropy::new(&[&[1u8; 32], &[2u8; 32]]),
+            config,
+            b"nonce",
+            &[],
+        )
+        .unwrap();
+
+        let mut output = [0u8; 16];
+        drbg.generate(&mut output, &[], false).unwrap();
+        drbg.generate(&mut output, &[], false).unwrap();
+        assert_eq!(3, drbg.reseed_counter);
+
+        // The third request reseeds automatically.
+        drbg.generate(&mut output, &[], false).unwrap();
+        assert_eq!(2, drbg.reseed_counter);
+        assert!(drbg.entropy_source.inputs.is_empty());
+        drbg.generate(&mut output, &[], false).unwrap();
+        assert_eq!(
+            Err(Error::EntropyUnavailable),
+            drbg.generate(&mut output, &[], false)
+        );
+    }
+
+    #[test]
+    fn prediction_resistance() {
+        let mut drbg = MarsCtrDrbg::new(
+            TestEntropy::new(&[&[1u8; 32], &[2u8; 32]]),
+            Config::default(),
+            b"nonce",
+            &[],
+        )
+        .unwrap();
+
+        let mut output = [0u8; 16];
+        assert_eq!(
+            Err(Error::PredictionResistanceUnavailable),
+            drbg.generate(&mut output, &[], true)
+        );
+
+        let config = Config {
+            prediction_resistance: true,
+            ..Config::default()
+        };
+        let mut drbg = MarsCtrDrbg::new(
+            TestEntropy::new(&[&[1u8; 32], &[2u8; 32], &[3u8; 32]]),
+            config,
+            b"nonce",
+            &[],
+        )
+        .unwrap();
+
+        // Every request with prediction resistance reseeds.
+        drbg.generate(&mut output, &[], true).unwrap();
+        drbg.generate(&mut output, &[], false).unwrap();
+        drbg.generate(&mut output, &[], true).unwrap();
+        assert!(drbg.entropy_source.inputs.is_empty());
+        assert_eq!(
+            Err(Error::EntropyUnavailable),
+            drbg.generate(&mut output, &[], true)
+        );
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        let entropy = [7u8; SEED_SIZE_BYTES];
+        assert!(MarsCtrDrbg::new(
+            TestEntropy::new(&[&entropy]),
+            no_df(),
+            &[],
+            &[0u8; SEED_SIZE_BYTES + 1]
+        )
+        .is_err());
+
+        let mut drbg = MarsCtrDrbg::new(TestEntropy::new(&[&entropy]), no_df(), &[], &[]).unwrap();
+        let mut output = vec![0u8; MAX_REQUEST_SIZE_BYTES + 1];
+        assert_eq!(
+            Err(Error::RequestTooLarge),
+            drbg.generate(&mut output, &[], false)
+        );
+        assert_eq!(
+            Err(Error::InputTooLong),
+            drbg.generate(&mut output[..16], &[0u8; SEED_SIZE_BYTES + 1], false)
+        );
+        assert_eq!(
+            Err(Error::InputTooLong),
+            drbg.reseed(&[0u8; SEED_SIZE_BYTES + 1])
+        );
+
+        // With the derivation function, inputs can have any length.
+        let mut drbg = MarsCtrDrbg::new(
+            TestEntropy::new(&[&entropy]),
+            Config::default(),
+            b"nonce",
+            &[0u8; 100],
+        )
+        .unwrap();
+        assert!(drbg.generate(&mut output[..16], &[0u8; 100], false).is_ok());
+    }
+
+    #[test]
+    fn aes_cavp_vectors() {
+        // NIST CAVP drbgvectors_no_reseed, CTR_DRBG.rsp, COUNT = 0 of
+        // [AES-256 no df] and [AES-256 use df], without prediction
+        // resistance: the generator is instantiated, then generates twice,
+        // and the second output is returned.
+        let vectors = [
+            (
+                false,
+                "df5d73faa468649edda33b5cca79b0b05600419ccb7a879ddfec9db32ee494e5\
+                 531b51de16a30f769262474c73bec010",
+                "",
+                "d1c07cd95af8a7f11012c84ce48bb8cb87189e99d40fccb1771c619bdf82ab22\
+                 80b1dc2f2581f39164f7ac0c510494b3a43c41b7db17514c87b107ae793e01c5",
+            ),
+            (
+                true,
+                "36401940fa8b1fba91a1661f211d78a0b9389a74e5bccfece8d766af1a6d3b14",
+                "496f25b0f1301b4f501be30380a137eb",
+                "5862eb38bd558dd978a696e6df164782ddd887e7e9a6c9f3f1fbafb78941b535\
+                 a64912dfd224c6dc7454e5250b3d97165e16260c2faf1cc7735cb75fb4f07e1d",
+            ),
+        ];
+
+        for (derivation_function, entropy, nonce, returned_bits) in vectors.iter() {
+            let config = Config {
+                derivation_function: *derivation_function,
+                ..Config::default()
+            };
+            let mut drbg = CtrDrbg::with_cipher(
+                Aes::new,
+                TestEntropy::new(&[&hex(entropy)]),
+                config,
+                &hex(nonce),
+                &[],
+            )
+            .unwrap();
+
+            let mut output = [0u8; 64];
+            drbg.generate(&mut output, &[], false).unwrap();
+            drbg.generate(&mut output, &[], false).unwrap();
+            assert_eq!(hex(returned_bits), output.to_vec());
+        }
+    }
+
+    #[test]
+    fn aes_vectors() {
+        // The CAVP vectors above have no personalization string, additional
+        // input or reseed: cross-check those against the outputs of the
+        // OpenSSL AES-256 CTR_DRBG with the same inputs.
+        let entropy: Vec<u8> = (0..48).collect();
+        let nonce: Vec<u8> = (100..116).collect();
+        let reseed_entropy: Vec<u8> = (200..248).collect();
+
+        let mut drbg = CtrDrbg::with_cipher(
+            Aes::new,
+            TestEntropy::new(&[&entropy, &reseed_entropy]),
+            Config::default(),
+            &nonce,
+            b"personalization",
+        )
+        .unwrap();
+        let mut output = [0u8; 64];
+        drbg.generate(&mut output, &[], false).unwrap();
+        assert_eq!(
+            hex(
+                "26523becbeabdc711da72aae70eb994f98cbe5a7707b32a363258d70233f0ea5\
+                 15379c7379af32997ac1264d612e9f8e0c853f24e2eeceddeef1d6c518e82c74"
+            ),
+            output.to_vec()
+        );
+        drbg.generate(&mut output[..32], b"additional", false)
+            .unwrap();
+        assert_eq!(
+            hex("d1965e9e5fdb09787ab5deedebf29bad459cc7b6c4c87c3f93a78cca9077def1"),
+            output[..32].to_vec()
+        );
+
+        let mut drbg = CtrDrbg::with_cipher(
+            Aes::new,
+            TestEntropy::new(&[&entropy, &reseed_entropy]),
+            no_df(),
+            &nonce,
+            b"personalization",
+        )
+        .unwrap();
+        drbg.generate(&mut output, &[], false).unwrap();
+        assert_eq!(
+            hex(
+                "ffdfec9d8d63e33c0bc7648eae72c6099e9f5e9eed0fad474da444192a348a35\
+                 b0ee7285dd6b5bcbbf76f827382349582b1408cca35639397a8da93e98c521b3"
+            ),
+            output.to_vec()
+        );
+        drbg.generate(&mut output[..32], b"additional", false)
+            .unwrap();
+        assert_eq!(
+            hex("2ec0614bfb2a2ec46295f0ba6e49b4d7c4ee12368ffff585f4a6ad00abf1877b"),
+            output[..32].to_vec()
+        );
+    }
+
+    #[test]
+    fn aes_reseed_vectors() {
+        // Outputs of the OpenSSL AES-256 CTR_DRBG with the same inputs.
+        let entropy: Vec<u8> = (0..48).collect();
+        let nonce: Vec<u8> = (100..116).collect();
+        let reseed_entropy: Vec<u8> = (200..248).collect();
+        let mut output = [0u8; 32];
+
+        let mut drbg = CtrDrbg::with_cipher(
+            Aes::new,
+            TestEntropy::new(&[&entropy, &reseed_entropy]),
+            Config::default(),
+            &nonce,
+            b"personalization",
+        )
+        .unwrap();
+        drbg.reseed(b"reseed").unwrap();
+        drbg.generate(&mut output, &[], false).unwrap();
+        assert_eq!(
+            hex("7f231f68d60b9b72fbfb250674f7c0e1448706c4b79422c6cdfe0c182c2e5efd"),
+            output.to_vec()
+        );
+
+        let mut drbg = CtrDrbg::with_cipher(
+            Aes::new,
+            TestEntropy::new(&[&entropy, &reseed_entropy]),
+            no_df(),
+            &nonce,
+            b"personalization",
+        )
+        .unwrap();
+        drbg.reseed(b"reseed").unwrap();
+        drbg.generate(&mut output, &[], false).unwrap();
+        assert_eq!(
+            hex("2b6ef83f1573b61e840924066e3a2d006d7bbf530824b2ba9ee0e2b7c306db43"),
+            output.to_vec()
+        );
+    }
+
+    #[test]
+    fn fill_bytes_splits_requests() {
+        let mut drbg = MarsCtrDrbg::new(rand::OsEntropy, Config::default(), b"nonce", &[]).unwrap();
+        let mut output = vec![0u8; MAX_REQUEST_SIZE_BYTES + 16];
+        drbg.fill_bytes(&mut output);
+        assert_eq!(3, drbg.reseed_counter);
+        assert_ne!([0u8; 16], output[MAX_REQUEST_SIZE_BYTES..]);
+    }
+}
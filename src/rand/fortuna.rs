@@ -0,0 +1,450 @@
+//! # Fortuna
+//!
+//! `fortuna` implements the Fortuna random number generator of Ferguson
+//! and Schneier, meant for long-running services that continuously collect
+//! entropy.
+//! Entropy events are spread over 32 pools.
+//! Pool i only contributes to every 2^i-th reseed, so even if an attacker
+//! controls most entropy sources, some pool eventually accumulates enough
+//! entropy to recover from a state compromise.
+//! The generator runs a cipher in counter mode, and replaces its key after
+//! every request.
+//! As in the design, pools are condensed with SHA-256d (SHA-256 applied
+//! twice), which is collision-resistant: a source cannot cancel out the
+//! events of other sources.
+
+use std::error;
+use std::fmt;
+use std::mem;
+use std::time::{Duration, Instant};
+
+use super::ctr_drbg::new_mars;
+use super::Rng;
+use cipher::block::BlockCipher;
+use cipher::stream::salsa20::key_expansion;
+use hash::sha2::Sha256;
+use hash::Hasher;
+
+/// The number of entropy pools.
+pub const POOL_COUNT: usize = 32;
+/// The amount of data pool 0 must receive before a reseed (in bytes).
+pub const MIN_POOL_SIZE_BYTES: usize = 64;
+/// The maximum size of an entropy event (in bytes).
+pub const MAX_EVENT_SIZE_BYTES: usize = 32;
+/// The maximum number of bytes returned by a single request.
+pub const MAX_REQUEST_SIZE_BYTES: usize = 1 << 20;
+/// The size of the generator key (in bytes).
+pub const KEY_SIZE_BYTES: usize = 32;
+
+/// The default minimum time between reseeds.
+const MIN_RESEED_INTERVAL_MS: u64 = 100;
+
+/// Errors returned by Fortuna.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The generator has not been seeded yet.
+    NotSeeded,
+    /// More than `MAX_REQUEST_SIZE_BYTES` were requested.
+    RequestTooLarge,
+    /// The event is empty or larger than `MAX_EVENT_SIZE_BYTES`.
+    InvalidEvent,
+    /// The pool does not exist.
+    InvalidPool,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NotSeeded => write!(f, "generator is not seeded"),
+            Error::RequestTooLarge => write!(f, "too many bytes requested"),
+            Error::InvalidEvent => write!(f, "invalid event size"),
+            Error::InvalidPool => write!(f, "invalid pool"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// The cipher used by the generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratorCipher {
+    /// Mars in counter mode (16-byte blocks).
+    Mars,
+    /// The Salsa20 key stream (64-byte blocks).
+    Salsa20,
+}
+
+/// The generator: a cipher key and a 128-bit counter.
+struct Generator {
+    cipher: GeneratorCipher,
+    key: [u8; KEY_SIZE_BYTES],
+    counter: u128,
+}
+
+/// An entropy pool, hashed with SHA-256 as events are added.
+struct Pool {
+    hasher: Sha256,
+    size: usize,
+}
+
+/// A Fortuna random number generator.
+pub struct Fortuna {
+    generator: Generator,
+    pools: Vec<Pool>,
+    reseed_count: u64,
+    last_reseed: Option<Instant>,
+    min_reseed_interval: Duration,
+}
+
+// Public methods.
+impl Fortuna {
+    /// new creates an unseeded Fortuna generator with the given cipher.
+    /// Random data is only available once enough entropy events have been
+    /// added to the first pool.
+    pub fn new(cipher: GeneratorCipher) -> Fortuna {
+        Fortuna {
+            generator: Generator {
+                cipher,
+                key: [0u8; KEY_SIZE_BYTES],
+                counter: 0,
+            },
+            pools: (0..POOL_COUNT).map(|_| Pool::new()).collect(),
+            reseed_count: 0,
+            last_reseed: None,
+            min_reseed_interval: Duration::from_millis(MIN_RESEED_INTERVAL_MS),
+        }
+    }
+
+    /// add_random_event adds an entropy event from the given source to the
+    /// given pool.
+    /// Sources should distribute their events over the pools in turn.
+    pub fn add_random_event(&mut self, source: u8, pool: usize, data: &[u8]) -> Result<(), Error> {
+        if data.is_empty() || data.len() > MAX_EVENT_SIZE_BYTES {
+            return Err(Error::InvalidEvent);
+        }
+        if pool >= POOL_COUNT {
+            return Err(Error::InvalidPool);
+        }
+
+        let p = &mut self.pools[pool];
+        p.hasher.update(&[source, data.len() as u8]);
+        p.hasher.update(data);
+        p.size += data.len() + 2;
+
+        Ok(())
+    }
+
+    /// random_data fills the destination with random bytes.
+    /// The generator first reseeds from the pools if pool 0 contains enough
+    /// data and the last reseed is old enough.
+    pub fn random_data(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        if dest.len() > MAX_REQUEST_SIZE_BYTES {
+            return Err(Error::RequestTooLarge);
+        }
+
+        if self.pools[0].size >= MIN_POOL_SIZE_BYTES && self.reseed_allowed() {
+            self.reseed();
+        }
+
+        if self.reseed_count == 0 {
+            return Err(Error::NotSeeded);
+        }
+
+        self.generator.pseudo_random_data(dest);
+        Ok(())
+    }
+
+    /// set_min_reseed_interval changes the minimum time between reseeds
+    /// (100ms by default), which prevents an attacker from flooding pool 0
+    /// to exhaust the other pools.
+    pub fn set_min_reseed_interval(&mut self, interval: Duration) {
+        self.min_reseed_interval = interval;
+    }
+
+    /// reseed_count returns the number of reseeds so far.
+    pub fn reseed_count(&self) -> u64 {
+        self.reseed_count
+    }
+}
+
+// Private methods.
+impl Fortuna {
+    fn reseed_allowed(&self) -> bool {
+        match self.last_reseed {
+            Some(t) => t.elapsed() >= self.min_reseed_interval,
+            None => true,
+        }
+    }
+
+    /// reseed condenses pool i into the seed if 2^i divides the reseed
+    /// count, and empties it.
+    fn reseed(&mut self) {
+        self.reseed_count += 1;
+        self.last_reseed = Some(Instant::now());
+
+        let mut seed = Vec::new();
+        for i in 0..POOL_COUNT {
+            if !self.reseed_count.is_multiple_of(1 << i) {
+                break;
+            }
+
+            let pool = mem::replace(&mut self.pools[i], Pool::new());
+            seed.extend_from_slice(&Sha256::digest(&pool.hasher.finalize()));
+        }
+
+        self.generator.reseed(&seed);
+    }
+}
+
+impl Rng for Fortuna {
+    /// fill_bytes panics if the generator has not been seeded.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(MAX_REQUEST_SIZE_BYTES) {
+            if self.random_data(chunk).is_err() {
+                panic!("Generator is not seeded.");
+            }
+        }
+    }
+}
+
+impl Pool {
+    fn new() -> Pool {
+        Pool {
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+}
+
+impl Generator {
+    /// reseed derives a new key from the current key and the seed, with
+    /// SHA-256d.
+    fn reseed(&mut self, seed: &[u8]) {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.key);
+        hasher.update(seed);
+        let derived = Sha256::digest(&hasher.finalize());
+        self.key.copy_from_slice(&derived);
+        self.counter = self.counter.wrapping_add(1);
+    }
+
+    /// pseudo_random_data fills the destination with the key stream, and
+    /// then replaces the key so that the output cannot be recomputed.
+    fn pseudo_random_data(&mut self, dest: &mut [u8]) {
+        let stream = self.generate_blocks(dest.len());
+        dest.copy_from_slice(&stream[..dest.len()]);
+
+        let key = self.generate_blocks(KEY_SIZE_BYTES);
+        self.key.copy_from_slice(&key[..KEY_SIZE_BYTES]);
+    }
+
+    /// generate_blocks returns at least len bytes of key stream.
+    fn generate_blocks(&mut self, len: usize) -> Vec<u8> {
+        let mut res = Vec::with_capacity(len + 64);
+
+        match self.cipher {
+            GeneratorCipher::Mars => {
+                let cipher = new_mars(&self.key);
+                while res.len() < len {
+                    res.extend_from_slice(&cipher.encrypt_block(&self.counter.to_le_bytes()));
+                    self.counter = self.counter.wrapping_add(1);
+                }
+            }
+            GeneratorCipher::Salsa20 => {
+                let mut k0 = [0u8; 16];
+                let mut k1 = [0u8; 16];
+                k0.copy_from_slice(&self.key[..16]);
+                k1.copy_from_slice(&self.key[16..]);
+                while res.len() < len {
+                    res.extend_from_slice(&key_expansion(k0, k1, self.counter.to_le_bytes()));
+                    self.counter = self.counter.wrapping_add(1);
+                }
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// seeded returns a generator whose pool 0 is ready for a reseed.
+    fn seeded(cipher: GeneratorCipher) -> Fortuna {
+        let mut fortuna = Fortuna::new(cipher);
+        fortuna.set_min_reseed_interval(Duration::from_secs(0));
+        for i in 0..2 {
+            fortuna.add_random_event(0, 0, &[i; 32]).unwrap();
+        }
+        fortuna
+    }
+
+    #[test]
+    fn not_seeded() {
+        let mut fortuna = Fortuna::new(GeneratorCipher::Mars);
+        let mut out = [0u8; 16];
+        assert_eq!(Err(Error::NotSeeded), fortuna.random_data(&mut out));
+
+        // A single event is not enough to fill pool 0.
+        fortuna.add_random_event(0, 0, &[1u8; 32]).unwrap();
+        assert_eq!(Err(Error::NotSeeded), fortuna.random_data(&mut out));
+
+        fortuna.add_random_event(0, 0, &[2u8; 32]).unwrap();
+        assert!(fortuna.random_data(&mut out).is_ok());
+        assert_eq!(1, fortuna.reseed_count());
+    }
+
+    #[test]
+    fn invalid_events() {
+        let mut fortuna = Fortuna::new(GeneratorCipher::Mars);
+        assert_eq!(
+            Err(Error::InvalidEvent),
+            fortuna.add_random_event(0, 0, &[])
+        );
+        assert_eq!(
+            Err(Error::InvalidEvent),
+            fortuna.add_random_event(0, 0, &[0u8; MAX_EVENT_SIZE_BYTES + 1])
+        );
+        assert_eq!(
+            Err(Error::InvalidPool),
+            fortuna.add_random_event(0, POOL_COUNT, &[0u8; 4])
+        );
+    }
+
+    #[test]
+    fn deterministic_with_injected_events() {
+        for cipher in [GeneratorCipher::Mars, GeneratorCipher::Salsa20].iter() {
+            let mut out1 = [0u8; 100];
+            let mut out2 = [0u8; 100];
+            seeded(*cipher).random_data(&mut out1).unwrap();
+            seeded(*cipher).random_data(&mut out2).unwrap();
+            assert_eq!(out1[..], out2[..]);
+
+            // A different event source changes the output.
+            let mut fortuna = Fortuna::new(*cipher);
+            fortuna.set_min_reseed_interval(Duration::from_secs(0));
+            fortuna.add_random_event(0, 0, &[0u8; 32]).unwrap();
+            fortuna.add_random_event(1, 0, &[1u8; 32]).unwrap();
+            fortuna.random_data(&mut out2).unwrap();
+            assert_ne!(out1[..], out2[..]);
+        }
+    }
+
+    #[test]
+    fn pools_are_condensed_with_sha256d() {
+        let mut fortuna = seeded(GeneratorCipher::Mars);
+        fortuna.reseed();
+
+        // Each event is prefixed with its source and length.
+        let mut events = Vec::new();
+        for i in 0..2 {
+            events.extend_from_slice(&[0, 32]);
+            events.extend_from_slice(&[i; 32]);
+        }
+        let seed = Sha256::digest(&Sha256::digest(&events));
+        let key_and_seed = [&[0u8; KEY_SIZE_BYTES][..], &seed].concat();
+        let key = Sha256::digest(&Sha256::digest(&key_and_seed));
+
+        assert_eq!(key[..], fortuna.generator.key[..]);
+    }
+
+    #[test]
+    fn rekeys_after_every_request() {
+        let mut fortuna = seeded(GeneratorCipher::Mars);
+        let mut out = [0u8; 16];
+        fortuna.random_data(&mut out).unwrap();
+
+        // The output is the counter mode key stream from the counter, and
+        // the next two blocks become the new key.
+        let key = fortuna.generator.key;
+        let counter = fortuna.generator.counter;
+        let cipher = new_mars(&key);
+        let block = |i: u128| cipher.encrypt_block(&counter.wrapping_add(i).to_le_bytes());
+
+        fortuna.random_data(&mut out).unwrap();
+        assert_eq!(block(0), out);
+        assert_eq!(block(1)[..], fortuna.generator.key[..16]);
+        assert_eq!(block(2)[..], fortuna.generator.key[16..]);
+        assert_eq!(counter + 3, fortuna.generator.counter);
+    }
+
+    #[test]
+    fn salsa20_generator() {
+        let mut fortuna = seeded(GeneratorCipher::Salsa20);
+        let mut out = [0u8; 16];
+        fortuna.random_data(&mut out).unwrap();
+
+        let key = fortuna.generator.key;
+        let counter = fortuna.generator.counter;
+        let mut k0 = [0u8; 16];
+        let mut k1 = [0u8; 16];
+        k0.copy_from_slice(&key[..16]);
+        k1.copy_from_slice(&key[16..]);
+
+        let mut out = [0u8; 80];
+        fortuna.random_data(&mut out).unwrap();
+        assert_eq!(key_expansion(k0, k1, counter.to_le_bytes())[..], out[..64]);
+        assert_eq!(
+            key_expansion(k0, k1, (counter + 1).to_le_bytes())[..16],
+            out[64..]
+        );
+        assert_eq!(
+            key_expansion(k0, k1, (counter + 2).to_le_bytes())[..32],
+            fortuna.generator.key[..]
+        );
+    }
+
+    #[test]
+    fn reseed_schedule() {
+        let mut fortuna = Fortuna::new(GeneratorCipher::Mars);
+        fortuna.set_min_reseed_interval(Duration::from_secs(0));
+        let mut out = [0u8; 16];
+
+        for reseed in 1..9u64 {
+            for pool in 0..POOL_COUNT {
+                fortuna
+                    .add_random_event(3, pool, &[reseed as u8; 32])
+                    .unwrap();
+                fortuna
+                    .add_random_event(3, pool, &[reseed as u8; 32])
+                    .unwrap();
+            }
+            fortuna.random_data(&mut out).unwrap();
+            assert_eq!(reseed, fortuna.reseed_count());
+
+            // Pool i is used (and emptied) if 2^i divides the reseed count.
+            // Each round adds two 34-byte events to every pool.
+            for i in 0..POOL_COUNT {
+                let rounds = (reseed % (1 << i)) as usize;
+                assert_eq!(68 * rounds, fortuna.pools[i].size, "pool {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn min_reseed_interval() {
+        let mut fortuna = seeded(GeneratorCipher::Mars);
+        fortuna.set_min_reseed_interval(Duration::from_secs(3600));
+        let mut out = [0u8; 16];
+
+        fortuna.random_data(&mut out).unwrap();
+        assert_eq!(1, fortuna.reseed_count());
+
+        // Pool 0 is full again, but the last reseed is too recent.
+        for i in 0..2 {
+            fortuna.add_random_event(0, 0, &[i; 32]).unwrap();
+        }
+        fortuna.random_data(&mut out).unwrap();
+        assert_eq!(1, fortuna.reseed_count());
+    }
+
+    #[test]
+    fn request_too_large() {
+        let mut fortuna = seeded(GeneratorCipher::Salsa20);
+        let mut out = vec![0u8; MAX_REQUEST_SIZE_BYTES + 1];
+        assert_eq!(Err(Error::RequestTooLarge), fortuna.random_data(&mut out));
+        fortuna.fill_bytes(&mut out);
+        assert_eq!(1, fortuna.reseed_count());
+    }
+}
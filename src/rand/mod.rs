@@ -0,0 +1,171 @@
+//! # Random number generators
+//!
+//! `rand` contains a collection of cryptographically secure random number
+//! generators.
+//! Generators are seeded from the operating system, and then expand this
+//! seed with a cipher: their output is unpredictable as long as the seed is
+//! secret.
+
+pub mod ctr_drbg;
+pub mod fortuna;
+pub mod salsa20;
+pub mod trivium;
+
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+
+/// The size of a seed (in bytes).
+pub const SEED_SIZE_BYTES: usize = 32;
+
+/// A seed.
+pub type Seed = [u8; SEED_SIZE_BYTES];
+
+/// Errors returned when seeding generators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The operating system randomness could not be read.
+    OsRandomUnavailable,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::OsRandomUnavailable => write!(f, "operating system randomness is unavailable"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A random number generator.
+/// Implementations only need to provide fill_bytes.
+pub trait Rng {
+    /// fill_bytes fills the destination with random bytes.
+    fn fill_bytes(&mut self, dest: &mut [u8]);
+
+    /// next_u32 returns a random u32.
+    fn next_u32(&mut self) -> u32 {
+        let mut b = [0u8; 4];
+        self.fill_bytes(&mut b);
+        u32::from_le_bytes(b)
+    }
+
+    /// next_u64 returns a random u64.
+    fn next_u64(&mut self) -> u64 {
+        let mut b = [0u8; 8];
+        self.fill_bytes(&mut b);
+        u64::from_le_bytes(b)
+    }
+
+    /// range returns a random number uniformly distributed in [low, high).
+    /// A plain modulo would favor small values, so outputs falling in the
+    /// incomplete last interval are rejected.
+    /// It panics if the range is empty.
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        if low >= high {
+            panic!("Range must not be empty.");
+        }
+
+        let span = high - low;
+        // 2^64 mod span: the values below this threshold are rejected, so
+        // that every result has the same number of preimages.
+        let threshold = span.wrapping_neg() % span;
+        loop {
+            let x = self.next_u64();
+            if x >= threshold {
+                return low + x % span;
+            }
+        }
+    }
+}
+
+/// A source of entropy used to seed and reseed deterministic generators.
+pub trait EntropySource {
+    /// fill_entropy fills the destination with full-entropy bytes.
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error>;
+}
+
+/// The operating system entropy source.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsEntropy;
+
+impl EntropySource for OsEntropy {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        os_random(dest)
+    }
+}
+
+/// os_random fills the destination with random bytes from the operating
+/// system (/dev/urandom).
+pub fn os_random(dest: &mut [u8]) -> Result<(), Error> {
+    File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(dest))
+        .map_err(|_| Error::OsRandomUnavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A generator returning a fixed sequence of u64.
+    struct Sequence {
+        values: Vec<u64>,
+    }
+
+    impl Rng for Sequence {
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            let v = self.values.remove(0).to_le_bytes();
+            dest.copy_from_slice(&v[..dest.len()]);
+        }
+    }
+
+    #[test]
+    fn range_rejects_biased_values() {
+        // 2^64 mod 3 = 1, so 0 must be rejected.
+        let mut rng = Sequence {
+            values: vec![0, 1, 2, u64::MAX],
+        };
+
+        assert_eq!(11, rng.range(10, 13));
+        assert_eq!(12, rng.range(10, 13));
+        assert_eq!(10, rng.range(10, 13));
+    }
+
+    #[test]
+    fn range_full_width() {
+        let mut rng = Sequence {
+            values: vec![0, u64::MAX - 1, u64::MAX],
+        };
+
+        // 2^64 mod (2^64 - 1) = 1, so 0 must be rejected.
+        assert_eq!(u64::MAX - 1, rng.range(0, u64::MAX));
+        assert_eq!(0, rng.range(0, u64::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn range_empty() {
+        let mut rng = Sequence { values: vec![0] };
+        rng.range(3, 3);
+    }
+
+    #[test]
+    fn next_u32_little_endian() {
+        let mut rng = Sequence {
+            values: vec![0x0102030405060708],
+        };
+
+        assert_eq!(0x05060708, rng.next_u32());
+    }
+
+    #[test]
+    fn os_random_fills_bytes() {
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+        os_random(&mut a).unwrap();
+        os_random(&mut b).unwrap();
+        assert_ne!(a, b);
+    }
+}
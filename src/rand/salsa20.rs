@@ -0,0 +1,196 @@
+//! # Salsa20 random number generator
+//!
+//! `salsa20` implements a random number generator based on the Salsa20
+//! key stream, with fast key erasure: each refill generates a few key
+//! stream blocks, immediately replaces the key with the first 32 bytes, and
+//! buffers the rest.
+//! Output bytes are erased from the buffer as soon as they are returned, so
+//! an attacker who compromises the generator state cannot recover past
+//! outputs.
+
+use super::{os_random, Error, Rng, Seed, SEED_SIZE_BYTES};
+use cipher::stream::salsa20::key_expansion;
+
+/// The number of key stream blocks generated on each refill.
+const BLOCKS_PER_REFILL: usize = 8;
+/// The number of output bytes available after each refill.
+const BUFFER_SIZE_BYTES: usize = 64 * BLOCKS_PER_REFILL - SEED_SIZE_BYTES;
+
+/// A Salsa20 random number generator.
+pub struct Salsa20Rng {
+    key: Seed,
+    buffer: [u8; BUFFER_SIZE_BYTES],
+    position: usize,
+}
+
+// Public methods.
+impl Salsa20Rng {
+    /// new creates a generator seeded from the operating system.
+    pub fn new() -> Result<Salsa20Rng, Error> {
+        let mut seed = [0u8; SEED_SIZE_BYTES];
+        os_random(&mut seed)?;
+        Ok(Salsa20Rng::from_seed(seed))
+    }
+
+    /// from_seed creates a generator with the given seed.
+    /// The same seed always produces the same output.
+    pub fn from_seed(seed: Seed) -> Salsa20Rng {
+        Salsa20Rng {
+            key: seed,
+            buffer: [0u8; BUFFER_SIZE_BYTES],
+            position: BUFFER_SIZE_BYTES,
+        }
+    }
+
+    /// reseed mixes a new seed into the generator.
+    /// The next key is derived from both the current key and the seed, so
+    /// the output is unpredictable if either of them is secret.
+    /// Buffered output is discarded.
+    pub fn reseed(&mut self, seed: &Seed) {
+        self.refill();
+        for (k, s) in self.key.iter_mut().zip(seed.iter()) {
+            *k ^= s;
+        }
+
+        self.buffer = [0u8; BUFFER_SIZE_BYTES];
+        self.position = BUFFER_SIZE_BYTES;
+    }
+
+    /// reseed_from_os mixes a new seed from the operating system into the
+    /// generator.
+    pub fn reseed_from_os(&mut self) -> Result<(), Error> {
+        let mut seed = [0u8; SEED_SIZE_BYTES];
+        os_random(&mut seed)?;
+        self.reseed(&seed);
+        Ok(())
+    }
+}
+
+// Private methods.
+impl Salsa20Rng {
+    /// refill generates new key stream blocks, replaces the key and fills
+    /// the buffer.
+    fn refill(&mut self) {
+        let mut k0 = [0u8; 16];
+        let mut k1 = [0u8; 16];
+        k0.copy_from_slice(&self.key[..16]);
+        k1.copy_from_slice(&self.key[16..]);
+
+        // Since every refill uses a new key, the nonce is always zero and
+        // the counter starts at zero.
+        let mut stream = [0u8; 64 * BLOCKS_PER_REFILL];
+        for i in 0..BLOCKS_PER_REFILL {
+            let mut n = [0u8; 16];
+            n[8] = i as u8;
+            stream[64 * i..64 * (i + 1)].copy_from_slice(&key_expansion(k0, k1, n));
+        }
+
+        self.key.copy_from_slice(&stream[..SEED_SIZE_BYTES]);
+        self.buffer.copy_from_slice(&stream[SEED_SIZE_BYTES..]);
+        self.position = 0;
+    }
+}
+
+impl Rng for Salsa20Rng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for b in dest.iter_mut() {
+            if self.position == BUFFER_SIZE_BYTES {
+                self.refill();
+            }
+
+            *b = self.buffer[self.position];
+            self.buffer[self.position] = 0;
+            self.position += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_SEED: Seed = [42u8; SEED_SIZE_BYTES];
+
+    fn block(key: &Seed, counter: u8) -> [u8; 64] {
+        let mut k0 = [0u8; 16];
+        let mut k1 = [0u8; 16];
+        k0.copy_from_slice(&key[..16]);
+        k1.copy_from_slice(&key[16..]);
+        let mut n = [0u8; 16];
+        n[8] = counter;
+        key_expansion(k0, k1, n)
+    }
+
+    #[test]
+    fn output_is_key_stream() {
+        let mut rng = Salsa20Rng::from_seed(TEST_SEED);
+        let mut out = [0u8; 64];
+        rng.fill_bytes(&mut out);
+
+        let first = block(&TEST_SEED, 0);
+        let second = block(&TEST_SEED, 1);
+        assert_eq!(&first[32..], &out[..32]);
+        assert_eq!(&second[..32], &out[32..]);
+    }
+
+    #[test]
+    fn key_and_output_are_erased() {
+        let mut rng = Salsa20Rng::from_seed(TEST_SEED);
+        let mut out = [0u8; 10];
+        rng.fill_bytes(&mut out);
+
+        assert_eq!(&block(&TEST_SEED, 0)[..32], &rng.key);
+        assert_eq!([0u8; 10], rng.buffer[..10]);
+        assert_ne!([0u8; 10], rng.buffer[10..20]);
+    }
+
+    #[test]
+    fn deterministic_across_refills() {
+        let mut rng = Salsa20Rng::from_seed(TEST_SEED);
+        let mut all = vec![0u8; 2000];
+        rng.fill_bytes(&mut all);
+
+        let mut rng = Salsa20Rng::from_seed(TEST_SEED);
+        let mut chunks = Vec::new();
+        for size in [1, 7, 480, 500, 1012].iter() {
+            let mut chunk = vec![0u8; *size];
+            rng.fill_bytes(&mut chunk);
+            chunks.extend(chunk);
+        }
+
+        assert_eq!(all, chunks);
+    }
+
+    #[test]
+    fn reseed() {
+        let mut rng1 = Salsa20Rng::from_seed(TEST_SEED);
+        let mut rng2 = Salsa20Rng::from_seed(TEST_SEED);
+        rng2.reseed(&[24u8; SEED_SIZE_BYTES]);
+        assert_ne!(rng1.next_u64(), rng2.next_u64());
+
+        let mut rng3 = Salsa20Rng::from_seed(TEST_SEED);
+        rng3.reseed(&[0u8; SEED_SIZE_BYTES]);
+        assert_ne!(rng1.next_u64(), rng3.next_u64());
+        assert!(rng3.reseed_from_os().is_ok());
+    }
+
+    #[test]
+    fn seeded_from_os() {
+        let mut rng1 = Salsa20Rng::new().unwrap();
+        let mut rng2 = Salsa20Rng::new().unwrap();
+        assert_ne!(rng1.next_u64(), rng2.next_u64());
+    }
+
+    #[test]
+    fn range_is_uniform() {
+        let mut rng = Salsa20Rng::from_seed(TEST_SEED);
+        let mut counts = [0u32; 6];
+        for _ in 0..6000 {
+            counts[rng.range(0, 6) as usize] += 1;
+        }
+
+        for c in counts.iter() {
+            assert!(*c > 850 && *c < 1150, "counts = {:?}", counts);
+        }
+    }
+}
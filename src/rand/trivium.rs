@@ -0,0 +1,150 @@
+//! # Trivium random number generator
+//!
+//! `trivium` implements a deterministic random bit generator based on the
+//! Trivium key stream, for reproducible simulations: the same seed always
+//! produces the same output, and the generator state can be saved and
+//! restored at any point.
+//! Its 80-bit key is too short for long-term secrets, so it should not be
+//! used to generate keys (use the Salsa20 generator instead).
+//!
+//! With the `rand_core` feature, the generator also implements the
+//! `RngCore` and `SeedableRng` traits of the `rand_core` crate.
+
+use super::Rng;
+use cipher::stream::trivium::{Trivium, IV_SIZE_BYTES, KEY_SIZE_BYTES, STATE_SIZE_BYTES};
+
+/// The size of a seed (in bytes): a Trivium key followed by an IV.
+pub const SEED_SIZE_BYTES: usize = KEY_SIZE_BYTES + IV_SIZE_BYTES;
+/// The size of a snapshot of the generator state (in bytes).
+pub const SNAPSHOT_SIZE_BYTES: usize = STATE_SIZE_BYTES;
+
+/// A seed.
+pub type Seed = [u8; SEED_SIZE_BYTES];
+/// A snapshot of the generator state.
+pub type Snapshot = [u8; SNAPSHOT_SIZE_BYTES];
+
+/// A Trivium random number generator.
+#[derive(Debug)]
+pub struct TriviumRng {
+    cipher: Trivium,
+}
+
+// Public methods.
+impl TriviumRng {
+    /// from_seed creates a generator with the given seed.
+    pub fn from_seed(seed: Seed) -> TriviumRng {
+        let mut key = [0u8; KEY_SIZE_BYTES];
+        let mut iv = [0u8; IV_SIZE_BYTES];
+        key.copy_from_slice(&seed[..KEY_SIZE_BYTES]);
+        iv.copy_from_slice(&seed[KEY_SIZE_BYTES..]);
+
+        TriviumRng {
+            cipher: Trivium::new(iv, key),
+        }
+    }
+
+    /// snapshot returns the current state of the generator.
+    /// The snapshot contains the seed, so it must be kept as secret as the
+    /// seed itself.
+    pub fn snapshot(&self) -> Snapshot {
+        self.cipher.state()
+    }
+
+    /// restore creates a generator that continues from a snapshot.
+    pub fn restore(snapshot: &Snapshot) -> TriviumRng {
+        TriviumRng {
+            cipher: Trivium::from_state(snapshot),
+        }
+    }
+}
+
+impl Rng for TriviumRng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for b in dest.iter_mut() {
+            *b = self.cipher.clock_byte();
+        }
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl ::rand_core::RngCore for TriviumRng {
+    fn next_u32(&mut self) -> u32 {
+        Rng::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        Rng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        Rng::fill_bytes(self, dest)
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl ::rand_core::SeedableRng for TriviumRng {
+    type Seed = Seed;
+
+    fn from_seed(seed: Seed) -> TriviumRng {
+        TriviumRng::from_seed(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_SEED: Seed = [42u8; SEED_SIZE_BYTES];
+
+    #[test]
+    fn output_is_key_stream() {
+        let mut rng = TriviumRng::from_seed(TEST_SEED);
+        let mut out = [0u8; 32];
+        rng.fill_bytes(&mut out);
+
+        let mut cipher = Trivium::new([42u8; IV_SIZE_BYTES], [42u8; KEY_SIZE_BYTES]);
+        assert_eq!(cipher.encrypt(&[0u8; 32]), out.to_vec());
+    }
+
+    #[test]
+    fn reproducible() {
+        let mut seed = TEST_SEED;
+        let mut rng1 = TriviumRng::from_seed(seed);
+        let mut rng2 = TriviumRng::from_seed(seed);
+        assert_eq!(rng1.next_u64(), rng2.next_u64());
+
+        seed[SEED_SIZE_BYTES - 1] ^= 1;
+        let mut rng3 = TriviumRng::from_seed(seed);
+        assert_ne!(rng1.next_u64(), rng3.next_u64());
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut rng = TriviumRng::from_seed(TEST_SEED);
+        rng.next_u64();
+
+        let snapshot = rng.snapshot();
+        let expected: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+
+        let mut restored = TriviumRng::restore(&snapshot);
+        let actual: Vec<u64> = (0..4).map(|_| restored.next_u64()).collect();
+        assert_eq!(expected, actual);
+        assert_eq!(rng.snapshot(), restored.snapshot());
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn rand_core_traits() {
+        use rand_core::{RngCore, SeedableRng};
+
+        let mut rng1 = <TriviumRng as SeedableRng>::from_seed(TEST_SEED);
+        let mut rng2 = TriviumRng::from_seed(TEST_SEED);
+        assert_eq!(RngCore::next_u64(&mut rng1), Rng::next_u64(&mut rng2));
+
+        let mut b1 = [0u8; 7];
+        let mut b2 = [0u8; 7];
+        RngCore::fill_bytes(&mut rng1, &mut b1);
+        Rng::fill_bytes(&mut rng2, &mut b2);
+        assert_eq!(b1, b2);
+    }
+}
@@ -0,0 +1,26 @@
+//! Helpers shared by the unit tests.
+
+use cipher::block::Block;
+
+/// hex decodes a hexadecimal string.
+pub(crate) fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// block decodes a hexadecimal string into a 16-byte block.
+pub(crate) fn block(s: &str) -> Block {
+    let mut b = [0u8; 16];
+    b.copy_from_slice(&hex(s));
+    b
+}
+
+/// words decodes a hexadecimal string into little-endian 32-bit words.
+pub(crate) fn words(s: &str) -> Vec<u32> {
+    hex(s)
+        .chunks(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}